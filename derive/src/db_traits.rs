@@ -2,7 +2,7 @@ use darling::{ast::Fields, FromDeriveInput, FromField, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
-use syn::{spanned::Spanned, Data, DataStruct, DeriveInput, Generics};
+use syn::{spanned::Spanned, Data, DataEnum, DataStruct, DeriveInput, Generics};
 
 use std::collections::HashSet;
 
@@ -11,6 +11,9 @@ use crate::find_meta_attrs;
 #[derive(Debug)]
 struct BinaryValueStruct {
     ident: Ident,
+    generics: Generics,
+    variants: Option<Vec<TaggedVariant>>,
+    fields: Option<Vec<VersionedField>>,
     attrs: BinaryValueAttrs,
 }
 
@@ -20,30 +23,63 @@ impl FromDeriveInput for BinaryValueStruct {
             .map(|meta| BinaryValueAttrs::from_nested_meta(&meta))
             .unwrap_or_else(|| Ok(BinaryValueAttrs::default()))?;
 
+        let variants = if attrs.codec == Codec::Tagged {
+            let variants = match &input.data {
+                Data::Enum(DataEnum { variants, .. }) => variants,
+                _ => {
+                    let e = "`tagged` codec can be only used with enums";
+                    return Err(darling::Error::unsupported_shape(e));
+                }
+            };
+            if variants.is_empty() {
+                let e = darling::Error::custom("Enum must have at least one variant");
+                return Err(e);
+            }
+            let variants = variants
+                .iter()
+                .map(|variant| TaggedVariant {
+                    ident: variant.ident.clone(),
+                    fields: variant.fields.clone(),
+                })
+                .collect();
+            Some(variants)
+        } else {
+            None
+        };
+
+        let fields = match &input.data {
+            Data::Struct(DataStruct { fields, .. }) => {
+                let fields: Fields<VersionedField> = Fields::try_from(fields)?;
+                Some(fields.fields)
+            }
+            _ => None,
+        };
+        attrs.validate_versions(fields.as_deref())?;
+
         Ok(Self {
             ident: input.ident.clone(),
+            generics: input.generics.clone(),
+            variants,
+            fields,
             attrs,
         })
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 enum Codec {
+    #[default]
     Bincode,
-}
-
-impl Default for Codec {
-    fn default() -> Self {
-        Codec::Bincode
-    }
+    Tagged,
 }
 
 impl FromMeta for Codec {
     fn from_string(value: &str) -> darling::Result<Self> {
         match value {
             "bincode" => Ok(Codec::Bincode),
+            "tagged" => Ok(Codec::Tagged),
             _ => {
-                let msg = format!("Unknown codec ({}). Use `bincode`", value);
+                let msg = format!("Unknown codec ({}). Use `bincode` or `tagged`", value);
                 Err(darling::Error::custom(msg))
             }
         }
@@ -54,6 +90,75 @@ impl FromMeta for Codec {
 struct BinaryValueAttrs {
     #[darling(default)]
     codec: Codec,
+    #[darling(default)]
+    version: Option<u8>,
+}
+
+impl BinaryValueAttrs {
+    /// Checks the `version` container attribute against the `since` attributes of `fields`,
+    /// which are `None` if the derive input is not a struct.
+    fn validate_versions(&self, fields: Option<&[VersionedField]>) -> darling::Result<()> {
+        let version = match self.version {
+            Some(version) => version,
+            None => {
+                let versioned_field = fields
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|field| field.since.is_some());
+                if let Some(field) = versioned_field {
+                    let e = "`since` requires the `version` container attribute";
+                    return Err(darling::Error::custom(e).with_span(&field.span));
+                }
+                return Ok(());
+            }
+        };
+
+        if self.codec != Codec::Bincode {
+            let e = "`version` can be only used with the `bincode` codec";
+            return Err(darling::Error::custom(e));
+        }
+        let fields = fields.ok_or_else(|| {
+            darling::Error::unsupported_shape("`version` can be only used with structs")
+        })?;
+        for field in fields {
+            let since = field.since.unwrap_or_default();
+            if since > version {
+                let msg = format!(
+                    "Field is added in version {}, which is newer than the struct version {}",
+                    since, version
+                );
+                return Err(darling::Error::custom(msg).with_span(&field.span));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, FromMeta)]
+struct BinaryValueFieldAttrs {
+    #[darling(default)]
+    since: Option<u8>,
+}
+
+/// Struct field encoded by the versioned `bincode` codec.
+#[derive(Debug)]
+struct VersionedField {
+    span: Span,
+    ident: Option<Ident>,
+    since: Option<u8>,
+}
+
+impl FromField for VersionedField {
+    fn from_field(field: &syn::Field) -> darling::Result<Self> {
+        let attrs = find_meta_attrs("binary_value", &field.attrs)
+            .map(|meta| BinaryValueFieldAttrs::from_nested_meta(&meta))
+            .unwrap_or_else(|| Ok(BinaryValueFieldAttrs::default()))?;
+        Ok(Self {
+            span: field.span(),
+            ident: field.ident.clone(),
+            since: attrs.since,
+        })
+    }
 }
 
 impl BinaryValueStruct {
@@ -77,9 +182,293 @@ impl BinaryValueStruct {
         }
     }
 
+    fn implement_versioned_binary_value(
+        &self,
+        version: u8,
+        fields: &[VersionedField],
+    ) -> proc_macro2::TokenStream {
+        let name = &self.ident;
+        let error = quote!(metaldb::_reexports::Error);
+        let bindings: Vec<_> = (0..fields.len())
+            .map(|i| Ident::new(&format!("field_{}", i), Span::call_site()))
+            .collect();
+
+        let write_fields = fields.iter().enumerate().map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote!(#ident),
+                None => {
+                    let index = syn::Index::from(i);
+                    quote!(#index)
+                }
+            };
+            quote! {
+                bincode::serialize_into(&mut buffer, &self.#member).expect(
+                    concat!("Failed to serialize `BinaryValue` for ", stringify!(#name))
+                );
+            }
+        });
+
+        let read_fields = fields
+            .iter()
+            .zip(&bindings)
+            .map(|(field, binding)| match field.since {
+                Some(since) if since > 0 => quote! {
+                    let #binding = if version >= #since {
+                        bincode::deserialize_from(&mut bytes)?
+                    } else {
+                        std::default::Default::default()
+                    };
+                },
+                _ => quote! {
+                    let #binding = bincode::deserialize_from(&mut bytes)?;
+                },
+            });
+
+        let constructor = if matches!(fields.first(), Some(VersionedField { ident: None, .. })) {
+            quote!(Self(#(#bindings),*))
+        } else {
+            let names = fields.iter().map(|field| &field.ident);
+            quote!(Self { #(#names: #bindings),* })
+        };
+
+        quote! {
+            impl metaldb::BinaryValue for #name {
+                fn to_bytes(&self) -> std::vec::Vec<u8> {
+                    let mut buffer = vec![#version];
+                    #(#write_fields)*
+                    buffer
+                }
+
+                fn from_bytes(
+                    value: std::borrow::Cow<[u8]>,
+                ) -> std::result::Result<Self, #error> {
+                    let (&version, mut bytes) = value.split_first().ok_or_else(|| {
+                        #error::msg(concat!("Missing version of `", stringify!(#name), "`"))
+                    })?;
+                    if version > #version {
+                        let msg = format!(
+                            "Unsupported version {} of `{}` (the latest supported version is {})",
+                            version,
+                            stringify!(#name),
+                            #version,
+                        );
+                        return Err(#error::msg(msg));
+                    }
+                    #(#read_fields)*
+                    Ok(#constructor)
+                }
+            }
+        }
+    }
+
+    fn implement_tagged_binary_value(
+        &self,
+        variants: &[TaggedVariant],
+    ) -> proc_macro2::TokenStream {
+        let name = &self.ident;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let error = quote!(metaldb::_reexports::Error);
+        // A single byte is enough to hold the discriminant of the most enums.
+        let wide_tag = variants.len() > usize::from(u8::MAX) + 1;
+
+        let encode_arms = variants.iter().enumerate().map(|(i, variant)| {
+            let write_tag = if wide_tag {
+                let tag = i as u64;
+                quote!(write_varint(&mut buffer, #tag))
+            } else {
+                // The number of variants is checked above.
+                #[allow(clippy::cast_possible_truncation)]
+                let tag = i as u8;
+                quote!(buffer.push(#tag))
+            };
+            let pattern = variant.pattern();
+            let bindings = variant.bindings();
+            let write_fields = bindings.iter().enumerate().map(|(j, binding)| {
+                if j + 1 == bindings.len() {
+                    quote! {
+                        buffer.extend_from_slice(&metaldb::BinaryValue::to_bytes(#binding));
+                    }
+                } else {
+                    quote! {
+                        let bytes = metaldb::BinaryValue::to_bytes(#binding);
+                        write_varint(&mut buffer, bytes.len() as u64);
+                        buffer.extend_from_slice(&bytes);
+                    }
+                }
+            });
+            quote! {
+                #pattern => {
+                    #write_tag;
+                    #(#write_fields)*
+                }
+            }
+        });
+
+        let decode_arms = variants.iter().enumerate().map(|(i, variant)| {
+            let tag = if wide_tag {
+                quote!(#i)
+            } else {
+                #[allow(clippy::cast_possible_truncation)]
+                let tag = i as u8;
+                quote!(#tag)
+            };
+            let pattern = variant.pattern();
+            let bindings = variant.bindings();
+            let read_fields = bindings.iter().enumerate().map(|(j, binding)| {
+                let bytes = if j + 1 == bindings.len() {
+                    quote!(bytes)
+                } else {
+                    quote!(read_chunk(&mut bytes)?)
+                };
+                quote! {
+                    let #binding = metaldb::BinaryValue::from_bytes(
+                        std::borrow::Cow::Borrowed(#bytes),
+                    )?;
+                }
+            });
+            let check_payload = if bindings.is_empty() {
+                Some(quote! {
+                    if !bytes.is_empty() {
+                        let msg = format!(
+                            "Unexpected payload for a unit variant of `{}`",
+                            stringify!(#name),
+                        );
+                        return Err(#error::msg(msg));
+                    }
+                })
+            } else {
+                None
+            };
+            quote! {
+                #tag => {
+                    #check_payload
+                    #(#read_fields)*
+                    Ok(#pattern)
+                }
+            }
+        });
+
+        let read_tag = if wide_tag {
+            quote!(read_varint(&mut bytes)? as usize)
+        } else {
+            quote! {{
+                let (&tag, rest) = bytes.split_first().ok_or_else(|| {
+                    #error::msg(concat!("Missing variant tag for `", stringify!(#name), "`"))
+                })?;
+                bytes = rest;
+                tag
+            }}
+        };
+
+        quote! {
+            #[allow(dead_code, clippy::cast_possible_truncation)]
+            fn write_varint(buffer: &mut std::vec::Vec<u8>, mut value: u64) {
+                while value >= 0x80 {
+                    buffer.push(value as u8 | 0x80);
+                    value >>= 7;
+                }
+                buffer.push(value as u8);
+            }
+
+            #[allow(dead_code)]
+            fn read_varint(bytes: &mut &[u8]) -> std::result::Result<u64, #error> {
+                let slice: &[u8] = *bytes;
+                let mut value = 0_u64;
+                for (i, &byte) in slice.iter().enumerate().take(10) {
+                    value |= u64::from(byte & 0x7f) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        *bytes = &slice[i + 1..];
+                        return Ok(value);
+                    }
+                }
+                Err(#error::msg("Invalid varint"))
+            }
+
+            #[allow(dead_code, clippy::cast_possible_truncation)]
+            fn read_chunk<'a>(bytes: &mut &'a [u8]) -> std::result::Result<&'a [u8], #error> {
+                let len = read_varint(bytes)?;
+                let slice: &'a [u8] = *bytes;
+                if len > slice.len() as u64 {
+                    return Err(#error::msg("Field length exceeds the value length"));
+                }
+                let (chunk, rest) = slice.split_at(len as usize);
+                *bytes = rest;
+                Ok(chunk)
+            }
+
+            impl #impl_generics metaldb::BinaryValue for #name #ty_generics #where_clause {
+                fn to_bytes(&self) -> std::vec::Vec<u8> {
+                    let mut buffer = std::vec::Vec::new();
+                    match self {
+                        #(#encode_arms)*
+                    }
+                    buffer
+                }
+
+                fn from_bytes(
+                    value: std::borrow::Cow<[u8]>,
+                ) -> std::result::Result<Self, #error> {
+                    let mut bytes: &[u8] = value.as_ref();
+                    match #read_tag {
+                        #(#decode_arms)*
+                        tag => {
+                            let msg = format!(
+                                "Unknown variant tag {} for `{}`",
+                                tag,
+                                stringify!(#name),
+                            );
+                            Err(#error::msg(msg))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     fn implement_binary_value(&self) -> impl ToTokens {
         match self.attrs.codec {
-            Codec::Bincode => self.implement_binary_value_from_bincode(),
+            Codec::Bincode => match self.attrs.version {
+                Some(version) => {
+                    let fields = self.fields.as_ref().unwrap();
+                    self.implement_versioned_binary_value(version, fields)
+                }
+                None => self.implement_binary_value_from_bincode(),
+            },
+            Codec::Tagged => {
+                let variants = self.variants.as_ref().unwrap();
+                self.implement_tagged_binary_value(variants)
+            }
+        }
+    }
+}
+
+/// Enum variant encoded by the `tagged` codec.
+#[derive(Debug)]
+struct TaggedVariant {
+    ident: Ident,
+    fields: syn::Fields,
+}
+
+impl TaggedVariant {
+    /// Names of local variables binding the variant fields.
+    fn bindings(&self) -> Vec<Ident> {
+        (0..self.fields.len())
+            .map(|i| Ident::new(&format!("field_{}", i), Span::call_site()))
+            .collect()
+    }
+
+    /// Pattern matching the variant and binding its fields; it is also used to construct
+    /// the variant from the bound fields.
+    fn pattern(&self) -> proc_macro2::TokenStream {
+        let ident = &self.ident;
+        let bindings = self.bindings();
+        match &self.fields {
+            syn::Fields::Named(fields) => {
+                let names = fields.named.iter().map(|field| &field.ident);
+                quote!(Self::#ident { #(#names: #bindings),* })
+            }
+            syn::Fields::Unnamed(_) => quote!(Self::#ident(#(#bindings),*)),
+            syn::Fields::Unit => quote!(Self::#ident),
         }
     }
 }
@@ -135,11 +524,19 @@ fn validate_address_component(name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Name of the entry storing the discriminant of an enum deriving `FromAccess`.
+const VARIANT_TAG_NAME: &str = "_variant";
+/// Default separator of address components.
+const DEFAULT_SEPARATOR: &str = ".";
+/// Separators allowed by the `IndexAddress` name validation.
+const ALLOWED_SEPARATORS: &[&str] = &[".", "/"];
+
 #[derive(Debug)]
 struct FromAccess {
     ident: Ident,
     access_ident: Ident,
     fields: Vec<AccessField>,
+    variants: Option<Vec<AccessVariant>>,
     generics: Generics,
     attrs: FromAccessAttrs,
 }
@@ -148,6 +545,34 @@ struct FromAccess {
 struct FromAccessAttrs {
     #[darling(default)]
     transparent: bool,
+    #[darling(default)]
+    separator: Option<String>,
+}
+
+impl FromAccessAttrs {
+    fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(DEFAULT_SEPARATOR)
+    }
+
+    fn validate(&self) -> darling::Result<()> {
+        if let Some(ref separator) = self.separator {
+            if self.transparent {
+                let e = "Transparent layout is incompatible with a custom separator";
+                return Err(darling::Error::custom(e));
+            }
+            if !ALLOWED_SEPARATORS.contains(&separator.as_str()) {
+                let msg = format!("Invalid separator `{}` (allowed: `.` and `/`)", separator);
+                return Err(darling::Error::custom(msg));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, FromMeta)]
+struct FromAccessVariantAttrs {
+    #[darling(default)]
+    rename: Option<String>,
 }
 
 #[derive(Debug, Default, FromMeta)]
@@ -156,6 +581,8 @@ struct FromAccessFieldAttrs {
     rename: Option<String>,
     #[darling(default)]
     flatten: bool,
+    #[darling(default)]
+    skip: bool,
 }
 
 impl FromAccess {
@@ -204,6 +631,7 @@ impl FromDeriveInput for FromAccess {
         let attrs = find_meta_attrs("from_access", &input.attrs)
             .map(|meta| FromAccessAttrs::from_nested_meta(&meta))
             .unwrap_or_else(|| Ok(FromAccessAttrs::default()))?;
+        attrs.validate()?;
 
         match &input.data {
             Data::Struct(DataStruct { fields, .. }) => {
@@ -212,6 +640,7 @@ impl FromDeriveInput for FromAccess {
                     access_ident: Self::extract_access_ident(&input.generics)?.clone(),
                     generics: input.generics.clone(),
                     fields: Fields::try_from(fields)?.fields,
+                    variants: None,
                     attrs,
                 };
 
@@ -222,45 +651,140 @@ impl FromDeriveInput for FromAccess {
                         );
                         return Err(e);
                     }
-                } else {
-                    let mut field_names = HashSet::new();
-
-                    for field in &this.fields {
-                        if let Some(ref name) = field.name_suffix {
-                            validate_address_component(name).map_err(|msg| {
-                                darling::Error::custom(msg).with_span(&field.span)
-                            })?;
-                            if !field_names.insert(name) {
-                                let e = "Duplicate field name";
-                                return Err(darling::Error::custom(e).with_span(&field.span));
-                            }
-                        } else if !field.flatten {
-                            let msg = if this.fields.len() == 1 {
-                                "Unnamed fields necessitate #[from_access(rename = ...)]. \
-                                 To use a wrapper, add #[from_access(transparent)] to the struct"
-                            } else {
-                                "Unnamed fields necessitate #[from_access(rename = ...)]"
-                            };
-                            let e = darling::Error::custom(msg).with_span(&field.span);
-                            return Err(e);
-                        }
+                    if this.fields[0].skip {
+                        let e = "Field of a transparent struct cannot be skipped";
+                        return Err(darling::Error::custom(e).with_span(&this.fields[0].span));
                     }
+                } else {
+                    validate_fields(&this.fields, true)?;
                 }
                 Ok(this)
             }
-            _ => Err(darling::Error::unsupported_shape(
-                "`FromAccess` can be only implemented for structs",
+
+            Data::Enum(DataEnum { variants, .. }) => {
+                if attrs.transparent {
+                    let e = darling::Error::custom("Enums cannot have transparent layout");
+                    return Err(e);
+                }
+                if variants.is_empty() {
+                    let e = darling::Error::custom("Enum must have at least one variant");
+                    return Err(e);
+                }
+                if variants.len() > usize::from(u8::MAX) + 1 {
+                    let e = darling::Error::custom("Enum must have at most 256 variants");
+                    return Err(e);
+                }
+
+                let variants = variants
+                    .iter()
+                    .map(AccessVariant::from_variant)
+                    .collect::<darling::Result<Vec<_>>>()?;
+                let mut variant_names = HashSet::new();
+                for variant in &variants {
+                    validate_address_component(&variant.name)
+                        .map_err(|msg| darling::Error::custom(msg).with_span(&variant.span))?;
+                    if variant.name == VARIANT_TAG_NAME {
+                        let msg = format!("Variant name `{}` is reserved", VARIANT_TAG_NAME);
+                        return Err(darling::Error::custom(msg).with_span(&variant.span));
+                    }
+                    if !variant_names.insert(&variant.name) {
+                        let e = "Duplicate variant name";
+                        return Err(darling::Error::custom(e).with_span(&variant.span));
+                    }
+                    validate_fields(&variant.fields, false)?;
+                }
+
+                Ok(Self {
+                    ident: input.ident.clone(),
+                    access_ident: Self::extract_access_ident(&input.generics)?.clone(),
+                    generics: input.generics.clone(),
+                    fields: vec![],
+                    variants: Some(variants),
+                    attrs,
+                })
+            }
+
+            Data::Union(_) => Err(darling::Error::unsupported_shape(
+                "`FromAccess` can be only implemented for structs and enums",
             )),
         }
     }
 }
 
+/// Checks that field names are valid and unique. `is_struct` influences error messages.
+fn validate_fields(fields: &[AccessField], is_struct: bool) -> darling::Result<()> {
+    let mut field_names = HashSet::new();
+
+    for field in fields {
+        if field.skip {
+            continue;
+        }
+        if let Some(ref name) = field.name_suffix {
+            validate_address_component(name)
+                .map_err(|msg| darling::Error::custom(msg).with_span(&field.span))?;
+            if !field_names.insert(name) {
+                let e = "Duplicate field name";
+                return Err(darling::Error::custom(e).with_span(&field.span));
+            }
+        } else if !field.flatten {
+            let msg = if is_struct && fields.len() == 1 {
+                "Unnamed fields necessitate #[from_access(rename = ...)]. \
+                 To use a wrapper, add #[from_access(transparent)] to the struct"
+            } else {
+                "Unnamed fields necessitate #[from_access(rename = ...)]"
+            };
+            let e = darling::Error::custom(msg).with_span(&field.span);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct AccessVariant {
+    span: Span,
+    ident: Ident,
+    name: String,
+    fields: Vec<AccessField>,
+}
+
+impl AccessVariant {
+    fn from_variant(variant: &syn::Variant) -> darling::Result<Self> {
+        let attrs = find_meta_attrs("from_access", &variant.attrs)
+            .map(|meta| FromAccessVariantAttrs::from_nested_meta(&meta))
+            .unwrap_or_else(|| Ok(FromAccessVariantAttrs::default()))?;
+
+        Ok(Self {
+            span: variant.span(),
+            ident: variant.ident.clone(),
+            name: attrs.rename.unwrap_or_else(|| variant.ident.to_string()),
+            fields: Fields::try_from(&variant.fields)?.fields,
+        })
+    }
+
+    fn constructor(&self, separator: &str) -> impl ToTokens {
+        let ident = &self.ident;
+        let name = &self.name;
+        let field_constructors = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| field.constructor(i, separator));
+
+        quote! {{
+            let addr = addr.append_name_with_separator(#separator, #name);
+            Ok(Self::#ident { #(#field_constructors,)* })
+        }}
+    }
+}
+
 #[derive(Debug)]
 struct AccessField {
     span: Span,
     ident: Option<Ident>,
     name_suffix: Option<String>,
     flatten: bool,
+    skip: bool,
 }
 
 impl FromField for AccessField {
@@ -271,6 +795,11 @@ impl FromField for AccessField {
             .map(|meta| FromAccessFieldAttrs::from_nested_meta(&meta))
             .unwrap_or_else(|| Ok(FromAccessFieldAttrs::default()))?;
 
+        if attrs.skip && (attrs.flatten || attrs.rename.is_some()) {
+            let e = "Skipped field cannot be flattened or renamed";
+            return Err(darling::Error::custom(e).with_span(&field.span()));
+        }
+
         let name_suffix = attrs
             .rename
             .or_else(|| ident.as_ref().map(ToString::to_string));
@@ -279,6 +808,7 @@ impl FromField for AccessField {
             name_suffix,
             span: field.span(),
             flatten: attrs.flatten,
+            skip: attrs.skip,
         })
     }
 }
@@ -293,21 +823,36 @@ impl AccessField {
         }
     }
 
-    fn constructor(&self, field_index: usize) -> impl ToTokens {
+    /// Initializes a skipped field with its default value.
+    fn default_constructor(&self, field_index: usize) -> proc_macro2::TokenStream {
+        let ident = self.ident(field_index);
+        quote!(#ident: std::default::Default::default())
+    }
+
+    fn constructor(&self, field_index: usize, separator: &str) -> impl ToTokens {
         let from_access = quote!(metaldb::access::FromAccess);
         let ident = self.ident(field_index);
-        if self.flatten {
+        if self.skip {
+            self.default_constructor(field_index)
+        } else if self.flatten {
             quote!(#ident: #from_access::from_access(access.clone(), addr.clone())?)
         } else {
             let name = self.name_suffix.as_ref().unwrap();
-            quote!(#ident: #from_access::from_access(access.clone(), addr.clone().append_name(#name))?)
+            quote! {
+                #ident: #from_access::from_access(
+                    access.clone(),
+                    addr.clone().append_name_with_separator(#separator, #name),
+                )?
+            }
         }
     }
 
     fn root_constructor(&self, field_index: usize) -> impl ToTokens {
         let from_access = quote!(metaldb::access::FromAccess);
         let ident = self.ident(field_index);
-        if self.flatten {
+        if self.skip {
+            self.default_constructor(field_index)
+        } else if self.flatten {
             quote!(#ident: #from_access::from_root(access.clone())?)
         } else {
             let name = &self.name_suffix;
@@ -317,17 +862,57 @@ impl AccessField {
 }
 
 impl FromAccess {
+    fn enum_access_fn_impl(&self, variants: &[AccessVariant]) -> proc_macro2::TokenStream {
+        let from_access = quote!(metaldb::access::FromAccess);
+        let access_ident = &self.access_ident;
+        let name = &self.ident;
+        let separator = self.attrs.separator();
+
+        let arms = variants.iter().enumerate().map(|(i, variant)| {
+            // The number of variants is checked when parsing the enum.
+            #[allow(clippy::cast_possible_truncation)]
+            let tag = i as u8;
+            let constructor = variant.constructor(separator);
+            quote!(#tag => #constructor)
+        });
+
+        quote! {
+            let tag_addr = addr.clone().append_name_with_separator(#separator, #VARIANT_TAG_NAME);
+            let tag = {
+                let tag_entry: metaldb::Entry<<#access_ident as metaldb::access::Access>::Base, u8> =
+                    #from_access::from_access(access.clone(), tag_addr)?;
+                tag_entry.get().unwrap_or(0)
+            };
+
+            match tag {
+                #(#arms,)*
+                tag => {
+                    let msg = format!("Unknown variant tag {} for `{}`", tag, stringify!(#name));
+                    Err(metaldb::access::AccessError {
+                        addr,
+                        kind: metaldb::access::AccessErrorKind::Custom(
+                            metaldb::_reexports::Error::msg(msg),
+                        ),
+                    })
+                }
+            }
+        }
+    }
+
     fn access_fn(&self) -> impl ToTokens {
-        let fn_impl = if self.attrs.transparent {
+        let fn_impl = if let Some(ref variants) = self.variants {
+            self.enum_access_fn_impl(variants)
+        } else if self.attrs.transparent {
             let from_access = quote!(metaldb::access::FromAccess);
             let ident = self.fields[0].ident(0);
             quote!(Ok(Self { #ident: #from_access::from_access(access, addr)? }))
         } else {
+            let separator = self.attrs.separator();
             let field_constructors = self
                 .fields
                 .iter()
                 .enumerate()
-                .map(|(i, field)| field.constructor(i));
+                .map(|(i, field)| field.constructor(i, separator));
             quote!(Ok(Self { #(#field_constructors,)* }))
         };
 
@@ -375,7 +960,12 @@ impl ToTokens for FromAccess {
         let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
 
         let from_access_fn = self.access_fn();
-        let from_root_fn = self.root_fn();
+        // Enums use the default implementation of `from_root`.
+        let from_root_fn = if self.variants.is_some() {
+            None
+        } else {
+            Some(self.root_fn())
+        };
 
         let expanded = quote! {
             impl #impl_generics #tr<#access_ident> for #name #ty_generics #where_clause {
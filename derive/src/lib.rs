@@ -18,13 +18,64 @@ use syn::{Attribute, NestedMeta};
 ///
 /// - `bincode` serialization via the eponymous crate. Switched on by the
 ///   `#[binary_value(codec = "bincode")]` attribute.
+/// - `tagged` encoding of enums. Switched on by the `#[binary_value(codec = "tagged")]`
+///   attribute.
 ///
 /// # Container Attributes
 ///
 /// ## `codec`
 ///
-/// Selects the serialization codec to use. Allowed values are `protobuf` (used by default)
-/// and `bincode`.
+/// Selects the serialization codec to use. Allowed values are `protobuf` (used by default),
+/// `bincode` and `tagged`.
+///
+/// ## `version`
+///
+/// ```text
+/// #[binary_value(codec = "bincode", version = 2)]
+/// ```
+///
+/// Switches a struct using the `bincode` codec to the versioned encoding (see
+/// [below](#versioned-structs)). The version is an integer from 0 to 255.
+///
+/// # Field Attributes
+///
+/// ## `since`
+///
+/// ```text
+/// #[binary_value(since = 2)]
+/// ```
+///
+/// Specifies the version of a struct with the versioned encoding in which the field
+/// was added. Fields without this attribute are present in all versions. The version cannot
+/// be newer than the struct version, and the field type must implement `Default`.
+///
+/// # Versioned Structs
+///
+/// A struct with the `version` attribute is encoded as the version byte followed by
+/// the `bincode` encodings of its fields in the declaration order. Thus, unlike
+/// the plain `bincode` codec, the struct itself does not need to implement
+/// `Serialize` / `Deserialize`; only its fields do.
+///
+/// When decoding, fields added in a version newer than the version of the stored value
+/// are set to their default values, so fields can be added anywhere in the struct
+/// by increasing the struct version and marking the new fields with `since`.
+/// Values with a version newer than the struct version cannot be decoded. Removing,
+/// reordering or changing the type of existing fields still requires a migration.
+/// Since the version byte is not a part of the plain `bincode` encoding, adding
+/// the `version` attribute to a struct breaks compatibility with its stored values.
+///
+/// # Tagged Enums
+///
+/// The `tagged` codec can be applied to enums whose variant fields implement `BinaryValue`.
+/// A value is encoded as the variant discriminant followed by the payload of the variant.
+/// The discriminant is the zero-based position of the variant in the enum declaration;
+/// it takes a single byte if the enum has at most 256 variants, and is encoded
+/// as an unsigned LEB128 varint otherwise. Unit variants have no payload. The payload
+/// of other variants is the concatenation of the `BinaryValue` encodings of their fields,
+/// in which all fields but the last one are prefixed by their length as a varint.
+///
+/// Since the discriminant depends on the variant position, reordering variants
+/// (or growing the enum beyond 256 variants) breaks compatibility with stored data.
 ///
 /// # Examples
 ///
@@ -47,6 +98,35 @@ use syn::{Attribute, NestedMeta};
 /// };
 /// let bytes = wallet.to_bytes();
 /// ```
+///
+/// With versioned `bincode` serialization:
+///
+/// ```ignore
+/// #[derive(Clone, Debug, BinaryValue)]
+/// #[binary_value(codec = "bincode", version = 2)]
+/// pub struct Wallet {
+///     pub username: String,
+///     /// Is set to `false` when reading values stored before the field was added.
+///     #[binary_value(since = 2)]
+///     pub frozen: bool,
+///     pub balance: u64,
+/// }
+/// ```
+///
+/// With `tagged` encoding:
+///
+/// ```ignore
+/// #[derive(Debug, BinaryValue)]
+/// #[binary_value(codec = "tagged")]
+/// pub enum Event {
+///     Created { username: String, balance: u64 },
+///     Renamed(String),
+///     Deleted,
+/// }
+///
+/// // The discriminant of `Deleted` followed by no payload.
+/// assert_eq!(Event::Deleted.to_bytes(), [2]);
+/// ```
 #[proc_macro_derive(BinaryValue, attributes(binary_value))]
 pub fn binary_value(input: TokenStream) -> TokenStream {
     db_traits::impl_binary_value(input)
@@ -54,8 +134,8 @@ pub fn binary_value(input: TokenStream) -> TokenStream {
 
 /// Derives `FromAccess` trait.
 ///
-/// This macro can be applied only to `struct`s, each field of which implements `FromAccess`
-/// itself (e.g., indexes, `Group`s, or `Lazy` indexes). The macro instantiates each field
+/// This macro can be applied to `struct`s and `enum`s (see [below](#enums)), each field of which
+/// implements `FromAccess` itself (e.g., indexes, `Group`s, or `Lazy` indexes). The macro instantiates each field
 /// using the address created by appending a dot `.` and the name of the field or its override
 /// (see [below](#rename)) to the root address where the struct is created. For example,
 /// if the struct is created at the address `"foo"` and has fields `"list"` and `"map"`, they
@@ -65,6 +145,33 @@ pub fn binary_value(input: TokenStream) -> TokenStream {
 /// The derive logic will determine this param as the first param with `T: Access` bound.
 /// If there are no such params, but there is a single type param, it will be used.
 ///
+/// # Enums
+///
+/// An enum stores its discriminant in a hidden `Entry<_, u8>` named `_variant` relative
+/// to the enum address. The discriminant is the zero-based position of the variant
+/// in the enum declaration; if the entry is missing, the first variant is assumed. Fields
+/// of the active variant are instantiated under the variant name (or its override),
+/// so if the enum is created at the address `"state"` and the active variant `Running`
+/// has a field `log`, the field will be instantiated at `"state.Running.log"`.
+/// An unknown discriminant results in an `AccessError`. Since the discriminant depends
+/// on the variant position, reordering variants breaks compatibility with stored data.
+///
+/// Unnamed fields in variants require `#[from_access(rename = ...)]` or
+/// `#[from_access(flatten)]`, in the latter case the field is instantiated at the variant
+/// address itself.
+///
+/// The derived implementation never writes the discriminant. To switch the variant,
+/// drop the existing instance, set the `_variant` entry to the position of the new variant
+/// and recreate the enum:
+///
+/// ```text
+/// fork.get_entry::<_, u8>("state._variant").set(1);
+/// let state = State::from_access(&fork, "state".into())?;
+/// ```
+///
+/// Indexes of the previously active variant are left intact; clear them beforehand
+/// if they are no longer needed.
+///
 /// # Container Attributes
 ///
 /// ## `transparent`
@@ -78,6 +185,22 @@ pub fn binary_value(input: TokenStream) -> TokenStream {
 /// A struct with the transparent layout must have a single field. The field will be created at
 /// the same address as the struct itself (i.e., no suffix will be added).
 ///
+/// ## `separator`
+///
+/// ```text
+/// #[from_access(separator = "/")]
+/// ```
+///
+/// Changes the separator appended to the address before the name of each field
+/// (and, for enums, before the variant name and the `_variant` discriminant entry).
+/// The separator may be a dot `.` (the default) or a slash `/`; both are allowed
+/// in index names. The attribute cannot be combined with the transparent layout.
+///
+/// The separator only applies to the fields of the struct it is specified on;
+/// nested components use their own separators. Keeping separators consistent across
+/// nested schemas is the responsibility of the caller. Note that namespace-aware
+/// functionality, such as migrations, recognizes only dot separators.
+///
 /// # Field Attributes
 ///
 /// ## `rename`
@@ -88,6 +211,28 @@ pub fn binary_value(input: TokenStream) -> TokenStream {
 ///
 /// Changes the suffix appended to the address when creating a field. The name should follow
 /// conventions for index names.
+///
+/// ## `skip`
+///
+/// ```text
+/// #[from_access(skip)]
+/// ```
+///
+/// Excludes the field from address-based instantiation; the field is initialized
+/// with `Default::default()` instead. This allows to keep helpers not backed by the storage
+/// (e.g., cached or parsed data) in a derived struct. The field type must implement `Default`.
+/// A skipped field cannot be renamed or flattened.
+///
+/// # Variant Attributes
+///
+/// ## `rename`
+///
+/// ```text
+/// #[from_access(rename = "name")]
+/// ```
+///
+/// Changes the suffix appended to the enum address for fields of the variant.
+/// The name should follow conventions for index names and cannot be `_variant`.
 #[proc_macro_derive(FromAccess, attributes(from_access))]
 pub fn from_access(input: TokenStream) -> TokenStream {
     db_traits::impl_from_access(input)
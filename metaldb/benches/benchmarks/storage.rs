@@ -12,6 +12,8 @@ const FAMILY: &str = "index_family";
 const SAMPLE_SIZE: usize = 10;
 const CHUNK_SIZE: usize = 64;
 const SEED: [u8; 32] = [100; 32];
+/// Size of the value used to compare checking presence of an entry and reading it.
+const LARGE_VALUE_SIZE: usize = 1 << 20;
 
 #[cfg(all(test, not(feature = "long_benchmarks")))]
 const ITEM_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
@@ -170,6 +172,36 @@ fn plain_map_index_with_family_read(b: &mut Bencher<'_>, len: usize) {
     );
 }
 
+fn list_index_push(b: &mut Bencher<'_>, len: usize) {
+    let data = generate_random_kv(len);
+    b.iter_with_setup(
+        || (BenchDB::default(), data.clone()),
+        |(db, data)| {
+            let fork = db.fork();
+            {
+                let mut list = fork.get_list(NAME);
+                for (_, value) in data {
+                    list.push(value);
+                }
+            }
+            db.merge_sync(fork.into_patch()).unwrap();
+        },
+    );
+}
+
+fn list_index_extend(b: &mut Bencher<'_>, len: usize) {
+    let data = generate_random_kv(len);
+    b.iter_with_setup(
+        || (BenchDB::default(), data.clone()),
+        |(db, data)| {
+            let fork = db.fork();
+            fork.get_list(NAME)
+                .extend(data.into_iter().map(|(_, value)| value));
+            db.merge_sync(fork.into_patch()).unwrap();
+        },
+    );
+}
+
 fn bench_fn<F>(c: &mut Criterion, name: &str, benchmark: F)
 where
     F: Fn(&mut Bencher<'_>, usize) + 'static,
@@ -230,6 +262,20 @@ fn bench_index_clearing(bencher: &mut Bencher<'_>) {
     }
 }
 
+fn bench_entry_presence(c: &mut Criterion) {
+    let db = BenchDB::default();
+    let fork = db.fork();
+    fork.get_entry(NAME).set(vec![1_u8; LARGE_VALUE_SIZE]);
+    db.merge_sync(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let entry = snapshot.get_entry::<_, Vec<u8>>(NAME);
+    let mut group = c.benchmark_group("storage/entry_presence");
+    group.bench_function("exists", |b| b.iter(|| black_box(entry.exists())));
+    group.bench_function("get", |b| b.iter(|| black_box(entry.get().is_some())));
+    group.finish();
+}
+
 pub fn bench_storage(c: &mut Criterion) {
     // MapIndex
     bench_fn(c, "storage/plain_map/insert", plain_map_index_insert);
@@ -251,6 +297,13 @@ pub fn bench_storage(c: &mut Criterion) {
         plain_map_index_with_family_read,
     );
 
+    // ListIndex
+    bench_fn(c, "storage/list/push", list_index_push);
+    bench_fn(c, "storage/list/extend", list_index_extend);
+
     // Index clearing
     c.bench_function("storage/clearing", bench_index_clearing);
+
+    // Entry
+    bench_entry_presence(c);
 }
@@ -1,8 +1,8 @@
 //! Extension traits to simplify index instantiation.
 
 use crate::{
-    access::{Access, FromAccess},
-    views::IndexType,
+    access::{Access, AsReadonly, FromAccess},
+    views::{IndexType, View},
     BinaryKey, BinaryValue, Entry, Group, IndexAddress, KeySetIndex, ListIndex, MapIndex,
     SparseListIndex,
 };
@@ -138,7 +138,118 @@ pub trait CopyAccessExt: Access + Copy {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
     }
 
+    /// Gets a readonly entry index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not an entry.
+    fn get_entry_readonly<I, V>(self, addr: I) -> Entry<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        Entry::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly list index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a list.
+    fn get_list_readonly<I, V>(self, addr: I) -> ListIndex<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        ListIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly map index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`, which allows to enforce
+    /// readonly intent on the type level.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_map("map").put(&1_u8, "foo".to_owned());
+    ///
+    /// let map = fork.get_map_readonly::<_, u8, String>("map");
+    /// assert_eq!(map.get(&1), Some("foo".to_owned()));
+    /// ```
+    ///
+    /// The returned index cannot be modified:
+    ///
+    /// ```compile_fail
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// # let db = TemporaryDB::new();
+    /// # let fork = db.fork();
+    /// let mut map = fork.get_map_readonly::<_, u8, String>("map");
+    /// map.put(&1, "foo".to_owned());
+    /// ```
+    fn get_map_readonly<I, K, V>(self, addr: I) -> MapIndex<<Self as AsReadonly>::Readonly, K, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        MapIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly sparse list index with the specified address. The index cannot be
+    /// modified even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a sparse list.
+    fn get_sparse_list_readonly<I, V>(
+        self,
+        addr: I,
+    ) -> SparseListIndex<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        SparseListIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly key set index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a key set.
+    fn get_key_set_readonly<I, K>(self, addr: I) -> KeySetIndex<<Self as AsReadonly>::Readonly, K>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+    {
+        KeySetIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
     /// Gets index type at the specified address, or `None` if there is no index.
+    ///
+    /// Unlike the `get_*` methods, this method does not create the index or its metadata,
+    /// so it can be used to check whether an index exists without side effects.
     fn index_type<I>(self, addr: I) -> Option<IndexType>
     where
         I: Into<IndexAddress>,
@@ -147,6 +258,39 @@ pub trait CopyAccessExt: Access + Copy {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
             .map(|metadata| metadata.index_type())
     }
+
+    /// Gets a raw value stored in the index with the specified address under the specified
+    /// raw key, or `None` if there is no such index or value. The value is not deserialized,
+    /// which is useful for generic tooling copying data between indexes.
+    ///
+    /// The key should be serialized in the same way as keys of the index, e.g., via
+    /// [`BinaryKey::write`]. Like [`index_type`], this method does not create the index.
+    ///
+    /// [`BinaryKey::write`]: ../trait.BinaryKey.html#tymethod.write
+    /// [`index_type`]: #method.index_type
+    ///
+    /// # Panics
+    ///
+    /// If the index is borrowed mutably at the time of the call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, BinaryValue, Database, TemporaryDB};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_map("map").put(&1_u8, "foo".to_owned());
+    /// assert_eq!(fork.get_bytes("map", &[1]), Some("foo".to_owned().into_bytes()));
+    /// assert_eq!(fork.get_bytes("map", &[2]), None);
+    /// assert_eq!(fork.get_bytes("other_map", &[1]), None);
+    /// ```
+    fn get_bytes<I>(self, addr: I, key: &[u8]) -> Option<Vec<u8>>
+    where
+        I: Into<IndexAddress>,
+    {
+        get_bytes(self, addr.into(), key)
+    }
 }
 
 impl<T: Access + Copy> CopyAccessExt for T {}
@@ -252,7 +396,99 @@ pub trait AccessExt: Access {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
     }
 
+    /// Gets a readonly entry index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not an entry.
+    fn get_entry_readonly<I, V>(&self, addr: I) -> Entry<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        Entry::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly list index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a list.
+    fn get_list_readonly<I, V>(&self, addr: I) -> ListIndex<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        ListIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly map index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`, which allows to enforce
+    /// readonly intent on the type level.
+    ///
+    /// See [`CopyAccessExt::get_map_readonly`] for details.
+    ///
+    /// [`CopyAccessExt::get_map_readonly`]: trait.CopyAccessExt.html#method.get_map_readonly
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a map.
+    fn get_map_readonly<I, K, V>(&self, addr: I) -> MapIndex<<Self as AsReadonly>::Readonly, K, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        MapIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly sparse list index with the specified address. The index cannot be
+    /// modified even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a sparse list.
+    fn get_sparse_list_readonly<I, V>(
+        &self,
+        addr: I,
+    ) -> SparseListIndex<<Self as AsReadonly>::Readonly, V>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        V: BinaryValue,
+    {
+        SparseListIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
+    /// Gets a readonly key set index with the specified address. The index cannot be modified
+    /// even if `self` is a mutable access, such as `&Fork`.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not a key set.
+    fn get_key_set_readonly<I, K>(&self, addr: I) -> KeySetIndex<<Self as AsReadonly>::Readonly, K>
+    where
+        Self: AsReadonly,
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+    {
+        KeySetIndex::from_access(self.as_readonly(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
     /// Gets index type at the specified address, or `None` if there is no index.
+    ///
+    /// Unlike the `get_*` methods, this method does not create the index or its metadata,
+    /// so it can be used to check whether an index exists without side effects.
     fn index_type<I>(&self, addr: I) -> Option<IndexType>
     where
         I: Into<IndexAddress>,
@@ -262,14 +498,43 @@ pub trait AccessExt: Access {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
             .map(|metadata| metadata.index_type())
     }
+
+    /// Gets a raw value stored in the index with the specified address under the specified
+    /// raw key, or `None` if there is no such index or value. The value is not deserialized.
+    ///
+    /// See [`CopyAccessExt::get_bytes`] for details.
+    ///
+    /// [`CopyAccessExt::get_bytes`]: trait.CopyAccessExt.html#method.get_bytes
+    fn get_bytes<I>(&self, addr: I, key: &[u8]) -> Option<Vec<u8>>
+    where
+        I: Into<IndexAddress>,
+    {
+        get_bytes(self.clone(), addr.into(), key)
+    }
 }
 
 impl<T: Access> AccessExt for T {}
 
+fn get_bytes<T: Access>(access: T, addr: IndexAddress, key: &[u8]) -> Option<Vec<u8>> {
+    let metadata = access
+        .clone()
+        .get_index_metadata(addr.clone())
+        .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))?;
+    let view: View<T::Base> = access
+        .get_or_create_view(addr, metadata.index_type())
+        .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+        .into();
+    view.get_bytes(key)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{AccessExt, CopyAccessExt, IndexType};
-    use crate::{access::Prefixed, migration::Migration, Database, TemporaryDB};
+    use crate::{
+        access::{Access, Prefixed},
+        migration::Migration,
+        Database, TemporaryDB,
+    };
 
     #[test]
     fn index_type_works() {
@@ -332,4 +597,98 @@ mod tests {
             Some(IndexType::Entry)
         );
     }
+
+    #[test]
+    fn index_type_does_not_create_index() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        assert_eq!(fork.index_type("list"), None);
+        assert_eq!(fork.index_type(("fam", &0_u8)), None);
+        let prefixed = Prefixed::new("ns", &fork);
+        assert_eq!(prefixed.index_type("map"), None);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        assert_eq!(fork.index_type("list"), None);
+        assert_eq!(fork.index_type(("fam", &0_u8)), None);
+        assert_eq!(fork.index_type("ns.map"), None);
+        // An index created afterwards is the first index in the database.
+        fork.get_list::<_, u32>("list").push(1);
+        let metadata = fork.get_index_metadata("list".into()).unwrap().unwrap();
+        assert_eq!(metadata.identifier().get(), 1);
+    }
+
+    #[test]
+    fn readonly_indexes_from_fork() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(42_u32);
+        fork.get_list("list").extend(vec![1_u8, 2]);
+        fork.get_map("map").put(&1_u8, 3_u64);
+        fork.get_sparse_list("sparse").set(5, 4_u8);
+        fork.get_key_set("set").insert(&5_u16);
+
+        assert_eq!(fork.get_entry_readonly("entry").get(), Some(42_u32));
+        assert_eq!(
+            fork.get_list_readonly::<_, u8>("list")
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(fork.get_map_readonly::<_, u8, u64>("map").get(&1), Some(3));
+        assert_eq!(
+            fork.get_sparse_list_readonly::<_, u8>("sparse").get(5),
+            Some(4)
+        );
+        assert!(fork.get_key_set_readonly::<_, u16>("set").contains(&5));
+
+        // Readonly indexes can coexist with each other.
+        let map = fork.get_map_readonly::<_, u8, u64>("map");
+        let same_map = fork.get_map_readonly::<_, u8, u64>("map");
+        assert_eq!(map.get(&1), same_map.get(&1));
+
+        // Nonexistent indexes are not created.
+        assert!(fork.get_list_readonly::<_, u8>("other_list").is_empty());
+        assert_eq!(fork.index_type("other_list"), None);
+
+        // `AccessExt` methods work as well.
+        let readonly = fork.readonly();
+        assert_eq!(
+            AccessExt::get_entry_readonly(&readonly, "entry").get(),
+            Some(42_u32)
+        );
+    }
+
+    #[test]
+    fn get_bytes_works() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(42_u32);
+        Prefixed::new("ns", &fork)
+            .get_list("list")
+            .extend(vec![1_u8, 2]);
+
+        assert_eq!(fork.get_bytes("entry", &[]), Some(vec![42, 0, 0, 0]));
+        assert_eq!(
+            fork.readonly().get_bytes("ns.list", &1_u64.to_be_bytes()),
+            Some(vec![2])
+        );
+        let prefixed = Prefixed::new("ns", fork.readonly());
+        assert_eq!(
+            prefixed.get_bytes("list", &0_u64.to_be_bytes()),
+            Some(vec![1])
+        );
+        assert_eq!(prefixed.get_bytes("list", &2_u64.to_be_bytes()), None);
+        assert_eq!(prefixed.get_bytes("other", &[]), None);
+        assert_eq!(prefixed.index_type("other"), None);
+
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_bytes("entry", &[]), Some(vec![42, 0, 0, 0]));
+        let prefixed = Prefixed::new("ns", &snapshot);
+        assert_eq!(
+            prefixed.get_bytes("list", &1_u64.to_be_bytes()),
+            Some(vec![2])
+        );
+    }
 }
@@ -7,10 +7,14 @@
 //!
 //! - `Access` is implemented for [`RawAccess`]es, that is, types that provide access to the
 //!   entire database. [`Snapshot`], [`Fork`] and [`ReadonlyFork`] fall into this category.
+//!   Snapshots can be used both by reference (`&dyn Snapshot`, `&Box<dyn Snapshot>`)
+//!   and via shared pointers (`Rc<dyn Snapshot>`, `Arc<dyn Snapshot>`, [`OwnedSnapshot`]);
+//!   the latter can be cloned cheaply and do not borrow from the database.
 //! - [`Prefixed`] restricts an access to a single *namespace*.
 //! - [`Migration`]s are used for data created during [migrations]. Similar to `Prefixed`, migrations
 //!   are separated by namespaces.
 //! - [`Scratchpad`]s can be used for temporary data. They are distinguished by namespaces as well.
+//! - [`TrackingAccess`] wraps a `RawAccess` and records entries read via it.
 //!
 //! [`CopyAccessExt`] extends [`Access`] and provides helper methods to instantiate indexes. This
 //! is useful in quick-and-dirty testing. For more complex applications, consider deriving
@@ -34,16 +38,21 @@
 //! [`Snapshot`]: ../trait.Snapshot.html
 //! [`Fork`]: ../struct.Fork.html
 //! [`ReadonlyFork`]: ../struct.ReadonlyFork.html
+//! [`OwnedSnapshot`]: ../struct.OwnedSnapshot.html
 //! [`Prefixed`]: struct.Prefixed.html
 //! [`Migration`]: ../migration/struct.Migration.html
 //! [migrations]: ../migration/index.html
 //! [`Scratchpad`]: ../migration/struct.Scratchpad.html
+//! [`TrackingAccess`]: struct.TrackingAccess.html
 //! [`CopyAccessExt`]: trait.CopyAccessExt.html
 //! [`FromAccess`]: trait.FromAccess.html
 
 use thiserror::Error;
 
-use std::fmt;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
 
 pub use self::extensions::{AccessExt, CopyAccessExt};
 pub use crate::views::{AsReadonly, RawAccess, RawAccessMut};
@@ -51,7 +60,7 @@ pub use crate::views::{AsReadonly, RawAccess, RawAccessMut};
 use crate::{
     validation::assert_valid_name_component,
     views::{GroupKeys, IndexAddress, IndexMetadata, IndexType, ViewWithMetadata},
-    BinaryKey,
+    BinaryKey, Database, Fork, ReadSet, ResolvedAddress, Snapshot,
 };
 
 mod extensions;
@@ -142,6 +151,11 @@ impl<T: RawAccess> Access for T {
 /// index in `Prefixed` accesses do not intersect with indexes in special-purpose `Access`
 /// implementations ([`Migration`]s and [`Scratchpad`]s).
 ///
+/// The wrapped access cannot be retrieved from a `Prefixed` instance (there is no
+/// `into_inner` method), since this would allow any holder of the instance to reach
+/// indexes outside its namespace. Code that needs both prefixed and unprefixed access
+/// should keep a copy of the unprefixed access separately.
+///
 /// [`Migration`]: ../migration/struct.Migration.html
 /// [`Scratchpad`]: ../migration/struct.Scratchpad.html
 ///
@@ -188,6 +202,96 @@ impl<T: RawAccess> Prefixed<T> {
         assert_valid_name_component(prefix.as_ref());
         Self { access, prefix }
     }
+
+    /// Returns the prefix prepended to the names of indexes accessed via this instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::Prefixed, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let prefixed = Prefixed::new("namespace", &fork);
+    /// assert_eq!(prefixed.prefix(), "namespace");
+    /// ```
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+}
+
+/// Fork shared by several subsystems, each of which operates in its own namespace.
+/// Changes in all namespaces are committed to the database atomically with a single merge.
+///
+/// A `SharedFork` hands out [`Prefixed`] accesses borrowing the underlying fork, so
+/// the subsystems cannot merge the fork themselves. The fork can only be committed
+/// with [`commit`], which consumes the `SharedFork`; thus, the borrow checker ensures that
+/// no namespace access outlives the commit.
+///
+/// [`Prefixed`]: struct.Prefixed.html
+/// [`commit`]: #method.commit
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::{CopyAccessExt, Prefixed, SharedFork}, Database, TemporaryDB};
+/// fn add_user(tenant: Prefixed<&metaldb::Fork>, name: &str) {
+///     tenant.get_list("users").push(name.to_owned());
+/// }
+///
+/// let db = TemporaryDB::new();
+/// let shared = SharedFork::new(db.fork());
+/// add_user(shared.namespace("tenant_a"), "Alice");
+/// add_user(shared.namespace("tenant_b"), "Bob");
+/// shared.commit(&db).unwrap();
+///
+/// let snapshot = db.snapshot();
+/// assert_eq!(snapshot.get_list::<_, String>("tenant_a.users").len(), 1);
+/// assert_eq!(snapshot.get_list::<_, String>("tenant_b.users").len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct SharedFork {
+    fork: Fork,
+}
+
+impl SharedFork {
+    /// Wraps the specified fork.
+    pub fn new(fork: Fork) -> Self {
+        Self { fork }
+    }
+
+    /// Returns an access to the namespace with the specified name. Several accesses
+    /// to the same or different namespaces may exist simultaneously.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is not a [valid prefix name].
+    ///
+    /// [valid prefix name]: ../validation/fn.is_valid_index_name_component.html
+    pub fn namespace(&self, prefix: impl Into<String>) -> Prefixed<&Fork> {
+        Prefixed::new(prefix, &self.fork)
+    }
+
+    /// Merges changes in all namespaces into the database atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same situations as [`Database::merge`].
+    ///
+    /// [`Database::merge`]: ../trait.Database.html#tymethod.merge
+    pub fn commit(self, db: &dyn Database) -> crate::Result<()> {
+        db.merge(self.fork.into_patch())
+    }
+
+    /// Discards the shared fork, returning the underlying fork with all changes.
+    pub fn into_fork(self) -> Fork {
+        self.fork
+    }
+}
+
+impl From<Fork> for SharedFork {
+    fn from(fork: Fork) -> Self {
+        Self::new(fork)
+    }
 }
 
 impl<T: RawAccess> Access for Prefixed<T> {
@@ -217,6 +321,105 @@ impl<T: RawAccess> Access for Prefixed<T> {
     }
 }
 
+/// Access recording entries read from the database via the wrapped access into a [`ReadSet`].
+/// The read set can be used, e.g., to invalidate cached results of a computation once
+/// any of the entries it has read is changed.
+///
+/// `TrackingAccess` implements [`RawAccess`], so it can be used in place of the wrapped access
+/// everywhere, including schemas deriving [`FromAccess`]. Clones of the access share
+/// the read set. The recorded reads and their limitations are the same as for forks created
+/// with [`Database::fork_tracking`]; in particular, reads of index metadata are recorded
+/// as well. Entries read from changes in the wrapped access (e.g., entries written
+/// to the wrapped fork) are not recorded, since they do not depend on the database state.
+///
+/// [`ReadSet`]: ../struct.ReadSet.html
+/// [`RawAccess`]: trait.RawAccess.html
+/// [`FromAccess`]: trait.FromAccess.html
+/// [`Database::fork_tracking`]: ../trait.Database.html#method.fork_tracking
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::{AccessExt, CopyAccessExt, TrackingAccess}, Database, TemporaryDB};
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// fork.get_map("prices").put(&1_u64, 100_u64);
+/// fork.get_map("prices").put(&2_u64, 200_u64);
+/// db.merge(fork.into_patch()).unwrap();
+///
+/// let snapshot = db.snapshot();
+/// let access = TrackingAccess::new(&snapshot);
+/// let price = access.get_map::<_, u64, u64>("prices").get(&1);
+/// assert_eq!(price, Some(100));
+///
+/// let read_set = access.into_read_set();
+/// let price_keys: Vec<_> = read_set
+///     .keys()
+///     .filter(|(address, _)| address.name == "prices")
+///     .map(|(_, key)| key.to_vec())
+///     .collect();
+/// assert_eq!(price_keys, vec![1_u64.to_be_bytes().to_vec()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrackingAccess<T> {
+    access: T,
+    reads: Arc<Mutex<ReadSet>>,
+}
+
+impl<T: RawAccess> TrackingAccess<T> {
+    /// Wraps the specified access.
+    pub fn new(access: T) -> Self {
+        Self {
+            access,
+            reads: Arc::default(),
+        }
+    }
+
+    /// Returns the entries read via this access and its clones so far.
+    pub fn read_set(&self) -> ReadSet {
+        self.reads.lock().expect("Failed to lock read set").clone()
+    }
+
+    /// Returns the entries read via this access and its clones. Unlike [`read_set`],
+    /// this method does not copy the read set if there are no other clones of the access
+    /// (e.g., held by indexes created from it).
+    ///
+    /// [`read_set`]: #method.read_set
+    pub fn into_read_set(self) -> ReadSet {
+        match Arc::try_unwrap(self.reads) {
+            Ok(reads) => reads.into_inner().expect("Failed to lock read set"),
+            Err(reads) => reads.lock().expect("Failed to lock read set").clone(),
+        }
+    }
+}
+
+impl<T: RawAccess> RawAccess for TrackingAccess<T> {
+    type Changes = T::Changes;
+
+    fn snapshot(&self) -> &dyn Snapshot {
+        self.access.snapshot()
+    }
+
+    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
+        self.access.changes(address)
+    }
+
+    fn tracked_reads(&self) -> Option<&Mutex<ReadSet>> {
+        Some(&self.reads)
+    }
+}
+
+impl<T: AsReadonly> AsReadonly for TrackingAccess<T> {
+    type Readonly = TrackingAccess<T::Readonly>;
+
+    fn as_readonly(&self) -> Self::Readonly {
+        TrackingAccess {
+            access: self.access.as_readonly(),
+            reads: Arc::clone(&self.reads),
+        }
+    }
+}
+
 /// Access error together with the location information.
 #[derive(Debug, Error)]
 pub struct AccessError {
@@ -269,6 +472,13 @@ pub enum AccessErrorKind {
     #[error("Invalid tombstone location. Tombstones can only be created in migrations")]
     InvalidTombstone,
 
+    /// Key hashing was requested for an index type which does not support it.
+    #[error("Key hashing is not supported for indexes of type {:?}", index_type)]
+    UnsupportedKeyHashing {
+        /// Type of the accessed index.
+        index_type: IndexType,
+    },
+
     /// Custom error.
     #[error("{0}")]
     Custom(#[source] anyhow::Error),
@@ -363,8 +573,13 @@ pub trait FromAccess<T: Access>: Sized {
 
 #[cfg(test)]
 mod tests {
-    use super::{Access, AccessExt, CopyAccessExt, FromAccess, IndexType, Prefixed};
-    use crate::{Database, ListIndex, TemporaryDB};
+    use super::{
+        Access, AccessExt, CopyAccessExt, FromAccess, IndexType, Prefixed, SharedFork,
+        TrackingAccess,
+    };
+    use crate::{Database, ListIndex, MergeError, Snapshot, TemporaryDB};
+
+    use std::{rc::Rc, sync::Arc, thread};
 
     #[test]
     fn prefixed_works() {
@@ -393,6 +608,29 @@ mod tests {
         assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
     }
 
+    #[test]
+    fn shared_fork_commits_namespaces_atomically() {
+        let db = TemporaryDB::new();
+        let shared = SharedFork::new(db.fork());
+        {
+            let first = shared.namespace("first");
+            let second = shared.namespace("second");
+            first.get_entry("entry").set(1_u32);
+            second.get_entry("entry").set(2_u32);
+            // Namespaced changes are visible to each other via the shared fork.
+            assert_eq!(
+                shared.namespace("first").get_entry::<_, u32>("entry").get(),
+                Some(1)
+            );
+        }
+        assert!(db.snapshot().index_type("first.entry").is_none());
+        shared.commit(&db).unwrap();
+
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_entry::<_, u32>("first.entry").get(), Some(1));
+        assert_eq!(snapshot.get_entry::<_, u32>("second.entry").get(), Some(2));
+    }
+
     #[test]
     fn prefixed_views_do_not_collide() {
         let db = TemporaryDB::new();
@@ -440,6 +678,33 @@ mod tests {
         assert!(!view.is_phantom());
     }
 
+    #[test]
+    fn shared_snapshot_access() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u64, 2, 3]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot: Rc<dyn Snapshot> = Rc::from(db.snapshot());
+        let list: ListIndex<_, u64> =
+            FromAccess::from_access(Rc::clone(&snapshot), "list".into()).unwrap();
+        assert_eq!(list.len(), 3);
+        let prefixed = Prefixed::new("foo", snapshot);
+        assert!(prefixed.get_list::<_, u64>("list").is_empty());
+
+        let snapshot: Arc<dyn Snapshot> = Arc::from(db.snapshot());
+        let handle = {
+            let snapshot = Arc::clone(&snapshot);
+            thread::spawn(move || {
+                let list: ListIndex<_, u64> =
+                    FromAccess::from_access(snapshot, "list".into()).unwrap();
+                list.iter().sum::<u64>()
+            })
+        };
+        assert_eq!(handle.join().unwrap(), 6);
+        assert_eq!(snapshot.get_list::<_, u64>("list").len(), 3);
+    }
+
     #[test]
     fn from_root_method() {
         let db = TemporaryDB::new();
@@ -451,4 +716,63 @@ mod tests {
         }
         assert_eq!(fork.get_list::<_, u64>("foo").len(), 3);
     }
+
+    #[test]
+    fn tracking_access_records_reads() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u64, 2, 3]);
+        fork.get_map("map").put(&1_u8, 10_u64);
+        fork.get_entry("entry").set(0_u64);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let access = TrackingAccess::new(&fork);
+        {
+            let sum: u64 = access.get_list::<_, u64>("list").iter().sum();
+            let map = access.get_map::<_, u8, u64>("map");
+            assert!(map.contains(&1));
+            assert!(!map.contains(&2));
+            let mut entry = access.get_entry::<_, u64>("entry");
+            entry.set(sum);
+            // Entries written via the access are read from the fork and are not recorded.
+            assert_eq!(entry.get(), Some(6));
+        }
+
+        let read_set = access.read_set();
+        let keys: Vec<_> = read_set
+            .keys()
+            .filter(|(address, _)| address.name != "__INDEXES_POOL__")
+            .map(|(address, key)| (address.name.as_str(), key.to_vec()))
+            .collect();
+        assert_eq!(keys.len(), 5);
+        assert!(keys.contains(&("list", 2_u64.to_be_bytes().to_vec())));
+        assert!(keys.contains(&("map", vec![2])));
+        assert!(keys.iter().all(|(name, _)| *name != "entry"));
+
+        // The read set can be used to merge the fork optimistically.
+        let other_fork = db.fork();
+        other_fork.get_map("map").put(&2_u8, 20_u64);
+        db.merge(other_fork.into_patch()).unwrap();
+        let read_set = access.into_read_set();
+        let err = db.merge_checked(fork.into_patch(), read_set).unwrap_err();
+        assert!(matches!(err, MergeError::Conflict(_)));
+    }
+
+    #[test]
+    fn tracking_access_for_readonly_snapshot() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(1_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let access = TrackingAccess::new(&snapshot);
+        let entry = access.get_entry::<_, u32>("entry");
+        assert_eq!(entry.get(), Some(1));
+        // Indexes keep clones of the access alive, which share the read set.
+        let read_set = access.into_read_set();
+        assert!(read_set.keys().any(|(address, _)| address.name == "entry"));
+        drop(entry);
+    }
 }
@@ -10,15 +10,35 @@ pub mod backup {
 
 use crossbeam::sync::{ShardedLock, ShardedLockReadGuard};
 use rocksdb::{
-    self, checkpoint::Checkpoint, Cache as RocksDBCache, ColumnFamily, DBIterator,
-    Options as RocksDBOptions, WriteBatch, WriteOptions as RocksDBWriteOptions,
+    self, checkpoint::Checkpoint, BlockBasedOptions, Cache as RocksDBCache, ColumnFamily,
+    ColumnFamilyDescriptor, DBIterator, Options as RocksDBOptions, ReadOptions, SliceTransform,
+    WriteBatch, WriteOptions as RocksDBWriteOptions,
 };
 use smallvec::SmallVec;
-use std::{fmt, iter, iter::Peekable, mem, path::Path, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt, fs,
+    io::{Read, Write},
+    iter,
+    iter::Peekable,
+    mem,
+    num::NonZeroUsize,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
 
 use crate::{
-    db::{check_database, Change},
-    DBOptions, Database, Iter, Iterator, Patch, ResolvedAddress, Snapshot,
+    access::Access,
+    db::{check_database, Change, KeyRange, SYSTEM_VIEW_NAMES},
+    latest::{copy_entries, LatestState},
+    metrics::MetricsHook,
+    migration::SCRATCHPAD_NAME,
+    validation::ConstraintSet,
+    views::PREFIX_LENGTHS_NAME,
+    BinaryValue, DBOptions, Database, Fork, IndexAddress, Iter, Iterator, MergeError, MetricsSink,
+    Patch, ReadSet, ResolvedAddress, Snapshot, ViewChanges, WriteDurability,
 };
 
 /// Size of a byte representation of an index ID, which is used to prefix index keys
@@ -35,8 +55,27 @@ pub const ID_SIZE: usize = mem::size_of::<u64>();
 pub struct RocksDB {
     db: Arc<ShardedLock<rocksdb::DB>>,
     options: DBOptions,
+    // Serializes merges, so that `merge_checked` and `merge_validated` can validate patches
+    // atomically.
+    merge_lock: Arc<Mutex<()>>,
+    metrics: MetricsHook,
+    // Key prefix lengths of column families opened or created with a prefix extractor.
+    prefix_lens: PrefixLens,
 }
 
+/// Key prefix lengths of column families, excluding the index ID.
+type PrefixLens = Arc<ShardedLock<HashMap<String, usize>>>;
+
+/// Bits per key in Bloom filters of column families with a prefix extractor.
+const BLOOM_BITS_PER_KEY: f64 = 10.0;
+/// Size of the memtable prefix Bloom filter relative to the write buffer size.
+const MEMTABLE_PREFIX_BLOOM_RATIO: f64 = 0.1;
+/// Refill period of the background IO rate limiter, in microseconds (the `RocksDB` default).
+const RATE_LIMITER_REFILL_PERIOD_US: i64 = 100_000;
+/// Fairness of the background IO rate limiter between low- and high-priority requests
+/// (the `RocksDB` default).
+const RATE_LIMITER_FAIRNESS: i32 = 10;
+
 impl From<DBOptions> for RocksDBOptions {
     fn from(opts: DBOptions) -> Self {
         Self::from(&opts)
@@ -45,25 +84,66 @@ impl From<DBOptions> for RocksDBOptions {
 
 impl From<&DBOptions> for RocksDBOptions {
     fn from(opts: &DBOptions) -> Self {
-        let mut defaults = Self::default();
-        defaults.create_if_missing(opts.create_if_missing);
-        defaults.set_compression_type(opts.compression_type.into());
-        defaults.set_max_open_files(opts.max_open_files.unwrap_or(-1));
-        defaults.set_max_total_wal_size(opts.max_total_wal_size.unwrap_or(0));
-        if let Some(capacity) = opts.max_cache_size {
-            defaults.set_row_cache(
-                &RocksDBCache::new_lru_cache(capacity)
-                    .expect("Failed to instantiate `Cache` for `RocksDB`"),
-            );
+        rocksdb_options(opts, None)
+    }
+}
+
+/// Converts database options into `RocksDB` options. If `prefix_len` is specified,
+/// the options configure a fixed-length prefix extractor for keys prefixed by the index ID
+/// together with prefix Bloom filters.
+fn rocksdb_options(opts: &DBOptions, prefix_len: Option<usize>) -> RocksDBOptions {
+    let mut defaults = RocksDBOptions::default();
+    defaults.create_if_missing(opts.create_if_missing);
+    defaults.set_compression_type(opts.compression_type.into());
+    defaults.set_max_open_files(opts.max_open_files.unwrap_or(-1));
+    defaults.set_max_total_wal_size(opts.max_total_wal_size.unwrap_or(0));
+    if let Some(capacity) = opts.max_cache_size {
+        defaults.set_row_cache(
+            &RocksDBCache::new_lru_cache(capacity)
+                .expect("Failed to instantiate `Cache` for `RocksDB`"),
+        );
+    }
+    if let Some(size) = opts.write_buffer_size {
+        defaults.set_write_buffer_size(size);
+    }
+    if let Some(number) = opts.max_write_buffer_number {
+        defaults.set_max_write_buffer_number(number);
+    }
+    if opts.block_size.is_some() || prefix_len.is_some() {
+        let mut table_options = BlockBasedOptions::default();
+        if let Some(block_size) = opts.block_size {
+            table_options.set_block_size(block_size);
         }
-        defaults
+        if let Some(prefix_len) = prefix_len {
+            table_options.set_bloom_filter(BLOOM_BITS_PER_KEY, false);
+            defaults
+                .set_prefix_extractor(SliceTransform::create_fixed_prefix(ID_SIZE + prefix_len));
+            defaults.set_memtable_prefix_bloom_ratio(MEMTABLE_PREFIX_BLOOM_RATIO);
+        }
+        defaults.set_block_based_table_factory(&table_options);
+    }
+    if opts.enable_statistics {
+        defaults.enable_statistics();
+    }
+    if let Some(paranoid_checks) = opts.paranoid_checks {
+        defaults.set_paranoid_checks(paranoid_checks);
     }
+    defaults.set_wal_recovery_mode(opts.on_corruption.into());
+    if let Some(bytes_per_sec) = opts.background_io_bytes_per_sec.filter(|&rate| rate > 0) {
+        defaults.set_ratelimiter(
+            bytes_per_sec,
+            RATE_LIMITER_REFILL_PERIOD_US,
+            RATE_LIMITER_FAIRNESS,
+        );
+    }
+    defaults
 }
 
 /// A snapshot of a `RocksDB`.
 pub struct RocksDBSnapshot {
     snapshot: rocksdb::Snapshot<'static>,
     db: Arc<ShardedLock<rocksdb::DB>>,
+    prefix_lens: PrefixLens,
 }
 
 /// An iterator over the entries of a `RocksDB`.
@@ -82,17 +162,111 @@ impl RocksDB {
     /// `create_if_missing` is switched on in `DBOptions`, a new database will
     /// be created at the indicated path.
     pub fn open<P: AsRef<Path>>(path: P, options: &DBOptions) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut prefix_lens = HashMap::new();
         let inner = {
-            if let Ok(names) = rocksdb::DB::list_cf(&RocksDBOptions::default(), &path) {
-                let cf_names = names.iter().map(String::as_str).collect::<Vec<_>>();
-                rocksdb::DB::open_cf(&options.into(), path, cf_names)?
+            if let Ok(names) = rocksdb::DB::list_cf(&RocksDBOptions::default(), path) {
+                if names.iter().any(|name| name == PREFIX_LENGTHS_NAME) {
+                    prefix_lens = read_prefix_lens(path);
+                }
+                let descriptors = cf_descriptors(&names, options, &prefix_lens);
+                rocksdb::DB::open_cf_descriptors(&options.into(), path, descriptors)?
             } else {
                 rocksdb::DB::open(&options.into(), path)?
             }
         };
+        Self::from_inner(inner, options, prefix_lens)
+    }
+
+    /// Opens a database stored at the specified path, opening only the specified column
+    /// families. For applications with a fixed schema, this bounds the resources used
+    /// by the database and ensures that it contains no unexpected indexes.
+    ///
+    /// Each index is stored in the column family named after the index; indexes in a group
+    /// share the column family named after the group. Column families used by the database
+    /// internally (including the column family of [`Scratchpad`]s used in migrations)
+    /// are opened automatically. Listed column families missing in the database
+    /// are created; column families created after opening the database (e.g., for new indexes)
+    /// are handled as usual.
+    ///
+    /// [`Scratchpad`]: migration/struct.Scratchpad.html
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database contains column families not listed in `cf_names`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_list("list").push(1_u32);
+    /// fork.get_entry(("group", &1_u8)).set(2_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    /// drop(db);
+    ///
+    /// let options = DBOptions::default();
+    /// let db = RocksDB::open_with_cfs(temp_dir.path(), &options, &["list", "group"]).unwrap();
+    /// assert_eq!(db.snapshot().get_list::<_, u32>("list").len(), 1);
+    /// drop(db);
+    /// assert!(RocksDB::open_with_cfs(temp_dir.path(), &options, &["list"]).is_err());
+    /// ```
+    pub fn open_with_cfs<P: AsRef<Path>>(
+        path: P,
+        options: &DBOptions,
+        cf_names: &[&str],
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut rocksdb_options = RocksDBOptions::from(options);
+        rocksdb_options.create_missing_column_families(true);
+        // Column families used by the database internally are always opened.
+        let mut all_cf_names = SYSTEM_VIEW_NAMES.to_vec();
+        all_cf_names.extend_from_slice(cf_names);
+
+        // Listing column families fails if the database does not exist yet.
+        let existing_cf_names =
+            rocksdb::DB::list_cf(&RocksDBOptions::default(), path).unwrap_or_default();
+        if existing_cf_names.iter().any(|name| name == SCRATCHPAD_NAME) {
+            all_cf_names.push(SCRATCHPAD_NAME);
+        }
+        let mut unlisted: Vec<_> = existing_cf_names
+            .iter()
+            .map(String::as_str)
+            .filter(|&name| {
+                name != rocksdb::DEFAULT_COLUMN_FAMILY_NAME && !all_cf_names.contains(&name)
+            })
+            .collect();
+        if !unlisted.is_empty() {
+            unlisted.sort_unstable();
+            return Err(crate::Error::new(format!(
+                "Database at {} contains column families not listed in `cf_names`: {}",
+                path.display(),
+                unlisted.join(", ")
+            )));
+        }
+        all_cf_names.sort_unstable();
+        all_cf_names.dedup();
+
+        let prefix_lens = read_prefix_lens(path);
+        let descriptors = cf_descriptors(&all_cf_names, options, &prefix_lens);
+        let inner = rocksdb::DB::open_cf_descriptors(&rocksdb_options, path, descriptors)?;
+        Self::from_inner(inner, options, prefix_lens)
+    }
+
+    fn from_inner(
+        inner: rocksdb::DB,
+        options: &DBOptions,
+        prefix_lens: HashMap<String, usize>,
+    ) -> crate::Result<Self> {
         let mut db = Self {
             db: Arc::new(ShardedLock::new(inner)),
             options: *options,
+            merge_lock: Arc::new(Mutex::new(())),
+            metrics: MetricsHook::default(),
+            prefix_lens: Arc::new(ShardedLock::new(prefix_lens)),
         };
         check_database(&mut db)?;
         Ok(db)
@@ -111,25 +285,496 @@ impl RocksDB {
         Ok(())
     }
 
+    /// Creates a checkpoint of this database and writes it into `writer` as a tar archive.
+    ///
+    /// The checkpoint is created in a temporary directory, which is removed afterwards.
+    /// To compress the archive, wrap the writer into a compressing one (e.g., a gzip encoder
+    /// from the `flate2` crate). The archive can be unpacked with [`restore_from_archive`].
+    ///
+    /// The checkpoint reflects the database state at a single point in time, but merges
+    /// performed concurrently with this call may or may not be included into it. For a
+    /// predictable backup, avoid writing to the database while the checkpoint is created.
+    ///
+    /// [`restore_from_archive`]: #method.restore_from_archive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path().join("db"), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_entry("entry").set(42_u64);
+    /// db.merge_sync(fork.into_patch()).unwrap();
+    ///
+    /// let mut archive = vec![];
+    /// db.create_backup_archive(&mut archive).unwrap();
+    ///
+    /// let restored_path = temp_dir.path().join("restored");
+    /// RocksDB::restore_from_archive(&archive[..], &restored_path).unwrap();
+    /// let restored = RocksDB::open(&restored_path, &DBOptions::default()).unwrap();
+    /// let snapshot = restored.snapshot();
+    /// assert_eq!(snapshot.get_entry::<_, u64>("entry").get(), Some(42));
+    /// ```
+    pub fn create_backup_archive<W: Write>(&self, writer: W) -> crate::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let checkpoint_path = temp_dir.path().join("checkpoint");
+        self.create_checkpoint(&checkpoint_path)?;
+
+        let mut builder = tar::Builder::new(writer);
+        builder.append_dir_all(".", &checkpoint_path)?;
+        builder.into_inner()?;
+        Ok(())
+    }
+
+    /// Unpacks an archive created by [`create_backup_archive`] into the specified directory.
+    /// The restored database can then be opened with [`open`](#method.open).
+    ///
+    /// Returns an error if the directory exists and is not empty.
+    ///
+    /// [`create_backup_archive`]: #method.create_backup_archive
+    pub fn restore_from_archive<R: Read, P: AsRef<Path>>(reader: R, path: P) -> crate::Result<()> {
+        let path = path.as_ref();
+        if path.exists() && fs::read_dir(path)?.next().is_some() {
+            return Err(crate::Error::new(format!(
+                "Cannot restore database into non-empty directory {}",
+                path.display()
+            )));
+        }
+        tar::Archive::new(reader).unpack(path)?;
+        Ok(())
+    }
+
     /// Retrieves read lock guard containing underlying `rocksdb::DB`.
     pub fn get_db_lock_guard(&self) -> ShardedLockReadGuard<'_, rocksdb::DB> {
         self.db.read().expect("Failed to get read lock to DB")
     }
 
+    /// Returns the sequence number of the most recent write to the database.
+    pub fn current_sequence(&self) -> u64 {
+        self.get_db_lock_guard().latest_sequence_number()
+    }
+
+    /// Returns internal `RocksDB` statistics formatted as a human-readable string.
+    /// The statistics include cache hit rates, compaction stats, read and write latencies, etc.
+    ///
+    /// Returns `None` if statistics are not [enabled] in the database options,
+    /// or if they cannot be retrieved.
+    ///
+    /// [enabled]: ../struct.DBOptions.html#structfield.enable_statistics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let mut options = DBOptions::default();
+    /// options.enable_statistics = true;
+    /// let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    /// let stats = db.statistics().unwrap();
+    /// assert!(stats.contains("rocksdb.block.cache.hit"));
+    ///
+    /// let other_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(other_dir.path(), &DBOptions::default()).unwrap();
+    /// assert!(db.statistics().is_none());
+    /// ```
+    pub fn statistics(&self) -> Option<String> {
+        if !self.options.enable_statistics {
+            return None;
+        }
+        self.get_db_lock_guard()
+            .property_value("rocksdb.options-statistics")
+            .ok()
+            .flatten()
+    }
+
+    /// Returns the value of a `RocksDB` property for the column family storing the index
+    /// with the specified address. Returns `None` if the column family does not exist,
+    /// the property is unknown, or its value cannot be retrieved.
+    ///
+    /// Note that indexes in a group share a column family, so properties of an index
+    /// in a group cover the entire group.
+    ///
+    /// Commonly used properties include:
+    ///
+    /// - `rocksdb.estimate-num-keys`: estimated number of keys in the column family
+    /// - `rocksdb.estimate-live-data-size`: estimated size of live data in bytes
+    /// - `rocksdb.total-sst-files-size`: total size of SST files in bytes
+    /// - `rocksdb.size-all-mem-tables`: size of active and unflushed memtables in bytes
+    /// - `rocksdb.num-files-at-level<N>`: number of SST files at level `N`
+    /// - `rocksdb.cfstats`: human-readable statistics of the column family
+    ///
+    /// See [`RocksDB` sources] for the full list of properties. Use [`property_int`]
+    /// for properties with integer values.
+    ///
+    /// [`RocksDB` sources]: https://github.com/facebook/rocksdb/blob/main/include/rocksdb/db.h
+    /// [`property_int`]: #method.property_int
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(0_u64..100);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let stats = db.property(&"list".into(), "rocksdb.cfstats").unwrap();
+    /// assert!(!stats.is_empty());
+    /// assert!(db.property(&"other_list".into(), "rocksdb.cfstats").is_none());
+    /// ```
+    pub fn property(&self, addr: &IndexAddress, name: &str) -> Option<String> {
+        let db = self.get_db_lock_guard();
+        let cf = db.cf_handle(addr.name())?;
+        db.property_value_cf(cf, name).ok().flatten()
+    }
+
+    /// Returns the value of an integer `RocksDB` property for the column family storing
+    /// the index with the specified address. See [`property`] for details.
+    ///
+    /// [`property`]: #method.property
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(0_u64..100);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let keys = db.property_int(&"list".into(), "rocksdb.estimate-num-keys").unwrap();
+    /// assert!(keys > 0);
+    /// ```
+    pub fn property_int(&self, addr: &IndexAddress, name: &str) -> Option<u64> {
+        let db = self.get_db_lock_guard();
+        let cf = db.cf_handle(addr.name())?;
+        db.property_int_value_cf(cf, name).ok().flatten()
+    }
+
+    /// Returns the number of snapshots of the database that are not yet released.
+    ///
+    /// Each live snapshot prevents compaction from discarding data overwritten or removed
+    /// after the snapshot was taken, so a count growing over time usually indicates
+    /// snapshots leaked by the application. Note that snapshots are held not only by
+    /// [`Snapshot`]s, but also by [`Fork`]s and the [`Patch`]es created from them until
+    /// the patches are merged. Together with [`oldest_snapshot_sequence()`],
+    /// this method can be used to detect such leaks.
+    ///
+    /// [`oldest_snapshot_sequence()`]: #method.oldest_snapshot_sequence
+    /// [`Snapshot`]: ../trait.Snapshot.html
+    /// [`Fork`]: ../struct.Fork.html
+    /// [`Patch`]: ../struct.Patch.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// assert_eq!(db.live_snapshot_count(), 0);
+    ///
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(db.live_snapshot_count(), 1);
+    /// drop(snapshot);
+    /// assert_eq!(db.live_snapshot_count(), 0);
+    /// ```
+    pub fn live_snapshot_count(&self) -> usize {
+        self.get_db_lock_guard()
+            .property_int_value("rocksdb.num-snapshots")
+            .ok()
+            .flatten()
+            .map_or(0, |count| count as usize)
+    }
+
+    /// Returns the sequence number of the oldest snapshot of the database that is not yet
+    /// released, or `None` if there are no live snapshots.
+    ///
+    /// Comparing the returned value to [`current_sequence()`] shows how far behind the latest
+    /// database state the oldest snapshot is.
+    ///
+    /// [`current_sequence()`]: #method.current_sequence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// assert_eq!(db.oldest_snapshot_sequence(), None);
+    ///
+    /// let snapshot = db.snapshot();
+    /// let sequence = db.current_sequence();
+    /// let fork = db.fork();
+    /// fork.get_list("list").push(1_u64);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// assert_eq!(db.oldest_snapshot_sequence(), Some(sequence));
+    /// assert!(db.current_sequence() > sequence);
+    /// drop(snapshot);
+    /// assert_eq!(db.oldest_snapshot_sequence(), None);
+    /// ```
+    pub fn oldest_snapshot_sequence(&self) -> Option<u64> {
+        if self.live_snapshot_count() == 0 {
+            // `RocksDB` reports zero as the oldest sequence if there are no snapshots.
+            return None;
+        }
+        self.get_db_lock_guard()
+            .property_int_value("rocksdb.oldest-snapshot-sequence")
+            .ok()
+            .flatten()
+    }
+
+    /// Creates a snapshot of the database together with the sequence number of the most recent
+    /// write included into the snapshot.
+    ///
+    /// The snapshot and the sequence number are captured atomically with respect to merges,
+    /// so the sequence number can be used to label the snapshot, e.g., to check whether
+    /// the database has been written to since the snapshot was taken by comparing
+    /// the number to [`current_sequence()`]. `RocksDB` does not retain historical states
+    /// of the database unless they are held by a snapshot, so to read the database
+    /// at a certain point in time later, keep the returned snapshot alive.
+    ///
+    /// [`current_sequence()`]: #method.current_sequence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let (snapshot, sequence) = db.snapshot_with_sequence();
+    /// assert_eq!(sequence, db.current_sequence());
+    ///
+    /// let fork = db.fork();
+    /// fork.get_entry("entry").set(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    /// // The snapshot is outdated, but still reflects the database state at `sequence`.
+    /// assert!(db.current_sequence() > sequence);
+    /// assert!(!snapshot.get_entry::<_, u32>("entry").exists());
+    /// ```
+    pub fn snapshot_with_sequence(&self) -> (Box<dyn Snapshot>, u64) {
+        // All writes except for writes by the spilling `TemporaryDB` (which does not expose
+        // the underlying `RocksDB`) are performed under the merge lock.
+        let _guard = self.lock_merges();
+        self.metrics.snapshot_created();
+        let snapshot = self.rocksdb_snapshot();
+        let sequence = self.current_sequence();
+        (Box::new(snapshot), sequence)
+    }
+
+    /// Scans the specified indexes in a background thread in order to populate the in-memory
+    /// cache, so that the first reads from these indexes do not hit the disk.
+    ///
+    /// The cache is configured with the [`max_cache_size`] option. If the cache is not
+    /// configured, this method is a no-op and returns `None`. Otherwise, the method returns
+    /// a handle to the spawned thread. The scan stops once the total size of the scanned
+    /// entries reaches `max_cache_size`, since loading more data would only evict
+    /// the previously loaded entries. Addresses without an index are skipped.
+    ///
+    /// [`max_cache_size`]: ../struct.DBOptions.html#structfield.max_cache_size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let mut options = DBOptions::default();
+    /// options.max_cache_size = Some(1 << 20);
+    /// let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(0_u64..100);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let handle = db.warm_cache(&["list".into()]).unwrap();
+    /// handle.join().unwrap();
+    ///
+    /// // Without the cache, there is nothing to warm up.
+    /// let other_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(other_dir.path(), &DBOptions::default()).unwrap();
+    /// assert!(db.warm_cache(&["list".into()]).is_none());
+    /// ```
+    pub fn warm_cache(&self, addresses: &[IndexAddress]) -> Option<JoinHandle<()>> {
+        let capacity = self.options.max_cache_size?;
+
+        let snapshot = self.snapshot();
+        let resolved_addrs: Vec<_> = addresses
+            .iter()
+            .filter_map(|addr| {
+                let metadata = snapshot.as_ref().get_index_metadata(addr.clone()).ok()??;
+                Some(ResolvedAddress::new(
+                    addr.name(),
+                    Some(metadata.identifier()),
+                ))
+            })
+            .collect();
+        drop(snapshot);
+
+        let db = Arc::clone(&self.db);
+        let handle = thread::spawn(move || {
+            use rocksdb::{Direction, IteratorMode};
+
+            let db = db.read().expect("Failed to get read lock to DB");
+            let mut loaded_size = 0;
+            for addr in &resolved_addrs {
+                let cf = if let Some(cf) = db.cf_handle(&addr.name) {
+                    cf
+                } else {
+                    continue;
+                };
+                let prefix = addr.keyed(&[]);
+                let iter = db.iterator_cf_opt(
+                    cf,
+                    total_order_read_options(),
+                    IteratorMode::From(&prefix, Direction::Forward),
+                );
+                for (key, value) in iter.take_while(|(key, _)| key.starts_with(&prefix)) {
+                    // The row cache is only populated by point lookups.
+                    let _ = db.get_pinned_cf(cf, &key);
+                    loaded_size += key.len() + value.len();
+                    if loaded_size >= capacity {
+                        return;
+                    }
+                }
+            }
+        });
+        Some(handle)
+    }
+
+    /// Atomically applies a patch to the database bypassing the write-ahead log (WAL).
+    ///
+    /// This considerably speeds up large imports, e.g., the initial data load.
+    ///
+    /// # Durability
+    ///
+    /// **Warning.** Data merged with this method is kept in memory until it is flushed
+    /// to the disk by `RocksDB` or by calling [`flush()`]. If the process crashes before that,
+    /// the merged data is lost, so the database may end up in the state preceding
+    /// any number of the latest bulk merges. Call `flush()` after the final `bulk_merge`
+    /// and do not mix bulk merges with ordinary ones unless this is acceptable.
+    /// [`merge`] and [`merge_sync`] always write to the WAL.
+    ///
+    /// [`flush()`]: ../trait.Database.html#method.flush
+    /// [`merge`]: ../trait.Database.html#tymethod.merge
+    /// [`merge_sync`]: ../trait.Database.html#tymethod.merge_sync
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// for chunk in 0..10_u64 {
+    ///     let fork = db.fork();
+    ///     fork.get_list("items").extend(chunk * 100..(chunk + 1) * 100);
+    ///     db.bulk_merge(fork.into_patch()).unwrap();
+    /// }
+    /// // Persist the imported data.
+    /// db.flush().unwrap();
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(snapshot.get_list::<_, u64>("items").len(), 1_000);
+    /// ```
+    pub fn bulk_merge(&self, patch: Patch) -> crate::Result<()> {
+        self.merge_with_options(patch, WriteDurability::NoWal)
+    }
+
+    /// Applies a patch to the database, writing changes to different column families
+    /// in parallel.
+    ///
+    /// This speeds up merging large patches spanning many column families, e.g., patches
+    /// touching many index families. Changes to each column family are written with
+    /// a separate write batch; the batches are distributed among threads, the number of which
+    /// is limited by the available parallelism. Changes to system views (e.g., index metadata)
+    /// are written with the final batch after all other batches have been written.
+    ///
+    /// # Atomicity
+    ///
+    /// **Warning.** Unlike [`merge`], this method is not atomic. Each column family
+    /// is updated atomically, but snapshots taken during the merge may observe some column
+    /// families updated and others not. If the merge fails or the process crashes
+    /// in the middle of it, the database is left with a part of the patch applied. Use this
+    /// method only when this is acceptable, e.g., for an initial data load which can be
+    /// restarted from scratch.
+    ///
+    /// [`merge`]: ../trait.Database.html#tymethod.merge
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// for i in 0..16_u64 {
+    ///     fork.get_list(format!("list_{}", i).as_str()).extend(0..i);
+    /// }
+    /// db.merge_parallel(fork.into_patch()).unwrap();
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(snapshot.get_list::<_, u64>("list_10").len(), 10);
+    /// ```
+    pub fn merge_parallel(&self, patch: Patch) -> crate::Result<()> {
+        self.metrics.observe_merge(patch, |patch| {
+            let _guard = self.lock_merges();
+            self.write_patch_parallel(patch)
+        })
+    }
+
     fn cf_exists(&self, cf_name: &str) -> bool {
         self.get_db_lock_guard().cf_handle(cf_name).is_some()
     }
 
-    fn create_cf(&self, cf_name: &str) -> crate::Result<()> {
+    fn create_cf(&self, cf_name: &str, prefix_len: Option<usize>) -> crate::Result<()> {
         self.db
             .write()
             .expect("Failed to get write lock to DB")
-            .create_cf(cf_name, &self.options.into())
-            .map_err(Into::into)
+            .create_cf(cf_name, &rocksdb_options(&self.options, prefix_len))?;
+        if let Some(prefix_len) = prefix_len {
+            self.prefix_lens
+                .write()
+                .expect("Failed to get write lock to prefix lengths")
+                .insert(cf_name.to_owned(), prefix_len);
+        }
+        Ok(())
+    }
+
+    /// Returns the key prefix length for a new column family storing indexes. The length
+    /// is taken from the patch being merged or, if the patch does not specify it, from
+    /// the database.
+    fn new_cf_prefix_len(
+        &self,
+        cf_name: &str,
+        new_prefix_lens: Option<&ViewChanges>,
+    ) -> Option<usize> {
+        let prefix_len = match new_prefix_lens.map(|changes| changes.get(cf_name.as_bytes())) {
+            Some(Ok(prefix_len)) => prefix_len,
+            _ => self.rocksdb_snapshot().get(
+                &ResolvedAddress::system(PREFIX_LENGTHS_NAME),
+                cf_name.as_bytes(),
+            ),
+        };
+        let prefix_len = u64::from_bytes(Cow::Owned(prefix_len?)).ok()?;
+        Some(prefix_len as usize)
     }
 
     /// Clears the column family completely, removing all keys from it.
     pub(super) fn clear_column_family(&self, batch: &mut WriteBatch, cf: &ColumnFamily) {
+        self.remove_keys_from(batch, cf, &[]);
+    }
+
+    /// Removes all keys greater or equal to `from` from the column family.
+    fn remove_keys_from(&self, batch: &mut WriteBatch, cf: &ColumnFamily, from: &[u8]) {
         /// Some lexicographically large key.
         const LARGER_KEY: &[u8] = &[u8::max_value(); 1_024];
 
@@ -138,66 +783,200 @@ impl RocksDB {
         iter.seek_to_last();
         if iter.valid() {
             if let Some(key) = iter.key() {
+                if key < from {
+                    return;
+                }
                 // For some reason, removing a range to a very large key is
                 // significantly faster than removing the exact range.
                 // This is specific to the debug mode, but since `TemporaryDB`
                 // is mostly used for testing, this optimization leads to practical
                 // performance improvement.
                 if key.len() < LARGER_KEY.len() {
-                    batch.delete_range_cf(cf, &[][..], LARGER_KEY);
+                    batch.delete_range_cf(cf, from, LARGER_KEY);
                 } else {
-                    batch.delete_range_cf(cf, &[][..], key);
+                    batch.delete_range_cf(cf, from, key);
                     batch.delete_cf(cf, &key);
                 }
             }
         }
     }
 
+    fn lock_merges(&self) -> MutexGuard<'_, ()> {
+        self.merge_lock.lock().expect("Failed to get merge lock")
+    }
+
     fn do_merge(&self, patch: Patch, w_opts: &RocksDBWriteOptions) -> crate::Result<()> {
-        let mut batch = WriteBatch::default();
-        for (resolved, changes) in patch.into_changes() {
-            if !self.cf_exists(&resolved.name) {
-                self.create_cf(&resolved.name)?;
-            }
+        self.metrics.observe_merge(patch, |patch| {
+            let _guard = self.lock_merges();
+            self.write_patch(patch, w_opts)
+        })
+    }
 
+    fn write_patch(&self, patch: Patch, w_opts: &RocksDBWriteOptions) -> crate::Result<()> {
+        let mut batch = WriteBatch::default();
+        let changes = patch.into_changes();
+        let new_prefix_lens = changes
+            .get(&ResolvedAddress::system(PREFIX_LENGTHS_NAME))
+            .cloned();
+        for (resolved, changes) in changes {
+            self.ensure_cf(&resolved, new_prefix_lens.as_ref())?;
             let db_reader = self.get_db_lock_guard();
             let cf = db_reader.cf_handle(&resolved.name).unwrap();
+            self.write_view_changes(&mut batch, cf, &resolved, changes);
+        }
 
-            if changes.is_cleared() {
-                self.clear_prefix(&mut batch, cf, &resolved);
-            }
+        self.get_db_lock_guard()
+            .write_opt(batch, w_opts)
+            .map_err(Into::into)
+    }
 
-            if let Some(id_bytes) = resolved.id_to_bytes() {
-                // Write changes to the column family with each key prefixed by the ID of the
-                // resolved address.
-
-                // We assume that typical key sizes are less than `1_024 - ID_SIZE = 1_016` bytes,
-                // so that they fit into stack.
-                let mut buffer: SmallVec<[u8; 1_024]> = SmallVec::new();
-                buffer.extend_from_slice(&id_bytes);
-
-                for (key, change) in changes.into_data() {
-                    buffer.truncate(ID_SIZE);
-                    buffer.extend_from_slice(&key);
-                    match change {
-                        Change::Put(ref value) => batch.put_cf(cf, &buffer, value),
-                        Change::Delete => batch.delete_cf(cf, &buffer),
-                    }
+    /// Creates the column family for the view if it does not exist yet.
+    fn ensure_cf(
+        &self,
+        resolved: &ResolvedAddress,
+        new_prefix_lens: Option<&ViewChanges>,
+    ) -> crate::Result<()> {
+        if !self.cf_exists(&resolved.name) {
+            let prefix_len = resolved
+                .id
+                .and_then(|_| self.new_cf_prefix_len(&resolved.name, new_prefix_lens));
+            self.create_cf(&resolved.name, prefix_len)?;
+        }
+        Ok(())
+    }
+
+    /// Adds changes of a single view to the write batch.
+    fn write_view_changes(
+        &self,
+        batch: &mut WriteBatch,
+        cf: &ColumnFamily,
+        resolved: &ResolvedAddress,
+        changes: ViewChanges,
+    ) {
+        if changes.is_cleared() {
+            self.clear_prefix(batch, cf, resolved);
+        }
+        for range in changes.removed_ranges() {
+            self.remove_range(batch, cf, resolved, range);
+        }
+
+        if let Some(id_bytes) = resolved.id_to_bytes() {
+            // Write changes to the column family with each key prefixed by the ID of the
+            // resolved address.
+
+            // We assume that typical key sizes are less than `1_024 - ID_SIZE = 1_016` bytes,
+            // so that they fit into stack.
+            let mut buffer: SmallVec<[u8; 1_024]> = SmallVec::new();
+            buffer.extend_from_slice(&id_bytes);
+
+            for (key, change) in changes.into_data() {
+                buffer.truncate(ID_SIZE);
+                buffer.extend_from_slice(&key);
+                match change {
+                    Change::Put(ref value) => batch.put_cf(cf, &buffer, value),
+                    Change::Delete => batch.delete_cf(cf, &buffer),
                 }
-            } else {
-                // Write changes to the column family as-is.
-                for (key, change) in changes.into_data() {
-                    match change {
-                        Change::Put(ref value) => batch.put_cf(cf, &key, value),
-                        Change::Delete => batch.delete_cf(cf, &key),
-                    }
+            }
+        } else {
+            // Write changes to the column family as-is.
+            for (key, change) in changes.into_data() {
+                match change {
+                    Change::Put(ref value) => batch.put_cf(cf, &key, value),
+                    Change::Delete => batch.delete_cf(cf, &key),
                 }
             }
         }
+    }
 
-        self.get_db_lock_guard()
-            .write_opt(batch, w_opts)
-            .map_err(Into::into)
+    fn write_patch_parallel(&self, patch: Patch) -> crate::Result<()> {
+        let changes = patch.into_changes();
+        let new_prefix_lens = changes
+            .get(&ResolvedAddress::system(PREFIX_LENGTHS_NAME))
+            .cloned();
+
+        let mut system_changes = vec![];
+        let mut cf_changes: HashMap<String, Vec<(ResolvedAddress, ViewChanges)>> = HashMap::new();
+        for (resolved, changes) in changes {
+            self.ensure_cf(&resolved, new_prefix_lens.as_ref())?;
+            if resolved.id.is_some() {
+                cf_changes
+                    .entry(resolved.name.clone())
+                    .or_default()
+                    .push((resolved, changes));
+            } else {
+                system_changes.push((resolved, changes));
+            }
+        }
+
+        let threads = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(cf_changes.len())
+            .max(1);
+        let mut buckets: Vec<Vec<_>> = (0..threads).map(|_| vec![]).collect();
+        for (i, item) in cf_changes.into_iter().enumerate() {
+            buckets[i % threads].push(item);
+        }
+
+        let results = crossbeam::thread::scope(|scope| {
+            let handles: Vec<_> = buckets
+                .into_iter()
+                .map(|bucket| scope.spawn(move |_| self.write_cf_batches(bucket)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Merge thread panicked"))
+                .collect::<Vec<_>>()
+        })
+        .expect("Merge thread panicked");
+        for result in results {
+            result?;
+        }
+
+        // System views are written last, so that index metadata is only updated
+        // after all index data has been written.
+        let mut batch = WriteBatch::default();
+        let db_reader = self.get_db_lock_guard();
+        for (resolved, changes) in system_changes {
+            let cf = db_reader.cf_handle(&resolved.name).unwrap();
+            self.write_view_changes(&mut batch, cf, &resolved, changes);
+        }
+        db_reader.write(batch).map_err(Into::into)
+    }
+
+    /// Writes changes of each column family with a separate write batch.
+    fn write_cf_batches(
+        &self,
+        cf_changes: Vec<(String, Vec<(ResolvedAddress, ViewChanges)>)>,
+    ) -> crate::Result<()> {
+        let db_reader = self.get_db_lock_guard();
+        for (cf_name, views) in cf_changes {
+            let cf = db_reader.cf_handle(&cf_name).unwrap();
+            let mut batch = WriteBatch::default();
+            for (resolved, changes) in views {
+                self.write_view_changes(&mut batch, cf, &resolved, changes);
+            }
+            db_reader.write(batch)?;
+        }
+        Ok(())
+    }
+
+    /// Writes already prefixed entries into the column family with the specified name,
+    /// creating the column family if necessary.
+    pub(super) fn write_raw<'a, I>(&self, cf_name: &str, entries: I) -> crate::Result<()>
+    where
+        I: iter::Iterator<Item = (&'a Vec<u8>, &'a Vec<u8>)>,
+    {
+        if !self.cf_exists(cf_name) {
+            self.create_cf(cf_name, None)?;
+        }
+
+        let db_reader = self.get_db_lock_guard();
+        let cf = db_reader.cf_handle(cf_name).unwrap();
+        let mut batch = WriteBatch::default();
+        for (key, value) in entries {
+            batch.put_cf(cf, key, value);
+        }
+        db_reader.write(batch).map_err(Into::into)
     }
 
     /// Removes all keys with the specified prefix from a column family.
@@ -210,6 +989,24 @@ impl RocksDB {
         }
     }
 
+    /// Removes a range of keys of the specified view from a column family.
+    fn remove_range(
+        &self,
+        batch: &mut WriteBatch,
+        cf: &ColumnFamily,
+        resolved: &ResolvedAddress,
+        range: &KeyRange,
+    ) {
+        let start = resolved.keyed(&range.start);
+        match (&range.end, resolved.id_to_bytes()) {
+            (Some(end), _) => batch.delete_range_cf(cf, &*start, &*resolved.keyed(end)),
+            (None, Some(id_bytes)) => {
+                batch.delete_range_cf(cf, &*start, &next_id_bytes(id_bytes)[..]);
+            }
+            (None, None) => self.remove_keys_from(batch, cf, &start),
+        }
+    }
+
     #[allow(unsafe_code)]
     #[allow(clippy::useless_transmute)]
     pub(super) fn rocksdb_snapshot(&self) -> RocksDBSnapshot {
@@ -223,6 +1020,7 @@ impl RocksDB {
             // FIXME: Investigate changing `rocksdb::Snapshot` / `DB` to remove `unsafe` (ECR-4273).
             snapshot: unsafe { mem::transmute(self.get_db_lock_guard().snapshot()) },
             db: Arc::clone(&self.db),
+            prefix_lens: Arc::clone(&self.prefix_lens),
         }
     }
 }
@@ -232,14 +1030,21 @@ impl RocksDBSnapshot {
         self.db.read().expect("Failed to get read lock to DB")
     }
 
-    fn rocksdb_iter(&self, name: &ResolvedAddress, from: &[u8]) -> RocksDBIterator<'_> {
+    fn rocksdb_iter(
+        &self,
+        name: &ResolvedAddress,
+        from: &[u8],
+        read_options: ReadOptions,
+    ) -> RocksDBIterator<'_> {
         use rocksdb::{Direction, IteratorMode};
 
         let from = name.keyed(from);
         let iter = match self.get_lock_guard().cf_handle(&name.name) {
-            Some(cf) => self
-                .snapshot
-                .iterator_cf(cf, IteratorMode::From(from.as_ref(), Direction::Forward)),
+            Some(cf) => self.snapshot.iterator_cf_opt(
+                cf,
+                read_options,
+                IteratorMode::From(from.as_ref(), Direction::Forward),
+            ),
             None => self.snapshot.iterator(IteratorMode::Start),
         };
         RocksDBIterator {
@@ -254,19 +1059,95 @@ impl RocksDBSnapshot {
 
 impl Database for RocksDB {
     fn snapshot(&self) -> Box<dyn Snapshot> {
+        self.metrics.snapshot_created();
         Box::new(self.rocksdb_snapshot())
     }
 
+    fn fork_latest(&self) -> Fork {
+        Fork::with_latest_state(self.clone())
+    }
+
     fn merge(&self, patch: Patch) -> crate::Result<()> {
-        let w_opts = RocksDBWriteOptions::default();
-        self.do_merge(patch, &w_opts)
+        self.merge_with_options(patch, WriteDurability::WalNoSync)
     }
 
     fn merge_sync(&self, patch: Patch) -> crate::Result<()> {
+        self.merge_with_options(patch, WriteDurability::WalSync)
+    }
+
+    fn merge_with_options(&self, patch: Patch, durability: WriteDurability) -> crate::Result<()> {
         let mut w_opts = RocksDBWriteOptions::default();
-        w_opts.set_sync(true);
+        match durability {
+            WriteDurability::NoWal => w_opts.disable_wal(true),
+            WriteDurability::WalNoSync => {}
+            WriteDurability::WalSync => w_opts.set_sync(true),
+        }
         self.do_merge(patch, &w_opts)
     }
+
+    fn merge_checked(&self, patch: Patch, read_set: ReadSet) -> Result<(), MergeError> {
+        self.metrics.observe_merge(patch, |patch| {
+            let _guard = self.lock_merges();
+            let snapshot = self.rocksdb_snapshot();
+            read_set.check(|addr, key| snapshot.get(addr, key))?;
+            let w_opts = RocksDBWriteOptions::default();
+            self.write_patch(patch, &w_opts).map_err(MergeError::from)
+        })
+    }
+
+    fn merge_validated(&self, patch: Patch, constraints: &ConstraintSet) -> crate::Result<()> {
+        self.metrics.observe_merge(patch, |patch| {
+            let _guard = self.lock_merges();
+            // Check the constraints against the changes applied on top of the latest state,
+            // which cannot be modified by concurrent merges until the patch is written.
+            let snapshot = Box::new(self.rocksdb_snapshot());
+            let patch = Patch::with_changes(snapshot, patch.into_changes());
+            constraints.check(&patch).map_err(|violation| {
+                crate::Error::new(format!("Constraint violated: {}", violation))
+            })?;
+            self.write_patch(patch, &RocksDBWriteOptions::default())
+        })
+    }
+
+    fn flush(&self) -> crate::Result<()> {
+        let db = self.get_db_lock_guard();
+        let cf_names = rocksdb::DB::list_cf(&RocksDBOptions::default(), db.path())?;
+        for cf_name in &cf_names {
+            if let Some(cf) = db.cf_handle(cf_name) {
+                db.flush_cf(cf)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_metrics_sink(&self, sink: Box<dyn MetricsSink>) {
+        self.metrics.set(sink);
+    }
+}
+
+impl LatestState for RocksDB {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        self.rocksdb_snapshot().get(name, key)
+    }
+
+    fn read_entries(
+        &self,
+        name: &ResolvedAddress,
+        from: &[u8],
+        limit: usize,
+        buffer: &mut Vec<(Vec<u8>, Vec<u8>)>,
+    ) {
+        let snapshot = self.rocksdb_snapshot();
+        copy_entries(snapshot.iter(name, from), limit, buffer);
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        self.rocksdb_snapshot().last_before(name, before)
+    }
 }
 
 impl Snapshot for RocksDBSnapshot {
@@ -278,6 +1159,26 @@ impl Snapshot for RocksDBSnapshot {
             .unwrap_or_else(|e| panic!("{}", e))
     }
 
+    fn contains(&self, resolved_addr: &ResolvedAddress, key: &[u8]) -> bool {
+        let lock = self.get_lock_guard();
+        let cf = if let Some(cf) = lock.cf_handle(&resolved_addr.name) {
+            cf
+        } else {
+            return false;
+        };
+
+        // Seeking to the key does not copy the value, unlike `get_cf`.
+        let key = resolved_addr.keyed(key);
+        let mut iter = self
+            .snapshot
+            .raw_iterator_cf_opt(cf, total_order_read_options());
+        iter.seek(&key);
+        if let Err(e) = iter.status() {
+            panic!("{}", e);
+        }
+        iter.key() == Some(key.as_ref())
+    }
+
     fn multi_get<'a>(
         &self,
         resolved_addr: &ResolvedAddress,
@@ -298,7 +1199,67 @@ impl Snapshot for RocksDBSnapshot {
     }
 
     fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
-        Box::new(self.rocksdb_iter(name, from))
+        Box::new(self.rocksdb_iter(name, from, total_order_read_options()))
+    }
+
+    fn iter_prefix(&self, name: &ResolvedAddress, prefix: &[u8], from: &[u8]) -> Iter<'_> {
+        let prefix_len = self
+            .prefix_lens
+            .read()
+            .expect("Failed to get read lock to prefix lengths")
+            .get(&name.name)
+            .copied();
+        match prefix_len {
+            // The prefix extractor is only applied to keys of indexes, which are prefixed by the ID.
+            Some(prefix_len) if name.id.is_some() && prefix.len() >= prefix_len => {
+                // Restrict iteration to keys sharing the extracted prefix with `from`,
+                // so that `RocksDB` can use prefix Bloom filters.
+                let mut read_options = ReadOptions::default();
+                read_options.set_prefix_same_as_start(true);
+                Box::new(self.rocksdb_iter(name, from, read_options))
+            }
+            _ => self.iter(name, from),
+        }
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        let lock = self.get_lock_guard();
+        let cf = lock.cf_handle(&name.name)?;
+        let prefix = name.id_to_bytes();
+        let upper_bound = match (before, prefix) {
+            (Some(key), _) => Some(name.keyed(key).into_owned()),
+            (None, Some(id)) => Some(next_id_bytes(id).to_vec()),
+            (None, None) => None,
+        };
+
+        let mut iter = self
+            .snapshot
+            .raw_iterator_cf_opt(cf, total_order_read_options());
+        if let Some(upper_bound) = upper_bound {
+            iter.seek_for_prev(&upper_bound);
+            // The upper bound is exclusive.
+            if iter.key() == Some(upper_bound.as_slice()) {
+                iter.prev();
+            }
+        } else {
+            iter.seek_to_last();
+        }
+
+        let key = iter.key()?;
+        let key = if let Some(prefix) = prefix {
+            // Do not read into a neighboring index in the same column family.
+            if key.len() < ID_SIZE || key[..ID_SIZE] != prefix {
+                return None;
+            }
+            &key[ID_SIZE..]
+        } else {
+            key
+        };
+        Some((key.to_vec(), iter.value()?.to_vec()))
     }
 }
 
@@ -363,6 +1324,58 @@ impl fmt::Debug for RocksDBSnapshot {
     }
 }
 
+/// Reads key prefix lengths of column families from the database at the specified path.
+/// Returns an empty map if the database or the column family storing prefix lengths
+/// does not exist.
+fn read_prefix_lens(path: &Path) -> HashMap<String, usize> {
+    use rocksdb::IteratorMode;
+
+    let db = match rocksdb::DB::open_cf_for_read_only(
+        &RocksDBOptions::default(),
+        path,
+        [PREFIX_LENGTHS_NAME],
+        false,
+    ) {
+        Ok(db) => db,
+        Err(_) => return HashMap::new(),
+    };
+    let cf = match db.cf_handle(PREFIX_LENGTHS_NAME) {
+        Some(cf) => cf,
+        None => return HashMap::new(),
+    };
+    db.iterator_cf(cf, IteratorMode::Start)
+        .filter_map(|(name, prefix_len)| {
+            let name = String::from_utf8(name.into_vec()).ok()?;
+            let prefix_len = u64::from_bytes(Cow::Borrowed(&prefix_len[..])).ok()?;
+            Some((name, prefix_len as usize))
+        })
+        .collect()
+}
+
+/// Creates options for reading keys in the total order regardless of the prefix extractor
+/// of the column family.
+fn total_order_read_options() -> ReadOptions {
+    let mut read_options = ReadOptions::default();
+    read_options.set_total_order_seek(true);
+    read_options
+}
+
+/// Creates descriptors for opening column families with the specified names.
+fn cf_descriptors<S: AsRef<str>>(
+    cf_names: &[S],
+    options: &DBOptions,
+    prefix_lens: &HashMap<String, usize>,
+) -> Vec<ColumnFamilyDescriptor> {
+    cf_names
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            let prefix_len = prefix_lens.get(name).copied();
+            ColumnFamilyDescriptor::new(name, rocksdb_options(options, prefix_len))
+        })
+        .collect()
+}
+
 /// Generates the sequence of bytes lexicographically following the provided one. Assumes that
 /// the provided sequence is less than `[u8::max_value(); ID_SIZE]`.
 pub fn next_id_bytes(id_bytes: [u8; ID_SIZE]) -> [u8; ID_SIZE] {
@@ -378,6 +1391,67 @@ pub fn next_id_bytes(id_bytes: [u8; ID_SIZE]) -> [u8; ID_SIZE] {
     next_id_bytes
 }
 
+#[test]
+fn opening_database_with_block_size() {
+    use crate::access::CopyAccessExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let mut options = DBOptions::default();
+    options.block_size = Some(64 * 1_024);
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    let fork = db.fork();
+    fork.get_list("list").extend(0_u64..1_000);
+    db.merge(fork.into_patch()).unwrap();
+    db.flush().unwrap();
+    drop(db);
+
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    let snapshot = db.snapshot();
+    let list = snapshot.get_list::<_, u64>("list");
+    assert_eq!(list.len(), 1_000);
+    assert_eq!(list.get(500), Some(500));
+}
+
+#[test]
+fn opening_database_with_corruption_handling() {
+    use crate::{access::CopyAccessExt, CorruptionMode};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let mut options = DBOptions::default();
+    options.paranoid_checks = Some(true);
+    options.on_corruption = CorruptionMode::Fail;
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    let fork = db.fork();
+    fork.get_list("list").extend(0_u64..100);
+    db.merge_sync(fork.into_patch()).unwrap();
+    drop(db);
+
+    // The WAL of a cleanly closed database is consistent.
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    let snapshot = db.snapshot();
+    assert_eq!(snapshot.get_list::<_, u64>("list").len(), 100);
+}
+
+#[test]
+fn opening_database_with_rate_limiter() {
+    use crate::access::CopyAccessExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let mut options = DBOptions::default();
+    options.background_io_bytes_per_sec = Some(16 << 20);
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    let fork = db.fork();
+    fork.get_list("list").extend(0_u64..1_000);
+    db.merge(fork.into_patch()).unwrap();
+    db.flush().unwrap();
+    drop(db);
+
+    // Non-positive limits are ignored.
+    options.background_io_bytes_per_sec = Some(0);
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    assert_eq!(db.snapshot().get_list::<_, u64>("list").len(), 1_000);
+}
+
 #[test]
 fn test_next_id_bytes() {
     assert_eq!(
@@ -401,3 +1475,305 @@ fn test_next_id_bytes() {
         [1, 2, 3, 4, 6, 0, 0, 0]
     );
 }
+
+#[test]
+fn opening_database_with_cfs() {
+    use crate::{
+        access::{AccessExt, CopyAccessExt},
+        migration::Scratchpad,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DBOptions::default();
+    let db = RocksDB::open_with_cfs(temp_dir.path(), &options, &["list"]).unwrap();
+    let fork = db.fork();
+    fork.get_list("list").extend(0_u32..10);
+    fork.get_entry(("group", &1_u8)).set(1_u32);
+    fork.get_entry(("group", &2_u8)).set(2_u32);
+    db.merge(fork.into_patch()).unwrap();
+    drop(db);
+
+    let db = RocksDB::open_with_cfs(temp_dir.path(), &options, &["group", "list"]).unwrap();
+    let snapshot = db.snapshot();
+    assert_eq!(snapshot.get_list::<_, u32>("list").len(), 10);
+    assert_eq!(
+        snapshot.get_entry::<_, u32>(("group", &2_u8)).get(),
+        Some(2)
+    );
+    drop(snapshot);
+    drop(db);
+
+    let err = RocksDB::open_with_cfs(temp_dir.path(), &options, &["list"]).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("contains column families not listed in `cf_names`"));
+    assert!(err.to_string().contains("group"));
+
+    // The database can still be opened with auto-discovery.
+    let db = RocksDB::open(temp_dir.path(), &options).unwrap();
+    assert_eq!(db.snapshot().get_list::<_, u32>("list").len(), 10);
+    // Column family used by scratchpads is opened automatically.
+    let fork = db.fork();
+    Scratchpad::new("migration", &fork)
+        .get_entry("entry")
+        .set(1_u8);
+    db.merge(fork.into_patch()).unwrap();
+    drop(db);
+
+    let db = RocksDB::open_with_cfs(temp_dir.path(), &options, &["group", "list"]).unwrap();
+    let fork = db.fork();
+    let entry = Scratchpad::new("migration", &fork).get_entry::<_, u8>("entry");
+    assert_eq!(entry.get(), Some(1));
+}
+
+#[test]
+fn querying_properties() {
+    use crate::access::CopyAccessExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    let fork = db.fork();
+    fork.get_entry(("group", &1_u8)).set(1_u32);
+    fork.get_entry(("group", &2_u8)).set(2_u32);
+    db.merge(fork.into_patch()).unwrap();
+    db.flush().unwrap();
+
+    // Indexes in a group share the column family.
+    let addr = IndexAddress::from_root("group").append_key(&1_u8);
+    let files = db
+        .property_int(&addr, "rocksdb.num-files-at-level0")
+        .unwrap();
+    assert!(files > 0);
+    assert_eq!(
+        db.property_int(&"group".into(), "rocksdb.num-files-at-level0"),
+        Some(files)
+    );
+    assert!(db.property(&addr, "rocksdb.unknown-property").is_none());
+    assert!(db
+        .property_int(&"other".into(), "rocksdb.estimate-num-keys")
+        .is_none());
+}
+
+#[test]
+fn prefix_extractor_for_grouped_maps() {
+    use crate::access::CopyAccessExt;
+
+    fn key(customer_id: u32, order_id: u32) -> u64 {
+        u64::from(customer_id) << 32 | u64::from(order_id)
+    }
+
+    fn check_orders(db: &RocksDB) {
+        assert_eq!(db.prefix_lens.read().unwrap().get("orders"), Some(&4));
+
+        let snapshot = db.snapshot();
+        for group_key in 1_u8..=2 {
+            let orders = snapshot.get_map::<_, u64, u64>(("orders", &group_key));
+            let customer_orders: Vec<_> = orders.iter_prefix(&5_u32).collect();
+            let expected: Vec<_> = (0..5)
+                .map(|order_id| (key(5, order_id), u64::from(group_key)))
+                .collect();
+            assert_eq!(customer_orders, expected);
+            assert_eq!(orders.iter_prefix(&100_u32).count(), 0);
+            assert_eq!(orders.iter_prefix(&key(5, 3)).count(), 1);
+            // Iteration not restricted to a prefix is not affected.
+            assert_eq!(orders.iter().count(), 50);
+            assert_eq!(orders.iter_from(&key(3, 2)).count(), 33);
+        }
+
+        // Unmerged changes are taken into account.
+        let fork = db.fork();
+        let mut orders = fork.get_map::<_, u64, u64>(("orders", &1_u8));
+        orders.put(&key(5, 10), 0);
+        orders.remove(&key(5, 0));
+        let order_ids: Vec<_> = orders
+            .iter_prefix(&5_u32)
+            .map(|(key, _)| key as u32)
+            .collect();
+        assert_eq!(order_ids, [1, 2, 3, 4, 10]);
+    }
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    let fork = db.fork();
+    let addr = IndexAddress::from_root("orders").append_key(&1_u8);
+    let mut orders = fork.get_map(addr.with_prefix_len(4));
+    // Indexes in a group share the column family and thus the prefix length.
+    let mut other_orders = fork.get_map(("orders", &2_u8));
+    for customer_id in 0..10 {
+        for order_id in 0..5 {
+            orders.put(&key(customer_id, order_id), 1_u64);
+            other_orders.put(&key(customer_id, order_id), 2_u64);
+        }
+    }
+    db.merge(fork.into_patch()).unwrap();
+    db.flush().unwrap();
+    check_orders(&db);
+    drop(db);
+
+    // The prefix length is retained after reopening the database.
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    check_orders(&db);
+    drop(db);
+    let db = RocksDB::open_with_cfs(temp_dir.path(), &DBOptions::default(), &["orders"]).unwrap();
+    check_orders(&db);
+}
+
+#[test]
+fn checking_key_presence() {
+    use crate::access::CopyAccessExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    let fork = db.fork();
+    fork.get_entry(("entry", &1_u8)).set(vec![1_u8; 1_024]);
+    // Neighbouring keys must not be mistaken for the checked ones.
+    let mut map = fork.get_map("map");
+    map.put(&vec![1_u8, 2], 1_u8);
+    map.put(&vec![3_u8], 3_u8);
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    assert!(snapshot.get_entry::<_, Vec<u8>>(("entry", &1_u8)).exists());
+    assert!(!snapshot.get_entry::<_, Vec<u8>>(("entry", &2_u8)).exists());
+    assert!(!snapshot.get_entry::<_, Vec<u8>>("missing").exists());
+    let map = snapshot.get_map::<_, Vec<u8>, u8>("map");
+    assert!(map.contains(&vec![1, 2]));
+    assert!(map.contains(&vec![3]));
+    assert!(!map.contains(&vec![1]));
+    assert!(!map.contains(&vec![2]));
+    assert!(!map.contains(&vec![4]));
+}
+
+#[test]
+fn parallel_merge() {
+    use crate::{access::CopyAccessExt, validation::snapshots_eq};
+
+    fn fill(fork: &Fork, round: u64) {
+        for i in 0..8_u64 {
+            let mut list = fork.get_list(("lists", &i));
+            list.extend(round * 10..round * 10 + i);
+            let mut map = fork.get_map(format!("map_{}", i).as_str());
+            map.put(&round, i);
+            map.remove(&(round + 1));
+        }
+        fork.get_entry("entry").set(round);
+        if round > 0 {
+            fork.get_list::<_, u64>(("lists", &3_u64)).clear();
+        }
+    }
+
+    let first_dir = tempfile::TempDir::new().unwrap();
+    let first = RocksDB::open(first_dir.path(), &DBOptions::default()).unwrap();
+    let second_dir = tempfile::TempDir::new().unwrap();
+    let second = RocksDB::open(second_dir.path(), &DBOptions::default()).unwrap();
+    for round in 0..3 {
+        let fork = first.fork();
+        fill(&fork, round);
+        first.merge(fork.into_patch()).unwrap();
+        let fork = second.fork();
+        fill(&fork, round);
+        second.merge_parallel(fork.into_patch()).unwrap();
+    }
+
+    let snapshot = second.snapshot();
+    assert!(snapshots_eq(&*first.snapshot(), &*snapshot, None));
+    assert_eq!(snapshot.get_list::<_, u64>(("lists", &7_u64)).len(), 21);
+    assert!(snapshot.get_list::<_, u64>(("lists", &3_u64)).is_empty());
+    assert_eq!(snapshot.get_entry::<_, u64>("entry").get(), Some(2));
+}
+
+#[test]
+fn tracking_live_snapshots() {
+    use crate::access::CopyAccessExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    assert_eq!(db.live_snapshot_count(), 0);
+    assert_eq!(db.oldest_snapshot_sequence(), None);
+
+    let fork = db.fork();
+    fork.get_list("list").push(1_u64);
+    let first_sequence = db.current_sequence();
+    let snapshot = db.snapshot();
+    assert_eq!(db.live_snapshot_count(), 2);
+
+    // The patch retains the snapshot of the fork until it is merged.
+    let patch = fork.into_patch();
+    assert_eq!(db.live_snapshot_count(), 2);
+    db.merge(patch).unwrap();
+    assert_eq!(db.live_snapshot_count(), 1);
+    assert_eq!(db.oldest_snapshot_sequence(), Some(first_sequence));
+
+    let second_snapshot = db.snapshot();
+    assert_eq!(db.live_snapshot_count(), 2);
+    assert_eq!(db.oldest_snapshot_sequence(), Some(first_sequence));
+    drop(snapshot);
+    assert_eq!(db.oldest_snapshot_sequence(), Some(db.current_sequence()));
+    drop(second_snapshot);
+    assert_eq!(db.live_snapshot_count(), 0);
+    assert_eq!(db.oldest_snapshot_sequence(), None);
+}
+
+#[test]
+fn merge_validated_checks_latest_state() {
+    use crate::access::CopyAccessExt;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(dir.path(), &DBOptions::default()).unwrap();
+    let fork = db.fork();
+    fork.get_entry("a").set(0_u64);
+    fork.get_entry("b").set(0_u64);
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut constraints = ConstraintSet::new();
+    constraints.add(|snapshot| {
+        let a = snapshot.get_entry::<_, u64>("a").get().unwrap_or(0);
+        let b = snapshot.get_entry::<_, u64>("b").get().unwrap_or(0);
+        if a + b <= 10 {
+            Ok(())
+        } else {
+            Err(format!("a + b = {}", a + b))
+        }
+    });
+
+    // Both forks are created from the same state, so each patch satisfies the constraint
+    // on its own, but not when merged after the other one.
+    let first_fork = db.fork();
+    first_fork.get_entry("a").set(6_u64);
+    let second_fork = db.fork();
+    second_fork.get_entry("b").set(6_u64);
+    db.merge_validated(first_fork.into_patch(), &constraints)
+        .unwrap();
+    let err = db
+        .merge_validated(second_fork.into_patch(), &constraints)
+        .unwrap_err();
+    assert!(err.to_string().contains("a + b = 12"), "{}", err);
+    assert_eq!(db.snapshot().get_entry::<_, u64>("b").get(), Some(0));
+}
+
+#[test]
+fn metrics_sink_is_shared_by_clones() {
+    use crate::MetricsEvent;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct SnapshotCounter(Arc<AtomicUsize>);
+
+    impl MetricsSink for SnapshotCounter {
+        fn record(&self, event: MetricsEvent) {
+            if event == MetricsEvent::SnapshotCreated {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    let clone = db.clone();
+    let counter = Arc::new(AtomicUsize::new(0));
+    db.set_metrics_sink(Box::new(SnapshotCounter(Arc::clone(&counter))));
+
+    // The clone was created before the sink was installed, but still reports to it.
+    drop(clone.snapshot());
+    drop(db.snapshot());
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+}
@@ -2,81 +2,281 @@
 
 use crossbeam::sync::ShardedLock;
 use smallvec::SmallVec;
+use uuid::Uuid;
+
 use std::{
     collections::{btree_map::Range, BTreeMap, HashMap},
-    iter,
+    env, fs, iter,
     iter::{Iterator, Peekable},
+    ops::Bound,
+    path::PathBuf,
+    result::Result as StdResult,
     sync::Arc,
 };
 
 use crate::{
-    backends::rocksdb::{next_id_bytes, ID_SIZE},
+    backends::rocksdb::{next_id_bytes, RocksDB, ID_SIZE},
     db::{check_database, Change, Iterator as DBIterator},
-    Database, Iter, Patch, ResolvedAddress, Result, Snapshot,
+    latest::{copy_entries, LatestState},
+    metrics::MetricsHook,
+    DBOptions, Database, Error, Fork, Iter, MergeError, MetricsSink, Patch, PinnedValue, ReadSet,
+    ResolvedAddress, Result, Snapshot, TempDbOptions,
 };
 
 type MemoryDB = HashMap<ResolvedAddress, BTreeMap<Vec<u8>, Vec<u8>>>;
 
 /// This in-memory database is only used for testing and experimenting; is not designed to
 /// operate under load in production.
+///
+/// If the database is created with [`with_options`] and a memory budget, its contents
+/// are transparently moved to a `RocksDB` instance in a temporary directory once the budget
+/// is exceeded. Note that spilling changes performance characteristics of the database:
+/// the spill itself copies all data, and subsequent operations are performed on disk.
+/// If spilling fails, the data stays in memory and the spill is retried on the next merge;
+/// the error can be retrieved with [`spill_error`].
+///
+/// [`with_options`]: #method.with_options
+/// [`spill_error`]: #method.spill_error
 #[derive(Debug)]
 pub struct TemporaryDB {
-    inner: Arc<ShardedLock<MemoryDB>>,
+    inner: Arc<ShardedLock<Storage>>,
+    options: TempDbOptions,
+    metrics: MetricsHook,
+}
+
+/// Storage backing a `TemporaryDB`.
+#[derive(Debug)]
+enum Storage {
+    /// Data is stored in memory.
+    Memory {
+        db: MemoryDB,
+        /// Total size of keys and values in `db`.
+        size: usize,
+        /// Error of the last failed attempt to spill `db` to disk.
+        spill_error: Option<Error>,
+    },
+    /// Data has been spilled to disk.
+    Spilled(SpilledDB),
+}
+
+/// `RocksDB` instance in a temporary directory.
+#[derive(Debug)]
+struct SpilledDB {
+    // Declared before `dir` so that the database is closed before the directory is removed.
+    db: RocksDB,
+    dir: Arc<SpillDir>,
 }
 
+/// Temporary directory of a `SpilledDB`, which is removed on drop. The directory is shared
+/// with snapshots of the spilled database, so it outlives the database if necessary.
+#[derive(Debug)]
+struct SpillDir(PathBuf);
+
 struct TemporarySnapshot {
     snapshot: MemoryDB,
 }
 
+/// Snapshot of a `SpilledDB` keeping its directory alive.
+struct SpilledSnapshot {
+    // Declared before `dir` so that the snapshot is dropped before the directory is removed.
+    snapshot: Box<dyn Snapshot>,
+    _dir: Arc<SpillDir>,
+}
+
+/// Latest state of a `TemporaryDB`.
+struct TemporaryLatest {
+    inner: Arc<ShardedLock<Storage>>,
+}
+
 struct TemporaryDBIterator<'a> {
     iter: Peekable<Range<'a, Vec<u8>, Vec<u8>>>,
     prefix: Option<[u8; ID_SIZE]>,
     ended: bool,
 }
 
+/// Returns the amount of memory occupied by an entry, as accounted by the memory budget.
+fn entry_size(key: &[u8], value: &[u8]) -> usize {
+    key.len() + value.len()
+}
+
+fn collection_size(collection: &BTreeMap<Vec<u8>, Vec<u8>>) -> usize {
+    collection
+        .iter()
+        .map(|(key, value)| entry_size(key, value))
+        .sum()
+}
+
+fn memory_iter<'a>(
+    db: &'a MemoryDB,
+    name: &ResolvedAddress,
+    from: &[u8],
+) -> TemporaryDBIterator<'a> {
+    let collection = db
+        .get(name)
+        .or_else(|| db.get(&ResolvedAddress::system("default")))
+        .unwrap();
+    let from = name.keyed(from).into_owned();
+    let iter = collection.range::<Vec<u8>, _>(&from..);
+
+    TemporaryDBIterator {
+        iter: iter.peekable(),
+        prefix: name.id_to_bytes(),
+        ended: false,
+    }
+}
+
+fn memory_last_before(
+    db: &MemoryDB,
+    name: &ResolvedAddress,
+    before: Option<&[u8]>,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let collection = db.get(name)?;
+    let prefix = name.id_to_bytes();
+    let lower_bound = prefix.map_or(Bound::Unbounded, |id| Bound::Included(id.to_vec()));
+    let upper_bound = match (before, prefix) {
+        (Some(key), _) => Bound::Excluded(name.keyed(key).into_owned()),
+        (None, Some(id)) => Bound::Excluded(next_id_bytes(id).to_vec()),
+        (None, None) => Bound::Unbounded,
+    };
+
+    let (key, value) = collection
+        .range::<Vec<u8>, _>((lower_bound, upper_bound))
+        .next_back()?;
+    let key = if prefix.is_some() {
+        &key[ID_SIZE..]
+    } else {
+        &key[..]
+    };
+    Some((key.to_vec(), value.clone()))
+}
+
+impl Storage {
+    fn empty() -> Self {
+        let mut db = HashMap::new();
+        db.insert(ResolvedAddress::system("default"), BTreeMap::new());
+        Self::Memory {
+            db,
+            size: 0,
+            spill_error: None,
+        }
+    }
+}
+
+impl SpilledDB {
+    /// Moves the in-memory data to a newly created `RocksDB` instance.
+    fn from_memory(memory_db: &MemoryDB) -> Result<Self> {
+        let path = env::temp_dir().join(format!("metaldb-spill-{}", Uuid::new_v4()));
+        let dir = Arc::new(SpillDir(path));
+        let db = RocksDB::open(&dir.0, &DBOptions::default())?;
+        let this = Self { db, dir };
+
+        for (resolved, collection) in memory_db {
+            this.db().write_raw(&resolved.name, collection.iter())?;
+        }
+        Ok(this)
+    }
+
+    fn db(&self) -> &RocksDB {
+        &self.db
+    }
+
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(SpilledSnapshot {
+            snapshot: self.db.snapshot(),
+            _dir: Arc::clone(&self.dir),
+        })
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
+
 impl TemporaryDB {
     /// Creates a new, empty database.
     pub fn new() -> Self {
-        let mut db = HashMap::new();
+        Self::with_options(TempDbOptions::default())
+    }
 
-        db.insert(ResolvedAddress::system("default"), BTreeMap::new());
-        let inner = Arc::new(ShardedLock::new(db));
-        let mut db = Self { inner };
+    /// Creates a new, empty database with the specified options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, Database, TempDbOptions, TemporaryDB};
+    ///
+    /// let db = TemporaryDB::with_options(TempDbOptions::new(Some(1_024)));
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(vec![0_u8; 2_048]);
+    /// db.merge(fork.into_patch()).unwrap();
+    /// // The memory budget is exceeded, so the data is now stored on disk.
+    /// assert!(db.is_spilled());
+    /// assert_eq!(db.snapshot().get_list::<_, u8>("list").len(), 2_048);
+    /// ```
+    pub fn with_options(options: TempDbOptions) -> Self {
+        let inner = Arc::new(ShardedLock::new(Storage::empty()));
+        let mut db = Self {
+            inner,
+            options,
+            metrics: MetricsHook::default(),
+        };
         check_database(&mut db).unwrap();
         db
     }
 
+    /// Checks if the database contents have been spilled to disk.
+    pub fn is_spilled(&self) -> bool {
+        matches!(
+            *self.inner.read().expect("Couldn't get read lock"),
+            Storage::Spilled(_)
+        )
+    }
+
+    /// Returns the error of the last failed attempt to spill the database contents to disk,
+    /// or `None` if the database has not tried to spill, or the last attempt has succeeded.
+    ///
+    /// A failed spill does not fail the merge that has triggered it: the merged data stays
+    /// in memory, and the spill is retried on the next merge.
+    pub fn spill_error(&self) -> Option<Error> {
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { spill_error, .. } => spill_error.clone(),
+            Storage::Spilled(_) => None,
+        }
+    }
+
     /// Clears the contents of the database.
+    ///
+    /// If the database has been spilled to disk, it is moved back to memory.
     pub fn clear(&self) -> crate::Result<()> {
         let mut rw_lock = self.inner.write().expect("Couldn't get read-write lock");
 
-        for collection in rw_lock.values_mut() {
-            collection.clear();
+        match &mut *rw_lock {
+            Storage::Memory {
+                db,
+                size,
+                spill_error,
+            } => {
+                for collection in db.values_mut() {
+                    collection.clear();
+                }
+                *size = 0;
+                *spill_error = None;
+            }
+            Storage::Spilled(_) => *rw_lock = Storage::empty(),
         }
 
         Ok(())
     }
 
-    fn temporary_snapshot(&self) -> TemporarySnapshot {
-        TemporarySnapshot {
-            snapshot: self.inner.read().expect("Couldn't get read lock").clone(),
-        }
-    }
-}
-
-impl Database for TemporaryDB {
-    fn snapshot(&self) -> Box<dyn Snapshot> {
-        Box::new(self.temporary_snapshot())
-    }
-
-    fn merge(&self, patch: Patch) -> Result<()> {
-        let mut inner = self.inner.write().expect("Couldn't get write lock");
+    fn merge_into_memory(memory_db: &mut MemoryDB, size: &mut usize, patch: Patch) {
         for (resolved, changes) in patch.into_changes() {
-            if !inner.contains_key(&resolved) {
-                inner.insert(resolved.clone(), BTreeMap::new());
+            if !memory_db.contains_key(&resolved) {
+                memory_db.insert(resolved.clone(), BTreeMap::new());
             }
 
-            let collection: &mut BTreeMap<Vec<u8>, Vec<u8>> = inner.get_mut(&resolved).unwrap();
+            let collection: &mut BTreeMap<Vec<u8>, Vec<u8>> = memory_db.get_mut(&resolved).unwrap();
 
             if changes.is_cleared() {
                 if let Some(id_bytes) = resolved.id_to_bytes() {
@@ -84,10 +284,25 @@ impl Database for TemporaryDB {
                     let mut middle_and_tail = collection.split_off(id_bytes.as_ref());
                     let mut tail = middle_and_tail.split_off(next_bytes.as_ref());
                     collection.append(&mut tail);
+                    *size -= collection_size(&middle_and_tail);
                 } else {
+                    *size -= collection_size(collection);
                     collection.clear();
                 }
             }
+            for range in changes.removed_ranges() {
+                let start = resolved.keyed(&range.start);
+                let mut middle_and_tail = collection.split_off(start.as_ref());
+                let mut tail = match (&range.end, resolved.id_to_bytes()) {
+                    (Some(end), _) => middle_and_tail.split_off(resolved.keyed(end).as_ref()),
+                    (None, Some(id_bytes)) => {
+                        middle_and_tail.split_off(next_id_bytes(id_bytes).as_ref())
+                    }
+                    (None, None) => BTreeMap::new(),
+                };
+                collection.append(&mut tail);
+                *size -= collection_size(&middle_and_tail);
+            }
 
             if let Some(id_bytes) = resolved.id_to_bytes() {
                 // Write changes to the column family with each key prefixed by the ID of the
@@ -102,26 +317,122 @@ impl Database for TemporaryDB {
                     buffer.truncate(ID_SIZE);
                     buffer.extend_from_slice(&key);
 
-                    match change {
-                        Change::Put(value) => collection.insert(buffer.to_vec(), value),
+                    let old_value = match change {
+                        Change::Put(value) => {
+                            *size += entry_size(&buffer, &value);
+                            collection.insert(buffer.to_vec(), value)
+                        }
                         Change::Delete => collection.remove(buffer.as_ref()),
                     };
+                    if let Some(old_value) = old_value {
+                        *size -= entry_size(&buffer, &old_value);
+                    }
                 }
             } else {
                 // Write changes to the column family as-is.
                 for (key, change) in changes.into_data() {
-                    match change {
-                        Change::Put(value) => collection.insert(key, value),
+                    let key_len = key.len();
+                    let old_value = match change {
+                        Change::Put(value) => {
+                            *size += entry_size(&key, &value);
+                            collection.insert(key, value)
+                        }
                         Change::Delete => collection.remove(&key),
                     };
+                    if let Some(old_value) = old_value {
+                        *size -= key_len + old_value.len();
+                    }
                 }
             }
         }
-        Ok(())
+    }
+
+    fn do_merge(&self, patch: Patch, sync: bool) -> Result<()> {
+        self.metrics.observe_merge(patch, |patch| {
+            let mut inner = self.inner.write().expect("Couldn't get write lock");
+            Self::merge_into_storage(&mut inner, patch, sync, self.options.max_memory_bytes)
+        })
+    }
+
+    fn merge_into_storage(
+        inner: &mut Storage,
+        patch: Patch,
+        sync: bool,
+        max_memory_bytes: Option<usize>,
+    ) -> Result<()> {
+        match &mut *inner {
+            Storage::Memory {
+                db,
+                size,
+                spill_error,
+            } => {
+                Self::merge_into_memory(db, size, patch);
+                let exceeds_budget = max_memory_bytes.map_or(false, |max_size| *size > max_size);
+                if exceeds_budget {
+                    // The patch is already merged, so a spill failure is not a merge failure.
+                    match SpilledDB::from_memory(db) {
+                        Ok(spilled) => *inner = Storage::Spilled(spilled),
+                        Err(e) => *spill_error = Some(e),
+                    }
+                }
+                Ok(())
+            }
+            Storage::Spilled(spilled) if sync => spilled.db().merge_sync(patch),
+            Storage::Spilled(spilled) => spilled.db().merge(patch),
+        }
+    }
+}
+
+impl Database for TemporaryDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        self.metrics.snapshot_created();
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { db, .. } => Box::new(TemporarySnapshot {
+                snapshot: db.clone(),
+            }),
+            Storage::Spilled(spilled) => spilled.snapshot(),
+        }
+    }
+
+    fn fork_latest(&self) -> Fork {
+        Fork::with_latest_state(TemporaryLatest {
+            inner: Arc::clone(&self.inner),
+        })
+    }
+
+    fn merge(&self, patch: Patch) -> Result<()> {
+        self.do_merge(patch, false)
     }
 
     fn merge_sync(&self, patch: Patch) -> Result<()> {
-        self.merge(patch)
+        self.do_merge(patch, true)
+    }
+
+    fn merge_checked(&self, patch: Patch, read_set: ReadSet) -> StdResult<(), MergeError> {
+        self.metrics.observe_merge(patch, |patch| {
+            let mut inner = self.inner.write().expect("Couldn't get write lock");
+            match &*inner {
+                Storage::Memory { db, .. } => read_set.check(|addr, key| {
+                    let collection = db.get(addr)?;
+                    collection.get(addr.keyed(key).as_ref()).cloned()
+                })?,
+                // The spilled database performs the check itself.
+                Storage::Spilled(spilled) => return spilled.db().merge_checked(patch, read_set),
+            }
+            Self::merge_into_storage(&mut inner, patch, false, self.options.max_memory_bytes)
+                .map_err(MergeError::from)
+        })
+    }
+
+    fn flush(&self) -> Result<()> {
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { .. } => Ok(()),
+            Storage::Spilled(spilled) => spilled.db().flush(),
+        }
+    }
+
+    fn set_metrics_sink(&self, sink: Box<dyn MetricsSink>) {
+        self.metrics.set(sink);
     }
 }
 
@@ -175,6 +486,18 @@ impl Snapshot for TemporarySnapshot {
         collection.get(name.keyed(key).as_ref()).cloned()
     }
 
+    fn contains(&self, name: &ResolvedAddress, key: &[u8]) -> bool {
+        self.snapshot.get(name).map_or(false, |collection| {
+            collection.contains_key(name.keyed(key).as_ref())
+        })
+    }
+
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        let collection = self.snapshot.get(name)?;
+        let value = collection.get(name.keyed(key).as_ref())?;
+        Some(PinnedValue::from(value.as_slice()))
+    }
+
     fn multi_get<'a>(
         &self,
         name: &ResolvedAddress,
@@ -191,19 +514,91 @@ impl Snapshot for TemporarySnapshot {
     }
 
     fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
-        let collection = self
-            .snapshot
-            .get(name)
-            .or_else(|| self.snapshot.get(&ResolvedAddress::system("default")))
-            .unwrap();
-        let from = name.keyed(from).into_owned();
-        let iter = collection.range::<Vec<u8>, _>(&from..);
-
-        Box::new(TemporaryDBIterator {
-            iter: iter.peekable(),
-            prefix: name.id_to_bytes(),
-            ended: false,
-        })
+        Box::new(memory_iter(&self.snapshot, name, from))
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        memory_last_before(&self.snapshot, name, before)
+    }
+}
+
+impl Snapshot for SpilledSnapshot {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        self.snapshot.get(name, key)
+    }
+
+    fn multi_get<'a>(
+        &self,
+        name: &ResolvedAddress,
+        keys: &'a mut dyn iter::Iterator<Item = &'a [u8]>,
+    ) -> Vec<Option<Vec<u8>>> {
+        self.snapshot.multi_get(name, keys)
+    }
+
+    fn contains(&self, name: &ResolvedAddress, key: &[u8]) -> bool {
+        self.snapshot.contains(name, key)
+    }
+
+    fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
+        self.snapshot.iter(name, from)
+    }
+
+    fn iter_prefix(&self, name: &ResolvedAddress, prefix: &[u8], from: &[u8]) -> Iter<'_> {
+        self.snapshot.iter_prefix(name, prefix, from)
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        self.snapshot.last_before(name, before)
+    }
+
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.snapshot.get_pinned(name, key)
+    }
+}
+
+impl LatestState for TemporaryLatest {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { db, .. } => {
+                let collection = db.get(name)?;
+                collection.get(name.keyed(key).as_ref()).cloned()
+            }
+            Storage::Spilled(spilled) => LatestState::get(spilled.db(), name, key),
+        }
+    }
+
+    fn read_entries(
+        &self,
+        name: &ResolvedAddress,
+        from: &[u8],
+        limit: usize,
+        buffer: &mut Vec<(Vec<u8>, Vec<u8>)>,
+    ) {
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { db, .. } => {
+                copy_entries(Box::new(memory_iter(db, name, from)), limit, buffer);
+            }
+            Storage::Spilled(spilled) => spilled.db().read_entries(name, from, limit, buffer),
+        }
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        match &*self.inner.read().expect("Couldn't get read lock") {
+            Storage::Memory { db, .. } => memory_last_before(db, name, before),
+            Storage::Spilled(spilled) => LatestState::last_before(spilled.db(), name, before),
+        }
     }
 }
 
@@ -247,3 +642,102 @@ fn clearing_database() {
     assert_eq!(list.len(), 3);
     assert_eq!(list.iter().collect::<Vec<_>>(), vec![4, 5, 6]);
 }
+
+#[test]
+fn spilling_database() {
+    use crate::access::CopyAccessExt;
+
+    let db = TemporaryDB::with_options(TempDbOptions::new(Some(4_096)));
+    let fork = db.fork();
+    fork.get_list("foo").extend(vec![1_u32, 2, 3]);
+    fork.get_entry(("bar", &0_u8)).set("!".to_owned());
+    db.merge(fork.into_patch()).unwrap();
+    assert!(!db.is_spilled());
+
+    let fork = db.fork();
+    fork.get_entry(("bar", &1_u8)).set("?".repeat(5_000));
+    db.merge(fork.into_patch()).unwrap();
+    assert!(db.is_spilled());
+
+    let fork = db.fork();
+    fork.get_list("foo").push(4_u32);
+    fork.get_map(("baz", &2_u8)).put(&1_u8, 42_u64);
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let list = snapshot.get_list::<_, u32>("foo");
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    let entry = snapshot.get_entry::<_, String>(("bar", &0_u8));
+    assert_eq!(entry.get().unwrap(), "!");
+    let entry = snapshot.get_entry::<_, String>(("bar", &1_u8));
+    assert_eq!(entry.get().unwrap().len(), 5_000);
+    let map = snapshot.get_map::<_, u8, u64>(("baz", &2_u8));
+    assert_eq!(map.get(&1), Some(42));
+
+    db.flush().unwrap();
+    db.clear().unwrap();
+    assert!(!db.is_spilled());
+    assert!(db.fork().index_type("foo").is_none());
+    db.flush().unwrap();
+}
+
+#[test]
+fn recording_metrics() {
+    use crate::{access::CopyAccessExt, MetricsEvent};
+    use std::sync::Mutex;
+
+    struct EventLog(Arc<Mutex<Vec<MetricsEvent>>>);
+
+    impl MetricsSink for EventLog {
+        fn record(&self, event: MetricsEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    // The sink can be installed on a database shared as a trait object.
+    let db: Arc<dyn Database> = Arc::new(TemporaryDB::new());
+    let events = Arc::new(Mutex::new(vec![]));
+    db.set_metrics_sink(Box::new(EventLog(Arc::clone(&events))));
+
+    let fork = db.fork();
+    fork.get_entry("foo").set(vec![0_u8; 100]);
+    db.merge(fork.into_patch()).unwrap();
+    db.snapshot();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], MetricsEvent::SnapshotCreated);
+    match events[1] {
+        MetricsEvent::MergeCompleted { bytes, .. } => assert!(bytes >= 100),
+        ref other => panic!("Unexpected event: {:?}", other),
+    }
+    assert_eq!(events[2], MetricsEvent::SnapshotCreated);
+}
+
+#[test]
+fn snapshots_outlive_spilled_database() {
+    use crate::access::CopyAccessExt;
+
+    let db = TemporaryDB::with_options(TempDbOptions::new(Some(1_024)));
+    let fork = db.fork();
+    fork.get_list("foo").extend(vec![0_u8; 2_048]);
+    db.merge(fork.into_patch()).unwrap();
+    assert!(db.spill_error().is_none());
+    let path = match &*db.inner.read().unwrap() {
+        Storage::Spilled(spilled) => spilled.dir.0.clone(),
+        Storage::Memory { .. } => panic!("Database is not spilled"),
+    };
+
+    let snapshot = db.snapshot();
+    db.clear().unwrap();
+    assert!(!db.is_spilled());
+    // The snapshot keeps the spilled database directory alive.
+    assert!(path.exists());
+    let list = snapshot.get_list::<_, u8>("foo");
+    assert_eq!(list.len(), 2_048);
+    assert_eq!(list.iter().count(), 2_048);
+
+    drop(list);
+    drop(snapshot);
+    assert!(!path.exists());
+}
@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     cell::RefCell,
     collections::{BTreeMap, HashMap},
     fmt, iter,
@@ -8,19 +9,54 @@ use std::{
     ops::{Bound, Deref, DerefMut},
     rc::Rc,
     result::Result as StdResult,
+    sync::{Arc, Mutex},
 };
 
+#[cfg(feature = "debug-tools")]
+use crate::{access::Access, validation::to_hex, BinaryKey, BinaryValue};
 use crate::{
-    validation::assert_valid_name_component,
-    views::{AsReadonly, ChangesIter, IndexesPool, RawAccess, ResolvedAddress, View},
-    Error, Result,
+    access::{AccessError, CopyAccessExt},
+    generic::SCHEMA_VERSION_NAME,
+    latest::{LatestSnapshot, LatestState},
+    read_set::{MergeError, ReadSet, TrackingSnapshot},
+    validation::{assert_valid_name_component, ConstraintSet},
+    views::{
+        AsReadonly, ChangesIter, IndexAddress, IndexType, IndexesPool, RawAccess, ResolvedAddress,
+        View, ViewWithMetadata, INDEXES_POOL_NAME, PREFIX_LENGTHS_NAME,
+    },
+    Error, MetricsSink, Result,
 };
 
-/// Changes related to a specific `View`.
+/// Half-open range of keys `[start, end)` removed from a view. If `end` is `None`,
+/// the range is unbounded from above.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRange {
+    /// Start of the range (inclusive).
+    pub start: Vec<u8>,
+    /// End of the range (exclusive), or `None` if the range is unbounded from above.
+    pub end: Option<Vec<u8>>,
+}
+
+impl KeyRange {
+    /// Checks if the range contains the specified key.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        key >= self.start.as_slice() && self.end.as_ref().map_or(true, |end| key < end.as_slice())
+    }
+}
+
+/// Changes related to a specific view.
+///
+/// Changes are applied to the stored view data in the following order: if the view
+/// [is cleared](#method.is_cleared), all stored data is removed; then, the
+/// [removed ranges](#method.removed_ranges) are removed; finally, the changes
+/// from [`into_data`](#method.into_data) are applied.
 #[derive(Debug, Default, Clone)]
 pub struct ViewChanges {
     /// Changes within the view.
     pub(super) data: BTreeMap<Vec<u8>, Change>,
+    /// Key ranges removed from the view. The ranges apply to the snapshot underlying
+    /// the changes; entries in `data` take precedence over them.
+    removed_ranges: Vec<KeyRange>,
     /// Was the view cleared as a part of changes?
     is_cleared: bool,
 }
@@ -30,29 +66,57 @@ impl ViewChanges {
         Self::default()
     }
 
+    /// Checks if the view was cleared as a part of changes.
     pub fn is_cleared(&self) -> bool {
         self.is_cleared
     }
 
+    /// Clears the view.
     pub fn clear(&mut self) {
         self.data.clear();
+        self.removed_ranges.clear();
         self.is_cleared = true;
     }
 
-    pub(crate) fn into_data(self) -> BTreeMap<Vec<u8>, Change> {
+    /// Removes all keys within the specified range.
+    pub(crate) fn remove_range(&mut self, range: KeyRange) {
+        self.data.retain(|key, _| !range.contains(key));
+        if !self.is_cleared {
+            self.removed_ranges.push(range);
+        }
+    }
+
+    /// Returns key ranges removed from the view.
+    pub fn removed_ranges(&self) -> &[KeyRange] {
+        &self.removed_ranges
+    }
+
+    /// Checks if a key in the underlying snapshot is hidden by clearing the view
+    /// or removing a key range.
+    fn is_removed(&self, key: &[u8]) -> bool {
+        self.is_cleared || self.removed_ranges.iter().any(|range| range.contains(key))
+    }
+
+    /// Returns changes to individual keys of the view.
+    pub fn into_data(self) -> BTreeMap<Vec<u8>, Change> {
         self.data
     }
 
     /// Returns a value for the specified key, or an `Err(_)` if the value should be determined
     /// by the underlying snapshot.
     pub fn get(&self, key: &[u8]) -> StdResult<Option<Vec<u8>>, ()> {
+        self.get_ref(key).map(|value| value.map(<[u8]>::to_vec))
+    }
+
+    /// Same as `get`, but returns a reference to the value.
+    pub fn get_ref(&self, key: &[u8]) -> StdResult<Option<&[u8]>, ()> {
         if let Some(change) = self.data.get(key) {
             return Ok(match *change {
-                Change::Put(ref v) => Some(v.clone()),
+                Change::Put(ref v) => Some(v.as_slice()),
                 Change::Delete => None,
             });
         }
-        if self.is_cleared() {
+        if self.is_removed(key) {
             return Ok(None);
         }
         Err(())
@@ -68,7 +132,7 @@ impl ViewChanges {
             });
         }
 
-        if self.is_cleared() {
+        if self.is_removed(key) {
             return Ok(false);
         }
         Err(())
@@ -86,17 +150,54 @@ struct WorkingPatch {
 
 #[derive(Debug)]
 enum WorkingPatchRef<'a> {
-    Borrowed(&'a WorkingPatch),
+    Borrowed(&'a Fork),
     Owned(Rc<Fork>),
 }
 
 impl WorkingPatchRef<'_> {
-    fn patch(&self) -> &WorkingPatch {
+    fn fork(&self) -> &Fork {
         match self {
-            WorkingPatchRef::Borrowed(patch) => patch,
-            WorkingPatchRef::Owned(ref fork) => &fork.working_patch,
+            WorkingPatchRef::Borrowed(fork) => fork,
+            WorkingPatchRef::Owned(ref fork) => fork,
         }
     }
+
+    fn patch(&self) -> &WorkingPatch {
+        &self.fork().working_patch
+    }
+}
+
+/// Callback invoked for each change staged in a `Fork`.
+type ChangeCallback = Box<dyn FnMut(&ResolvedAddress, &[u8], &Change) + Send>;
+
+/// Callbacks registered with `Fork::on_change()`.
+#[derive(Default)]
+struct ChangeObservers {
+    callbacks: RefCell<Vec<ChangeCallback>>,
+}
+
+impl fmt::Debug for ChangeObservers {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ChangeObservers")
+            .field("len", &self.callbacks.borrow().len())
+            .finish()
+    }
+}
+
+impl ChangeObservers {
+    fn notify(&self, address: &ResolvedAddress, key: &[u8], change: &Change) {
+        // Callbacks are taken out of the cell while they run, so that the cell is not borrowed
+        // if a callback causes another notification. Such nested notifications are not
+        // delivered to the callbacks.
+        let mut callbacks = self.callbacks.take();
+        for callback in &mut callbacks {
+            callback(address, key, change);
+        }
+        let mut cell = self.callbacks.borrow_mut();
+        callbacks.append(&mut cell);
+        *cell = callbacks;
+    }
 }
 
 #[derive(Debug)]
@@ -149,6 +250,13 @@ impl DerefMut for ChangesMut<'_> {
     }
 }
 
+impl ChangesMut<'_> {
+    /// Notifies callbacks registered in the parent fork about a change to the view.
+    pub(crate) fn notify(&self, key: &[u8], change: &Change) {
+        self.parent.fork().observers.notify(&self.key, key, change);
+    }
+}
+
 impl Drop for ChangesMut<'_> {
     fn drop(&mut self) {
         let mut change_map = self.parent.patch().changes.borrow_mut();
@@ -253,6 +361,9 @@ impl WorkingPatch {
             if changes.is_cleared() {
                 *patch_changes = changes;
             } else {
+                for range in changes.removed_ranges {
+                    patch_changes.remove_range(range);
+                }
                 patch_changes.data.extend(changes.data);
             }
         }
@@ -262,6 +373,32 @@ impl WorkingPatch {
 /// A generalized iterator over the storage views.
 pub type Iter<'a> = Box<dyn Iterator + 'a>;
 
+/// Durability guarantees for merging a patch into the database, used with
+/// [`Database::merge_with_options`]. The options trade merge throughput for durability
+/// of the merged data in case of a crash.
+///
+/// [`Database::merge_with_options`]: trait.Database.html#method.merge_with_options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WriteDurability {
+    /// Changes are not written to the write-ahead log (WAL). This is the fastest option,
+    /// but changes are lost if the process crashes before they are flushed to disk,
+    /// e.g., with [`Database::flush`].
+    ///
+    /// [`Database::flush`]: trait.Database.html#method.flush
+    NoWal,
+    /// Changes are written to the WAL, but the WAL is not synced to disk. Changes survive
+    /// a process crash, but may be lost if the machine crashes. Used by [`Database::merge`].
+    ///
+    /// [`Database::merge`]: trait.Database.html#tymethod.merge
+    WalNoSync,
+    /// Changes are written to the WAL, and the WAL is synced to disk before the merge
+    /// completes. Used by [`Database::merge_sync`].
+    ///
+    /// [`Database::merge_sync`]: trait.Database.html#tymethod.merge_sync
+    WalSync,
+}
+
 /// An enum that represents a type of change made to some key in the storage.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(test, derive(Eq, Hash))] // needed for patch equality comparison
@@ -272,6 +409,35 @@ pub enum Change {
     Delete,
 }
 
+/// Statistics of changes pending in a [`Fork`].
+///
+/// [`Fork`]: struct.Fork.html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PatchStats {
+    /// Number of keys to be written.
+    pub put_count: usize,
+    /// Number of keys to be deleted.
+    pub delete_count: usize,
+    /// Approximate total size of written keys and values and deleted keys, in bytes.
+    pub approx_bytes: usize,
+}
+
+impl PatchStats {
+    fn add_change(&mut self, key: &[u8], change: &Change) {
+        match change {
+            Change::Put(value) => {
+                self.put_count += 1;
+                self.approx_bytes += key.len() + value.len();
+            }
+            Change::Delete => {
+                self.delete_count += 1;
+                self.approx_bytes += key.len();
+            }
+        }
+    }
+}
+
 /// A combination of a database snapshot and changes on top of it.
 ///
 /// A `Fork` provides both immutable and mutable operations over the database by implementing
@@ -359,6 +525,8 @@ pub enum Change {
 pub struct Fork {
     patch: Patch,
     working_patch: WorkingPatch,
+    observers: ChangeObservers,
+    read_set: Option<Arc<Mutex<ReadSet>>>,
 }
 
 /// A set of changes that can be atomically applied to a `Database`.
@@ -366,6 +534,17 @@ pub struct Fork {
 /// This set can contain changes from multiple indexes. Changes can be read from the `Patch`
 /// using its `RawAccess` implementation.
 ///
+/// # Reading from a Patch
+///
+/// A `Patch` implements [`Snapshot`], and `&Patch` can be used to instantiate indexes
+/// in the same way as a snapshot. Reads overlay the changes in the patch on top of the snapshot
+/// the patch is based on, so they reflect the state the database would have after merging
+/// the patch (provided that no other patches are merged before it). In particular, entries
+/// removed in the patch and entries of indexes cleared in the patch are read as absent.
+/// This allows validating a patch against the resulting state before committing it.
+///
+/// [`Snapshot`]: trait.Snapshot.html
+///
 /// # Examples
 ///
 /// ```
@@ -380,6 +559,26 @@ pub struct Fork {
 /// let list = patch.get_list::<_, i32>("list");
 /// assert_eq!(list.len(), 3);
 /// ```
+///
+/// Validating a patch before merging it:
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// fork.get_map("balances").put("alice", 10_u64);
+/// db.merge(fork.into_patch()).unwrap();
+///
+/// let fork = db.fork();
+/// fork.get_map::<_, str, u64>("balances").remove("alice");
+/// let patch = fork.into_patch();
+/// // The removed entry is read as absent.
+/// let balances = patch.get_map::<_, str, u64>("balances");
+/// assert_eq!(balances.get("alice"), None);
+/// assert!(balances.iter().next().is_none());
+/// // ...while the database is not changed until the patch is merged.
+/// assert_eq!(db.snapshot().get_map::<_, str, u64>("balances").get("alice"), Some(10));
+/// ```
 #[derive(Debug)]
 pub struct Patch {
     snapshot: Box<dyn Snapshot>,
@@ -484,25 +683,176 @@ enum NextIterValue {
 /// workflow should only be used for minor changes, for which the proof that a patch does not overlap
 /// with concurrent patches is tractable.
 ///
+/// # Implementing a Backend
+///
+/// `Database` and [`Snapshot`] form the storage backend abstraction; forks, indexes
+/// and the [`access`] layer are implemented on top of them and work unchanged with any backend.
+/// [`RocksDB`] and [`TemporaryDB`] are the backends provided by this crate. A custom backend
+/// needs to:
+///
+/// - Implement [`Snapshot`], providing point reads and ordered iteration over views
+///   identified by [`ResolvedAddress`]es.
+/// - Implement `Database`, applying changes obtained with [`Patch::into_changes`] atomically.
+/// - Call [`check_database`] once when the database is opened.
+///
+/// [`Snapshot`]: trait.Snapshot.html
+/// [`access`]: access/index.html
+/// [`RocksDB`]: struct.RocksDB.html
+/// [`TemporaryDB`]: struct.TemporaryDB.html
+/// [`ResolvedAddress`]: struct.ResolvedAddress.html
+/// [`Patch::into_changes`]: struct.Patch.html#method.into_changes
+/// [`check_database`]: fn.check_database.html
 /// [`snapshot`]: #tymethod.snapshot
 /// [`fork`]: #method.fork
 /// [`merge`]: #tymethod.merge
 /// [interior-mut]: https://doc.rust-lang.org/book/ch15-05-interior-mutability.html
 pub trait Database: Send + Sync + 'static {
     /// Creates a new snapshot of the database from its current state.
+    ///
+    /// The returned snapshot does not borrow the database: `Snapshot` implementations
+    /// are required to be `Send + Sync + 'static`, so the snapshot may be stored
+    /// in long-lived structs or moved to another thread. Use [`OwnedSnapshot`] to build
+    /// indexes and schemas from such a snapshot without borrowing it.
+    ///
+    /// [`OwnedSnapshot`]: struct.OwnedSnapshot.html
     fn snapshot(&self) -> Box<dyn Snapshot>;
 
     /// Creates a new fork of the database from its current state.
     fn fork(&self) -> Fork {
+        self.fork_from(self.snapshot())
+    }
+
+    /// Creates a new fork based on a snapshot captured earlier. Entries not written
+    /// in the fork are read from `snapshot`, so a computation performed in the fork
+    /// is reproducible regardless of the changes merged into the database in the meantime.
+    ///
+    /// The snapshot must be obtained from this database; otherwise, the results of reading
+    /// from the fork and merging its patch are unspecified.
+    ///
+    /// # Merging
+    ///
+    /// The patch of the fork can be merged into the database as usual. If the database
+    /// has been changed since the snapshot was taken, this is a non-sequential merge
+    /// (see the [trait docs](#merge-workflow)): the patch overwrites entries changed
+    /// in the meantime without detecting a conflict, and the fork **must not** change
+    /// indexes modified after the snapshot was taken. Creating new indexes in the fork
+    /// updates the shared index metadata and thus counts as such an overlapping change
+    /// if other indexes were created in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_entry("input").set(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    /// let snapshot = db.snapshot();
+    ///
+    /// let fork = db.fork();
+    /// fork.get_entry("input").set(2_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// // Compute against the old state...
+    /// let fork = db.fork_from(snapshot);
+    /// let input = fork.get_entry::<_, u32>("input").get().unwrap();
+    /// assert_eq!(input, 1);
+    /// fork.get_entry("output").set(input * 10);
+    /// // ...and commit to the new one.
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(snapshot.get_entry::<_, u32>("input").get(), Some(2));
+    /// assert_eq!(snapshot.get_entry::<_, u32>("output").get(), Some(10));
+    /// ```
+    fn fork_from(&self, snapshot: Box<dyn Snapshot>) -> Fork {
+        Fork {
+            patch: Patch {
+                snapshot,
+                changes: HashMap::new(),
+            },
+            working_patch: WorkingPatch::new(),
+            observers: ChangeObservers::default(),
+            read_set: None,
+        }
+    }
+
+    /// Creates a new fork of the database from its current state, which records values read
+    /// from the database into a [`ReadSet`]. The read set can be obtained with
+    /// [`Fork::read_set`] and used to merge the fork with [`merge_checked`].
+    ///
+    /// Tracking reads has a performance cost, so it should only be used for forks
+    /// merged concurrently with other forks.
+    ///
+    /// [`ReadSet`]: struct.ReadSet.html
+    /// [`Fork::read_set`]: struct.Fork.html#method.read_set
+    /// [`merge_checked`]: #method.merge_checked
+    fn fork_tracking(&self) -> Fork {
+        let read_set = Arc::new(Mutex::new(ReadSet::new()));
+        let snapshot = TrackingSnapshot::new(self.snapshot(), Arc::clone(&read_set));
         Fork {
             patch: Patch {
-                snapshot: self.snapshot(),
+                snapshot: Box::new(snapshot),
                 changes: HashMap::new(),
             },
             working_patch: WorkingPatch::new(),
+            observers: ChangeObservers::default(),
+            read_set: Some(read_set),
         }
     }
 
+    /// Creates a new fork of the database, which reads the latest committed state
+    /// of the database instead of a snapshot taken at fork creation.
+    ///
+    /// Use this method for long-lived forks that should observe changes merged
+    /// into the database after the fork was created.
+    ///
+    /// # Isolation
+    ///
+    /// The returned fork provides weaker isolation than a fork created with [`fork`].
+    /// Entries not written in the fork are read from the state of the database at the time
+    /// of each read, so two reads of the same entry may return different values if a patch
+    /// is merged in between. Iterators read entries in batches, each of which reflects
+    /// the database state at the time it is read. Index metadata (e.g., the length of a list)
+    /// is read in the same way, so an index concurrently modified in the database
+    /// may appear inconsistent.
+    ///
+    /// Read-your-writes still holds: entries written to the fork, including removed entries
+    /// and cleared indexes, are always read from the fork changes.
+    ///
+    /// Merging the fork follows the same rules as merging non-sequentially created forks;
+    /// see the [trait docs](#merge-workflow).
+    ///
+    /// The default implementation falls back to [`fork`], i.e., reads from a snapshot.
+    /// Backends provided by this crate override it.
+    ///
+    /// [`fork`]: #method.fork
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_entry("written").set(0_u32);
+    /// fork.get_entry("unwritten").set(0_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let fork = db.fork_latest();
+    /// fork.get_entry("written").set(1_u32);
+    /// let other_fork = db.fork();
+    /// other_fork.get_entry("written").set(2_u32);
+    /// other_fork.get_entry("unwritten").set(3_u32);
+    /// db.merge(other_fork.into_patch()).unwrap();
+    ///
+    /// // The fork observes the merged changes for entries it has not written.
+    /// assert_eq!(fork.get_entry::<_, u32>("unwritten").get(), Some(3));
+    /// assert_eq!(fork.get_entry::<_, u32>("written").get(), Some(1));
+    /// ```
+    fn fork_latest(&self) -> Fork {
+        self.fork()
+    }
+
     /// Atomically applies a sequence of patch changes to the database.
     ///
     /// Note that this method may be called concurrently from different threads, the
@@ -538,6 +888,167 @@ pub trait Database: Send + Sync + 'static {
     /// will be returned. In case of an error, the method guarantees no changes are applied to
     /// the database.
     fn merge_sync(&self, patch: Patch) -> Result<()>;
+
+    /// Atomically applies a patch to the database with the specified durability guarantees.
+    ///
+    /// The default implementation calls [`merge_sync`] for [`WriteDurability::WalSync`]
+    /// and [`merge`] otherwise, which is appropriate for databases without a write-ahead log.
+    ///
+    /// [`merge`]: #tymethod.merge
+    /// [`merge_sync`]: #tymethod.merge_sync
+    /// [`WriteDurability::WalSync`]: enum.WriteDurability.html#variant.WalSync
+    ///
+    /// # Logical Safety
+    ///
+    /// The same considerations as for `merge` apply; see the [trait docs](#merge-workflow).
+    ///
+    /// # Errors
+    ///
+    /// If this method encounters any form of I/O or other error during merging, an error variant
+    /// will be returned. In case of an error, the method guarantees no changes are applied to
+    /// the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, RocksDB, WriteDurability};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// fork.get_entry("entry").set(1_u32);
+    /// db.merge_with_options(fork.into_patch(), WriteDurability::WalSync).unwrap();
+    /// assert_eq!(db.snapshot().get_entry::<_, u32>("entry").get(), Some(1));
+    /// ```
+    fn merge_with_options(&self, patch: Patch, durability: WriteDurability) -> Result<()> {
+        match durability {
+            WriteDurability::WalSync => self.merge_sync(patch),
+            WriteDurability::NoWal | WriteDurability::WalNoSync => self.merge(patch),
+        }
+    }
+
+    /// Atomically applies a patch to the database if the values recorded in `read_set`
+    /// have not changed since they were read. This allows to implement optimistic
+    /// transactions: a patch is computed in a fork created with [`fork_tracking`],
+    /// and the computation is retried with a fresh fork if the merge fails with a conflict.
+    ///
+    /// Unlike [`merge`], this method is safe to use with forks created and merged
+    /// non-sequentially (see the [trait docs](#merge-workflow)), as long as all data
+    /// the patch depends on was read via the fork.
+    ///
+    /// The default implementation checks the read set and then merges the patch
+    /// without additional synchronization, so a concurrent merge in between may go unnoticed.
+    /// Implementations should override this method to make the check and the merge atomic;
+    /// the implementations in this crate do this.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MergeError::Conflict`] without applying any changes if any value recorded
+    /// in the read set has changed. Returns [`MergeError::Storage`] if merging fails.
+    ///
+    /// [`fork_tracking`]: #method.fork_tracking
+    /// [`merge`]: #tymethod.merge
+    /// [`MergeError::Conflict`]: enum.MergeError.html#variant.Conflict
+    /// [`MergeError::Storage`]: enum.MergeError.html#variant.Storage
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, MergeError, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_entry("counter").set(0_u64);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// // Two concurrent transactions incrementing the counter.
+    /// let forks = vec![db.fork_tracking(), db.fork_tracking()];
+    /// for fork in &forks {
+    ///     let mut counter = fork.get_entry::<_, u64>("counter");
+    ///     counter.set(counter.get().unwrap() + 1);
+    /// }
+    /// let mut results = forks.into_iter().map(|fork| {
+    ///     let read_set = fork.read_set();
+    ///     db.merge_checked(fork.into_patch(), read_set)
+    /// });
+    /// assert!(results.next().unwrap().is_ok());
+    /// // The second transaction has read an outdated counter value.
+    /// assert!(matches!(results.next().unwrap(), Err(MergeError::Conflict(_))));
+    /// assert_eq!(db.snapshot().get_entry::<_, u64>("counter").get(), Some(1));
+    /// ```
+    fn merge_checked(&self, patch: Patch, read_set: ReadSet) -> StdResult<(), MergeError> {
+        let snapshot = self.snapshot();
+        read_set.check(|addr, key| snapshot.get(addr, key))?;
+        self.merge(patch).map_err(MergeError::from)
+    }
+
+    /// Applies a patch to the database if the resulting state satisfies
+    /// all `constraints`. Constraints are checked against the patch itself, which
+    /// implements [`Snapshot`] by applying its changes on top of the snapshot the patch
+    /// was created from. If patches are created and merged sequentially
+    /// (see the [trait docs](#merge-workflow)), this is exactly the post-merge state
+    /// of the database.
+    ///
+    /// In the default implementation, the check and the merge are not synchronized, so
+    /// the constraints are not guaranteed to hold if other patches are merged concurrently;
+    /// such merges need to be synchronized externally. The `RocksDB` implementation
+    /// performs the check and the merge under the same lock as [`merge_checked`], and checks
+    /// the constraints against the patch changes applied on top of the latest database state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without applying any changes if any of the constraints is violated;
+    /// the error message contains the description returned by the first violated constraint.
+    /// Returns an error if merging fails.
+    ///
+    /// See [`ConstraintSet`] for an example.
+    ///
+    /// [`Snapshot`]: trait.Snapshot.html
+    /// [`merge_checked`]: #method.merge_checked
+    /// [`ConstraintSet`]: validation/struct.ConstraintSet.html
+    fn merge_validated(&self, patch: Patch, constraints: &ConstraintSet) -> Result<()> {
+        constraints
+            .check(&patch)
+            .map_err(|violation| Error::new(format!("Constraint violated: {}", violation)))?;
+        self.merge(patch)
+    }
+
+    /// Persists all data written to the database so far, including data that has been
+    /// merged without a write-ahead log (e.g., with [`RocksDB::bulk_merge`]).
+    ///
+    /// For `RocksDB`, this flushes memtables of all column families to SST files.
+    /// This differs from [`merge_sync`], which makes a merge durable by syncing
+    /// the write-ahead log, but leaves the merged data in memtables. Flushing is useful,
+    /// e.g., before copying database files with filesystem-level tools instead of
+    /// [`RocksDB::create_checkpoint`].
+    ///
+    /// The default implementation does nothing, which is appropriate for in-memory databases.
+    ///
+    /// # Errors
+    ///
+    /// If this method encounters any form of I/O or other error during flushing, an error variant
+    /// will be returned.
+    ///
+    /// [`RocksDB::bulk_merge`]: struct.RocksDB.html#method.bulk_merge
+    /// [`RocksDB::create_checkpoint`]: struct.RocksDB.html#method.create_checkpoint
+    /// [`merge_sync`]: #tymethod.merge_sync
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Installs a sink receiving [`MetricsEvent`]s about merges and created snapshots,
+    /// replacing the previously installed sink, if any. If no sink is installed,
+    /// the database does not collect any metrics.
+    ///
+    /// The sink is shared by all handles to the database, including its clones, so it
+    /// can be installed on a database shared as `Arc<dyn Database>`.
+    ///
+    /// The default implementation ignores the sink; the backends in this crate
+    /// override it.
+    ///
+    /// [`MetricsEvent`]: enum.MetricsEvent.html
+    fn set_metrics_sink(&self, sink: Box<dyn MetricsSink>) {
+        drop(sink);
+    }
 }
 
 /// Extension trait for `Database`.
@@ -609,12 +1120,21 @@ pub trait DatabaseExt: Database {
                     view_changes.insert(key.to_vec(), Change::Put(value.to_vec()));
                 }
             }
+            for range in changes.removed_ranges() {
+                let mut iter = snapshot.iter(name, &range.start);
+                while let Some((key, value)) = iter.next() {
+                    if !range.contains(key) {
+                        break;
+                    }
+                    view_changes.insert(key.to_vec(), Change::Put(value.to_vec()));
+                }
+            }
 
             rev_changes.insert(
                 name.clone(),
                 ViewChanges {
                     data: view_changes,
-                    is_cleared: false,
+                    ..ViewChanges::default()
                 },
             );
         }
@@ -625,6 +1145,199 @@ pub trait DatabaseExt: Database {
             changes: rev_changes,
         })
     }
+
+    /// Creates a snapshot of the database, runs `f` with it and drops the snapshot.
+    ///
+    /// This is the recommended pattern for read-only operations: all reads performed
+    /// by the closure observe the same consistent database state, and the snapshot is released
+    /// as soon as the closure returns. Holding snapshots for a long time prevents the backend
+    /// from reclaiming the space occupied by outdated data (e.g., for `RocksDB`).
+    /// If the operation needs to be retried, call this method again to read a fresh state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DatabaseExt, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(vec![1_u32, 2, 3]);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let sum: u32 = db.with_snapshot(|snapshot| snapshot.get_list::<_, u32>("list").iter().sum());
+    /// assert_eq!(sum, 6);
+    /// ```
+    fn with_snapshot<R>(&self, f: impl FnOnce(&dyn Snapshot) -> R) -> R {
+        let snapshot = self.snapshot();
+        f(snapshot.as_ref())
+    }
+
+    /// Fallible version of [`with_snapshot`], which allows to use the `?` operator
+    /// in the closure.
+    ///
+    /// [`with_snapshot`]: #method.with_snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns an error returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::{AccessError, FromAccess}, Database, DatabaseExt, ListIndex, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let len = db.try_with_snapshot(|snapshot| {
+    ///     let list = ListIndex::<_, u32>::from_access(snapshot, "list".into())?;
+    ///     Ok::<_, AccessError>(list.len())
+    /// })?;
+    /// assert_eq!(len, 0);
+    /// # Ok::<_, AccessError>(())
+    /// ```
+    fn try_with_snapshot<R, E>(
+        &self,
+        f: impl FnOnce(&dyn Snapshot) -> StdResult<R, E>,
+    ) -> StdResult<R, E> {
+        self.with_snapshot(f)
+    }
+
+    /// Creates a fork which is merged into the database once the size of accumulated
+    /// changes exceeds `max_pending_bytes`. See [`BatchedFork`] for details.
+    ///
+    /// [`BatchedFork`]: struct.BatchedFork.html
+    fn batched_fork(&self, max_pending_bytes: usize) -> BatchedFork<'_>
+    where
+        Self: Sized,
+    {
+        BatchedFork::new(self, max_pending_bytes)
+    }
+
+    /// Writes the contents of the index at the specified address to `writer`
+    /// as newline-delimited JSON, which is useful for debugging. Each line corresponds
+    /// to a single index entry and has the following fields:
+    ///
+    /// - `key_hex`: hex-encoded raw key
+    /// - `key`: `Debug` representation of the key
+    /// - `value`: value serialized with `serde`
+    ///
+    /// The index is read from a snapshot of the database; it is not created if it does
+    /// not exist. Available only with the `debug-tools` crate feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no index at the specified address, or if writing
+    /// to `writer` fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DatabaseExt, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_map("map").put(&1_u8, "foo".to_owned());
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// db.dump_index::<_, u8, String, _>("map", &mut output).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "{\"key\":\"1\",\"key_hex\":\"01\",\"value\":\"foo\"}\n"
+    /// );
+    /// ```
+    #[cfg(feature = "debug-tools")]
+    fn dump_index<I, K, V, W>(&self, addr: I, mut writer: W) -> Result<()>
+    where
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+        K::Owned: fmt::Debug,
+        V: BinaryValue + serde::Serialize,
+        W: std::io::Write,
+    {
+        let snapshot = self.snapshot();
+        let addr = addr.into();
+        let metadata = (&snapshot)
+            .get_index_metadata(addr.clone())
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new(format!("No index at address {:?}", addr)))?;
+        let view: View<_> = (&snapshot)
+            .get_or_create_view(addr, metadata.index_type())
+            .map_err(|e| Error::new(e.to_string()))?
+            .into();
+
+        for (raw_key, value) in view.iter::<_, [u8], V>(&()) {
+            let entry = serde_json::json!({
+                "key_hex": to_hex(&raw_key),
+                "key": format!("{:?}", K::read(&raw_key)),
+                "value": value,
+            });
+            serde_json::to_writer(&mut writer, &entry).map_err(|e| Error::new(e.to_string()))?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the contents of the database to `writer` in a portable format, which
+    /// can be read by [`import`] into a database with any backend.
+    ///
+    /// The output consists of a header followed by `(column_family, key, value)` triples,
+    /// each field of which is prefixed by its length as a little-endian `u32`. Keys
+    /// of indexes include the index identifier, and the views holding index metadata
+    /// are exported as well, so the imported database is structurally identical
+    /// to the exported one. The contents are read from a single snapshot of the database.
+    ///
+    /// [`import`]: #method.import
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `writer` fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DatabaseExt, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_list("list").extend(vec![1_u32, 2, 3]);
+    /// fork.get_map(("group", &1_u8)).put(&2_u64, "foo".to_owned());
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let mut dump = vec![];
+    /// db.export(&mut dump).unwrap();
+    /// let other_db = TemporaryDB::new();
+    /// other_db.import(&dump[..]).unwrap();
+    ///
+    /// let snapshot = other_db.snapshot();
+    /// assert_eq!(snapshot.get_list::<_, u32>("list").len(), 3);
+    /// assert_eq!(
+    ///     snapshot.get_map::<_, u64, String>(("group", &1_u8)).get(&2),
+    ///     Some("foo".to_owned())
+    /// );
+    /// ```
+    fn export<W: std::io::Write>(&self, writer: W) -> Result<()>
+    where
+        Self: Sized,
+    {
+        crate::export::export(self, writer)
+    }
+
+    /// Reads the database contents written by [`export`] from `reader` and merges them
+    /// into this database. The database must not contain any indexes.
+    ///
+    /// Unlike `merge`, the import is not atomic: the contents are merged in batches
+    /// to limit memory usage. The views holding index metadata are written last, so
+    /// the database does not contain indexes until the import has succeeded; an interrupted
+    /// import can be retried on the same database.
+    ///
+    /// [`export`]: #method.export
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database is not empty, if the input is not a valid
+    /// export stream (including a truncated one), or if reading or merging fails.
+    fn import<R: std::io::Read>(&self, reader: R) -> Result<()>
+    where
+        Self: Sized,
+    {
+        crate::export::import(self, reader)
+    }
 }
 
 impl<T: Database> DatabaseExt for T {}
@@ -657,24 +1370,287 @@ pub trait Snapshot: Send + Sync + 'static {
     /// Returns an iterator over the entries of the snapshot in ascending order starting from
     /// the specified key. The iterator element type is `(&[u8], &[u8])`.
     fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_>;
-}
 
-/// A trait that defines a streaming iterator over storage view entries. Unlike
-/// the standard [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html)
-/// trait, `Iterator` in `metaldb` is low-level and, therefore, operates with bytes.
-pub trait Iterator {
-    /// Advances the iterator and returns a reference to the next key and value.
-    fn next(&mut self) -> Option<(&[u8], &[u8])>;
+    /// Returns an iterator over the entries of the snapshot with keys starting with `prefix`
+    /// in ascending order starting from the specified key. `from` must start with `prefix`.
+    ///
+    /// The iterator is only required to yield the entries with keys starting with `prefix`;
+    /// it may either end after them or continue with other entries, so callers must check
+    /// returned keys. This allows backends to use prefix-based optimizations, such as
+    /// prefix Bloom filters in `RocksDB`. The default implementation
+    /// calls [`iter`](#tymethod.iter).
+    fn iter_prefix(&self, name: &ResolvedAddress, prefix: &[u8], from: &[u8]) -> Iter<'_> {
+        let _ = prefix;
+        self.iter(name, from)
+    }
+
+    /// Returns the entry with the greatest key less than `before` for the specified address,
+    /// or the last entry for the address if `before` is `None`.
+    ///
+    /// The default implementation iterates over the entries from the start; backends should
+    /// override it with a reverse seek bounded by the address.
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        let mut iter = self.iter(name, &[]);
+        let mut last = None;
+        while let Some((key, value)) = iter.next() {
+            if before.map_or(false, |before| key >= before) {
+                break;
+            }
+            last = Some((key.to_vec(), value.to_vec()));
+        }
+        last
+    }
+
+    /// Returns a value corresponding to the specified address and key, borrowing it
+    /// from the snapshot if possible.
+    ///
+    /// The default implementation copies the value returned by [`get`]. Backends storing
+    /// values in memory should override it to avoid copying.
+    ///
+    /// [`get`]: #tymethod.get
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.get(name, key).map(PinnedValue::from)
+    }
+}
+
+impl dyn Snapshot {
+    /// Checks whether an index with the specified address has been created. Only the index
+    /// metadata is read; unlike creating an index, this has no side effects.
+    ///
+    /// This is a shortcut for [`CopyAccessExt::index_type`], which also works with forks
+    /// and other accesses.
+    ///
+    /// [`CopyAccessExt::index_type`]: access/trait.CopyAccessExt.html#method.index_type
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address is invalid, e.g., contains invalid chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_list("list").push(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let snapshot = db.snapshot();
+    /// assert!(snapshot.exists_index("list"));
+    /// assert!(!snapshot.exists_index("other_list"));
+    /// ```
+    pub fn exists_index<I>(&self, addr: I) -> bool
+    where
+        I: Into<IndexAddress>,
+    {
+        self.index_type(addr).is_some()
+    }
+
+    /// Returns addresses and types of all indexes in the snapshot, including indexes
+    /// in groups and indexes created within unfinished migrations.
+    ///
+    /// The list reflects the state of the database at the moment the snapshot was taken;
+    /// indexes created after that are not included. Indexes are ordered by their names
+    /// and then by keys within a group, as determined by comparing the byte representations
+    /// of names and keys. Indexes in migration are ordered among themselves in the same way,
+    /// but their position relative to ordinary indexes is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, IndexAddress, IndexType, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_map(("wallets", &1_u8)).put(&1_u8, 2_u8);
+    /// fork.get_list("history").push(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let snapshot = db.snapshot();
+    /// let indexes = snapshot.index_names();
+    /// assert_eq!(indexes.len(), 2);
+    /// assert_eq!(indexes[0], (IndexAddress::from_root("history"), IndexType::List));
+    /// let wallets_addr = IndexAddress::from_root("wallets").append_key(&1_u8);
+    /// assert_eq!(indexes[1], (wallets_addr, IndexType::Map));
+    /// ```
+    pub fn index_names(&self) -> Vec<(IndexAddress, IndexType)> {
+        IndexesPool::new(self)
+            .indexes_by_prefix(&[])
+            .map(|(full_name, _, index_type)| {
+                let addr = IndexAddress::from_fully_qualified_name(&full_name);
+                (addr, index_type)
+            })
+            .collect()
+    }
+}
+
+/// Raw value read from the database, which is borrowed from the underlying storage
+/// when possible and owned otherwise.
+///
+/// `PinnedValue` is returned by [`MapIndex::get_pinned`] and similar methods. Since the value
+/// may borrow from the storage, it is tied to the lifetime of the index (and thus,
+/// of the snapshot or fork the index is based on); call [`into_vec`] to detach it.
+///
+/// [`MapIndex::get_pinned`]: indexes/map/struct.MapIndex.html#method.get_pinned
+/// [`into_vec`]: #method.into_vec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedValue<'a>(Cow<'a, [u8]>);
+
+impl PinnedValue<'_> {
+    /// Checks if the value is borrowed from the storage, i.e., was obtained without copying.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+
+    /// Converts this value into an owned byte vector, copying it if necessary.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0.into_owned()
+    }
+}
+
+impl Deref for PinnedValue<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for PinnedValue<'_> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for PinnedValue<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self(Cow::Borrowed(value))
+    }
+}
+
+impl From<Vec<u8>> for PinnedValue<'_> {
+    fn from(value: Vec<u8>) -> Self {
+        Self(Cow::Owned(value))
+    }
+}
+
+/// Returns the entry with the greatest key less than `before` in the snapshot, skipping
+/// entries from the key ranges removed in `changes`.
+fn last_stored_before(
+    snapshot: &dyn Snapshot,
+    name: &ResolvedAddress,
+    changes: &ViewChanges,
+    before: Option<&[u8]>,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut bound = before.map(<[u8]>::to_vec);
+    loop {
+        let (key, value) = snapshot.last_before(name, bound.as_deref())?;
+        let removed_range = changes
+            .removed_ranges
+            .iter()
+            .find(|range| range.contains(&key));
+        match removed_range {
+            // The entry is hidden; continue the search below the start of the removed range.
+            Some(range) => bound = Some(range.start.clone()),
+            None => return Some((key, value)),
+        }
+    }
+}
+
+/// Wraps a snapshot iterator so that it skips entries from the key ranges removed
+/// in `changes`.
+pub(crate) fn skip_removed_ranges<'a>(
+    snapshot_iter: Iter<'a>,
+    changes: Option<&'a ViewChanges>,
+) -> Iter<'a> {
+    match changes {
+        Some(changes) if !changes.removed_ranges.is_empty() => Box::new(SkipRemovedRanges {
+            inner: snapshot_iter,
+            ranges: &changes.removed_ranges,
+        }),
+        _ => snapshot_iter,
+    }
+}
+
+struct SkipRemovedRanges<'a> {
+    inner: Iter<'a>,
+    ranges: &'a [KeyRange],
+}
+
+impl SkipRemovedRanges<'_> {
+    /// Advances the inner iterator until it points to an entry outside removed ranges.
+    /// Returns `false` if the iterator is exhausted.
+    fn skip_removed(&mut self) -> bool {
+        loop {
+            let is_removed = match self.inner.peek() {
+                Some((key, _)) => self.ranges.iter().any(|range| range.contains(key)),
+                None => return false,
+            };
+            if !is_removed {
+                return true;
+            }
+            self.inner.next();
+        }
+    }
+}
+
+impl Iterator for SkipRemovedRanges<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        if self.skip_removed() {
+            self.inner.next()
+        } else {
+            None
+        }
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        if self.skip_removed() {
+            self.inner.peek()
+        } else {
+            None
+        }
+    }
+}
+
+/// A trait that defines a streaming iterator over storage view entries. Unlike
+/// the standard [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html)
+/// trait, `Iterator` in `metaldb` is low-level and, therefore, operates with bytes.
+pub trait Iterator {
+    /// Advances the iterator and returns a reference to the next key and value.
+    fn next(&mut self) -> Option<(&[u8], &[u8])>;
 
     /// Returns a reference to the current key and value without advancing the iterator.
     fn peek(&mut self) -> Option<(&[u8], &[u8])>;
 }
 
 impl Patch {
-    /// Iterates over changes in this patch.
-    pub(crate) fn into_changes(self) -> HashMap<ResolvedAddress, ViewChanges> {
+    /// Creates a patch with the specified changes on top of `snapshot`.
+    pub(crate) fn with_changes(
+        snapshot: Box<dyn Snapshot>,
+        changes: HashMap<ResolvedAddress, ViewChanges>,
+    ) -> Self {
+        Self { snapshot, changes }
+    }
+
+    /// Returns changes in this patch grouped by the view address. This method is intended
+    /// for implementing [`Database::merge`] in custom backends.
+    ///
+    /// [`Database::merge`]: trait.Database.html#tymethod.merge
+    pub fn into_changes(self) -> HashMap<ResolvedAddress, ViewChanges> {
         self.changes
     }
+
+    /// Returns the total size of keys and values written in this patch.
+    pub(crate) fn pending_bytes(&self) -> usize {
+        let mut stats = PatchStats::default();
+        for (key, change) in self.changes.values().flat_map(|changes| &changes.data) {
+            stats.add_change(key, change);
+        }
+        stats.approx_bytes
+    }
 }
 
 impl Snapshot for Patch {
@@ -731,6 +1707,15 @@ impl Snapshot for Patch {
             .unwrap_or_else(|()| self.snapshot.contains(name, key))
     }
 
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.changes
+            .get(name)
+            .map_or(Err(()), |changes| changes.get_ref(key))
+            .map(|value| value.map(PinnedValue::from))
+            // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
+            .unwrap_or_else(|()| self.snapshot.get_pinned(name, key))
+    }
+
     fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
         let maybe_changes = self.changes.get(name);
         let changes_iter = maybe_changes.map(|changes| {
@@ -744,7 +1729,59 @@ impl Snapshot for Patch {
             // Ignore all changes from the snapshot.
             Box::new(ChangesIter::new(changes_iter.unwrap()))
         } else {
-            Box::new(ForkIter::new(self.snapshot.iter(name, from), changes_iter))
+            let snapshot_iter = skip_removed_ranges(self.snapshot.iter(name, from), maybe_changes);
+            Box::new(ForkIter::new(snapshot_iter, changes_iter))
+        }
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        last_before_with_changes(&*self.snapshot, name, self.changes.get(name), before)
+    }
+}
+
+/// Returns the entry with the greatest key less than `before` taking into account
+/// changes on top of the snapshot.
+pub(crate) fn last_before_with_changes(
+    snapshot: &dyn Snapshot,
+    name: &ResolvedAddress,
+    changes: Option<&ViewChanges>,
+    before: Option<&[u8]>,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let changes = match changes {
+        Some(changes) => changes,
+        None => return snapshot.last_before(name, before),
+    };
+
+    let mut bound = before.map(<[u8]>::to_vec);
+    loop {
+        let upper_bound = bound.as_deref().map_or(Bound::Unbounded, Bound::Excluded);
+        let last_change = changes
+            .data
+            .range::<[u8], _>((Bound::Unbounded, upper_bound))
+            .next_back();
+        let last_stored = if changes.is_cleared() {
+            None
+        } else {
+            last_stored_before(snapshot, name, changes, bound.as_deref())
+        };
+
+        match last_change {
+            Some((key, change))
+                if last_stored
+                    .as_ref()
+                    .map_or(true, |(stored_key, _)| key >= stored_key) =>
+            {
+                match change {
+                    Change::Put(value) => return Some((key.clone(), value.clone())),
+                    // The entry is removed; look for the previous one.
+                    Change::Delete => bound = Some(key.clone()),
+                }
+            }
+            _ => return last_stored,
         }
     }
 }
@@ -768,9 +1805,41 @@ impl AsReadonly for &'_ Patch {
 }
 
 impl Fork {
+    /// Creates a fork reading through to the latest state of a database.
+    /// See [`Database::fork_latest`] for details.
+    ///
+    /// [`Database::fork_latest`]: trait.Database.html#method.fork_latest
+    pub(crate) fn with_latest_state(state: impl LatestState) -> Self {
+        Self {
+            patch: Patch {
+                snapshot: Box::new(LatestSnapshot::new(state)),
+                changes: HashMap::new(),
+            },
+            working_patch: WorkingPatch::new(),
+            observers: ChangeObservers::default(),
+            read_set: None,
+        }
+    }
+
     /// Finalizes all changes that were made after previous execution of the `flush` method.
     /// If no `flush` method had been called before, finalizes all changes that were
     /// made after creation of `Fork`.
+    ///
+    /// Since the method takes `&mut self`, indexes instantiated from the fork must be dropped
+    /// before the call. Thus, there is no way to use an index handle after the fork state
+    /// it was created from is flushed or rolled back:
+    ///
+    /// ```compile_fail
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// let mut map = fork.get_map("map");
+    /// map.put(&1_u32, 2_u32);
+    /// fork.flush(); // Won't compile: `fork` is borrowed by `map`!
+    /// map.put(&2_u32, 3_u32);
+    /// ```
+    ///
+    /// The same holds for converting the fork into a patch, which consumes the fork.
     pub fn flush(&mut self) {
         let working_patch = mem::replace(&mut self.working_patch, WorkingPatch::new());
         working_patch.merge_into(&mut self.patch);
@@ -795,6 +1864,41 @@ impl Fork {
         self.working_patch = WorkingPatch::new();
     }
 
+    /// Discards all changes in the fork, both [flushed](#method.flush) and not flushed,
+    /// so that the fork reflects the snapshot it was created from. Unlike creating a new fork,
+    /// this retains allocated memory and the callbacks registered with
+    /// [`on_change`](#method.on_change), which is useful in retry loops.
+    ///
+    /// Discarding only affects the fork; changes already merged into the database
+    /// are not reverted. The fork is not updated to the latest database state either;
+    /// if this is needed, create a new fork instead. For forks created with
+    /// [`Database::fork_tracking`], the recorded reads are retained, since the underlying
+    /// snapshot does not change.
+    ///
+    /// [`Database::fork_tracking`]: trait.Database.html#method.fork_tracking
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_list("list").push(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let mut fork = db.fork();
+    /// fork.get_list("list").push(2_u32);
+    /// fork.flush();
+    /// fork.get_list("list").push(3_u32);
+    /// fork.discard();
+    /// assert_eq!(fork.get_list::<_, u32>("list").iter().collect::<Vec<_>>(), vec![1]);
+    /// assert_eq!(fork.pending_changes().put_count, 0);
+    /// ```
+    pub fn discard(&mut self) {
+        self.working_patch.changes.get_mut().clear();
+        self.patch.changes.clear();
+    }
+
     /// Rolls back the migration with the specified name. This will remove all indexes
     /// within the migration.
     pub(crate) fn rollback_migration(&mut self, prefix: &str) {
@@ -806,12 +1910,223 @@ impl Fork {
         }
     }
 
+    /// Returns statistics of the changes accumulated in this fork, both flushed
+    /// and not flushed. The fork is not modified.
+    ///
+    /// Changes to indexes that are currently borrowed mutably are not accounted for;
+    /// they become visible once the index is dropped. Clearing an index or a range of keys
+    /// is not counted as a separate operation. Index metadata written by the database itself
+    /// (e.g., when an index is created) is included into the statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let stats = fork.pending_changes();
+    /// assert_eq!(stats.put_count, 0);
+    ///
+    /// fork.get_list("list").extend(vec![1_u32, 2, 3]);
+    /// let stats = fork.pending_changes();
+    /// assert!(stats.put_count >= 3);
+    /// assert!(stats.approx_bytes > 0);
+    /// ```
+    pub fn pending_changes(&self) -> PatchStats {
+        let working_changes = self.working_patch.changes.borrow();
+        let mut stats = PatchStats::default();
+
+        for (address, changes) in &self.patch.changes {
+            let newer_changes = working_changes.get(address).and_then(Option::as_ref);
+            if newer_changes.map_or(false, |newer| newer.is_cleared()) {
+                // Flushed changes are overridden by clearing the index.
+                continue;
+            }
+            for (key, change) in &changes.data {
+                // Newer changes to the same key override flushed changes.
+                let is_overridden = newer_changes.map_or(false, |newer| {
+                    newer.data.contains_key(key) || newer.is_removed(key)
+                });
+                if !is_overridden {
+                    stats.add_change(key, change);
+                }
+            }
+        }
+
+        for changes in working_changes.values().flatten() {
+            for (key, change) in &changes.data {
+                stats.add_change(key, change);
+            }
+        }
+        stats
+    }
+
     /// Converts the fork into `Patch` consuming the fork instance.
     pub fn into_patch(mut self) -> Patch {
         self.flush();
         self.patch
     }
 
+    /// Atomically renames an index, i.e., moves its data and metadata from the `from` address
+    /// to the `to` address. The change is applied to the database when the fork is merged.
+    ///
+    /// # Cost
+    ///
+    /// Index data is stored in a column family determined by the name part of the address,
+    /// with keys prefixed by an identifier assigned to the index on creation. Thus:
+    ///
+    /// - If the addresses have the same name and differ only in keys (i.e., the indexes
+    ///   belong to the same [group]), renaming only moves metadata and takes constant time.
+    /// - Otherwise, all entries of the index are copied to another column family,
+    ///   which takes time and memory linear in the index size. Avoid renaming large indexes
+    ///   in this way.
+    ///
+    /// [group]: indexes/group/struct.Group.html
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no index at the `from` address, if an index at
+    /// the `to` address already exists, or if either address is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, IndexAddress, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// fork.get_list("cache.old").extend(vec![1_u32, 2, 3]);
+    ///
+    /// let from = IndexAddress::from_root("cache.old");
+    /// let to = IndexAddress::from_root("cache.new");
+    /// fork.rename_index(&from, &to).unwrap();
+    /// assert_eq!(fork.index_type("cache.old"), None);
+    /// assert_eq!(fork.get_list::<_, u32>("cache.new").len(), 3);
+    /// // The target index exists now.
+    /// assert!(fork.rename_index(&from, &to).is_err());
+    /// ```
+    pub fn rename_index(&mut self, from: &IndexAddress, to: &IndexAddress) -> Result<()> {
+        // Flushing is necessary for iterating over the index data.
+        self.flush();
+
+        let access_error = |e: AccessError| Error::new(e.to_string());
+        let metadata = ViewWithMetadata::get_metadata(&*self, from)
+            .map_err(access_error)?
+            .ok_or_else(|| Error::new(format!("No index at address {:?}", from)))?;
+        if ViewWithMetadata::get_metadata(&*self, to)
+            .map_err(access_error)?
+            .is_some()
+        {
+            return Err(Error::new(format!(
+                "Index at address {:?} already exists",
+                to
+            )));
+        }
+
+        IndexesPool::new(&*self)
+            .rename_index(&from.fully_qualified_name(), &to.fully_qualified_name());
+        if from.name() != to.name() {
+            let id = Some(metadata.identifier());
+            let mut old_view = View::new(&*self, ResolvedAddress::new(from.name(), id));
+            let mut new_view = View::new(&*self, ResolvedAddress::new(to.name(), id));
+            for (key, value) in old_view.iter::<_, [u8], Vec<u8>>(&()) {
+                new_view.put(key.as_slice(), value);
+            }
+            old_view.clear();
+        }
+        Ok(())
+    }
+
+    /// Removes all indexes in the namespace `prefix`, together with their data and metadata.
+    /// The change is applied to the database when the fork is merged.
+    ///
+    /// The namespace is defined in the same way as for [`Prefixed`] accesses. For example,
+    /// clearing the namespace `tenant` removes indexes `tenant.foo` and `(tenant.bar, 1_u32)`,
+    /// but not `tenant` or `tenant_2.foo`. Indexes in [migrations] are not affected.
+    ///
+    /// Returns the number of removed indexes.
+    ///
+    /// [`Prefixed`]: access/struct.Prefixed.html
+    /// [migrations]: migration/index.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is not a valid name component (e.g., contains a dot).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::{CopyAccessExt, Prefixed}, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// let tenant = Prefixed::new("tenant_42", &fork);
+    /// tenant.get_list("list").extend(vec![1_u32, 2, 3]);
+    /// tenant.get_entry(("entries", &1_u8)).set("!".to_owned());
+    /// fork.get_entry("tenant_4.entry").set(1_u64);
+    ///
+    /// assert_eq!(fork.clear_prefix("tenant_42"), 2);
+    /// assert_eq!(fork.index_type("tenant_42.list"), None);
+    /// assert_eq!(fork.get_entry::<_, u64>("tenant_4.entry").get(), Some(1));
+    /// ```
+    pub fn clear_prefix(&mut self, prefix: &str) -> usize {
+        assert_valid_name_component(prefix);
+        // Flushing is necessary to keep `self.patch` up to date.
+        self.flush();
+
+        let removed_addrs = IndexesPool::new(&*self).remove_namespace(prefix);
+        for addr in &removed_addrs {
+            self.patch.changes.entry(addr.clone()).or_default().clear();
+        }
+        removed_addrs.len()
+    }
+
+    /// Registers a callback invoked for each put or removal of a key staged in the fork.
+    /// The callback receives the resolved address of the index, the raw key within the index
+    /// and the change itself. Multiple callbacks can be registered; they are invoked
+    /// in the registration order.
+    ///
+    /// Callbacks are invoked immediately when an index is modified, including modifications
+    /// of index metadata performed by the database itself. Thus, callbacks are invoked
+    /// for the changes which may be later discarded with [`rollback`]. Clearing an index
+    /// does not invoke callbacks, since it does not correspond to a single key.
+    ///
+    /// [`rollback`]: #method.rollback
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Change, Database, TemporaryDB};
+    /// # use std::sync::{Arc, Mutex};
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// let removed_keys = Arc::new(Mutex::new(vec![]));
+    /// let removed_keys_ = Arc::clone(&removed_keys);
+    /// fork.on_change(Box::new(move |_addr, key, change| {
+    ///     if *change == Change::Delete {
+    ///         removed_keys_.lock().unwrap().push(key.to_vec());
+    ///     }
+    /// }));
+    ///
+    /// let mut map = fork.get_map("map");
+    /// map.put(&1_u8, 2_u8);
+    /// map.remove(&1_u8);
+    /// assert_eq!(*removed_keys.lock().unwrap(), vec![vec![1_u8]]);
+    /// ```
+    pub fn on_change(&mut self, callback: Box<dyn FnMut(&ResolvedAddress, &[u8], &Change) + Send>) {
+        self.observers.callbacks.get_mut().push(callback);
+    }
+
+    /// Returns values read from the database by this fork so far. The read set is empty
+    /// unless the fork was created with [`Database::fork_tracking`].
+    ///
+    /// [`Database::fork_tracking`]: trait.Database.html#method.fork_tracking
+    pub fn read_set(&self) -> ReadSet {
+        self.read_set
+            .as_ref()
+            .map_or_else(ReadSet::new, |read_set| {
+                read_set.lock().expect("Failed to lock read set").clone()
+            })
+    }
+
     /// Returns a readonly wrapper around the fork. Indexes created based on the readonly
     /// version cannot be modified; on the other hand, it is possible to have multiple
     /// copies of an index at the same time.
@@ -830,6 +2145,8 @@ impl From<Patch> for Fork {
         Self {
             patch,
             working_patch: WorkingPatch::new(),
+            observers: ChangeObservers::default(),
+            read_set: None,
         }
     }
 }
@@ -846,7 +2163,7 @@ impl<'a> RawAccess for &'a Fork {
         ChangesMut {
             changes,
             key: address.clone(),
-            parent: WorkingPatchRef::Borrowed(&self.working_patch),
+            parent: WorkingPatchRef::Borrowed(*self),
         }
     }
 }
@@ -967,34 +2284,261 @@ impl<'a> RawAccess for ReadonlyFork<'a> {
 #[derive(Debug, Clone)]
 pub struct OwnedReadonlyFork(Rc<Fork>);
 
-impl RawAccess for OwnedReadonlyFork {
-    type Changes = ChangesRef<'static>;
+impl RawAccess for OwnedReadonlyFork {
+    type Changes = ChangesRef<'static>;
+
+    fn snapshot(&self) -> &dyn Snapshot {
+        &self.0.patch
+    }
+
+    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
+        ChangesRef {
+            inner: self.0.working_patch.clone_view_changes(address),
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl AsReadonly for OwnedReadonlyFork {
+    type Readonly = Self;
+
+    fn as_readonly(&self) -> Self::Readonly {
+        self.clone()
+    }
+}
+
+impl AsReadonly for Rc<Fork> {
+    type Readonly = OwnedReadonlyFork;
+
+    fn as_readonly(&self) -> Self::Readonly {
+        OwnedReadonlyFork(self.clone())
+    }
+}
+
+/// Owned, cheaply cloneable snapshot that can be used as an index access.
+///
+/// Unlike `&Box<dyn Snapshot>`, `OwnedSnapshot` does not borrow anything, so indexes
+/// and schemas built from it have the `'static` lifetime. `OwnedSnapshot` is `Send + Sync`;
+/// clones share the same underlying snapshot (e.g., the same `RocksDB` snapshot handle),
+/// so they are guaranteed to observe identical database state. Since `OwnedSnapshot`
+/// implements [`Snapshot`] itself, it can also be passed where a `Box<dyn Snapshot>`
+/// is expected; see [`clone_snapshot`](#method.clone_snapshot).
+///
+/// [`Snapshot`]: trait.Snapshot.html
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{
+/// #     access::{AccessExt, CopyAccessExt}, Database, ListIndex, OwnedSnapshot, TemporaryDB,
+/// # };
+/// struct Cache {
+///     list: ListIndex<OwnedSnapshot, u32>,
+/// }
+///
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// fork.get_list("list").extend(vec![1_u32, 2, 3]);
+/// db.merge(fork.into_patch()).unwrap();
+///
+/// let snapshot = OwnedSnapshot::from(db.snapshot());
+/// let cache = Cache {
+///     list: snapshot.get_list("list"),
+/// };
+/// let handle = std::thread::spawn(move || snapshot.get_list::<_, u32>("list").len());
+/// assert_eq!(handle.join().unwrap(), 3);
+/// assert_eq!(cache.list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OwnedSnapshot(Arc<dyn Snapshot>);
+
+impl OwnedSnapshot {
+    /// Wraps the provided snapshot.
+    pub fn new(snapshot: Box<dyn Snapshot>) -> Self {
+        Self(snapshot.into())
+    }
+
+    /// Returns a boxed snapshot sharing the underlying snapshot with this one.
+    /// Unlike [`Database::snapshot`], this does not create a new snapshot of the database.
+    ///
+    /// [`Database::snapshot`]: trait.Database.html#tymethod.snapshot
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{
+    /// #     access::{AccessExt, CopyAccessExt}, Database, OwnedSnapshot, TemporaryDB,
+    /// # };
+    /// let db = TemporaryDB::new();
+    /// let snapshot = OwnedSnapshot::from(db.snapshot());
+    /// let boxed = snapshot.clone_snapshot();
+    ///
+    /// let fork = db.fork();
+    /// fork.get_entry("entry").set(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    /// // Both snapshots reflect the database state before the merge.
+    /// assert!(!boxed.get_entry::<_, u32>("entry").exists());
+    /// assert!(!snapshot.get_entry::<_, u32>("entry").exists());
+    /// ```
+    pub fn clone_snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(self.clone())
+    }
+}
+
+impl Snapshot for OwnedSnapshot {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(name, key)
+    }
+
+    fn multi_get<'a>(
+        &self,
+        name: &ResolvedAddress,
+        keys: &'a mut dyn iter::Iterator<Item = &'a [u8]>,
+    ) -> Vec<Option<Vec<u8>>> {
+        self.0.multi_get(name, keys)
+    }
+
+    fn contains(&self, name: &ResolvedAddress, key: &[u8]) -> bool {
+        self.0.contains(name, key)
+    }
+
+    fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
+        self.0.iter(name, from)
+    }
+
+    fn iter_prefix(&self, name: &ResolvedAddress, prefix: &[u8], from: &[u8]) -> Iter<'_> {
+        self.0.iter_prefix(name, prefix, from)
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        self.0.last_before(name, before)
+    }
+
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.0.get_pinned(name, key)
+    }
+}
+
+impl From<Box<dyn Snapshot>> for OwnedSnapshot {
+    fn from(snapshot: Box<dyn Snapshot>) -> Self {
+        Self::new(snapshot)
+    }
+}
+
+impl AsRef<dyn Snapshot> for OwnedSnapshot {
+    fn as_ref(&self) -> &dyn Snapshot {
+        &*self.0
+    }
+}
+
+/// Fork that is periodically merged into the database to cap the size of accumulated changes.
+///
+/// Indexes can be created from `&BatchedFork` in the same way as from `&Fork`. Once the indexes
+/// are dropped, [`merge_if_needed`] checks the size of accumulated changes and, if it exceeds
+/// the threshold, merges them into the database and starts a new batch. This is useful for
+/// bulk loading, which would otherwise build one enormous in-memory patch.
+///
+/// Note that read-your-writes only holds within the current batch window: changes from merged
+/// batches are visible via the database, but a batch cannot be rolled back once merged,
+/// and other readers may observe a partially loaded state.
+///
+/// [`merge_if_needed`]: #method.merge_if_needed
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, Database, DatabaseExt, TemporaryDB};
+/// let db = TemporaryDB::new();
+/// let mut batched = db.batched_fork(1_024);
+/// for i in 0_u64..1_000 {
+///     batched.get_list("list").push(i);
+///     batched.merge_if_needed().unwrap();
+/// }
+/// batched.finish().unwrap();
+///
+/// let snapshot = db.snapshot();
+/// assert_eq!(snapshot.get_list::<_, u64>("list").len(), 1_000);
+/// ```
+pub struct BatchedFork<'a> {
+    db: &'a dyn Database,
+    // Only equals `None` during merges.
+    fork: Option<Fork>,
+    max_pending_bytes: usize,
+}
+
+impl fmt::Debug for BatchedFork<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("BatchedFork")
+            .field("fork", &self.fork)
+            .field("max_pending_bytes", &self.max_pending_bytes)
+            .finish()
+    }
+}
+
+impl<'a> BatchedFork<'a> {
+    fn new(db: &'a dyn Database, max_pending_bytes: usize) -> Self {
+        Self {
+            db,
+            fork: Some(db.fork()),
+            max_pending_bytes,
+        }
+    }
 
-    fn snapshot(&self) -> &dyn Snapshot {
-        &self.0.patch
+    fn fork_ref(&self) -> &Fork {
+        // `unwrap` is safe due to the way we define `fork`
+        self.fork.as_ref().unwrap()
     }
 
-    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
-        ChangesRef {
-            inner: self.0.working_patch.clone_view_changes(address),
-            _lifetime: PhantomData,
+    /// Returns the total size of keys and values written in the current batch.
+    pub fn pending_bytes(&mut self) -> usize {
+        let fork = self.fork.as_mut().unwrap();
+        fork.flush();
+        fork.patch.pending_bytes()
+    }
+
+    /// Merges the current batch into the database if its size exceeds the threshold.
+    /// Returns `true` if the batch was merged.
+    pub fn merge_if_needed(&mut self) -> Result<bool> {
+        if self.pending_bytes() > self.max_pending_bytes {
+            let patch = self.fork.take().unwrap().into_patch();
+            self.db.merge(patch)?;
+            self.fork = Some(self.db.fork());
+            Ok(true)
+        } else {
+            Ok(false)
         }
     }
+
+    /// Merges the remaining changes into the database.
+    pub fn finish(mut self) -> Result<()> {
+        let patch = self.fork.take().unwrap().into_patch();
+        self.db.merge(patch)
+    }
 }
 
-impl AsReadonly for OwnedReadonlyFork {
-    type Readonly = Self;
+impl<'a> RawAccess for &'a BatchedFork<'_> {
+    type Changes = ChangesMut<'a>;
 
-    fn as_readonly(&self) -> Self::Readonly {
-        self.clone()
+    fn snapshot(&self) -> &dyn Snapshot {
+        &self.fork_ref().patch
+    }
+
+    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
+        let fork: &'a Fork = self.fork_ref();
+        fork.changes(address)
     }
 }
 
-impl AsReadonly for Rc<Fork> {
-    type Readonly = OwnedReadonlyFork;
+impl<'a> AsReadonly for &'a BatchedFork<'_> {
+    type Readonly = ReadonlyFork<'a>;
 
     fn as_readonly(&self) -> Self::Readonly {
-        OwnedReadonlyFork(self.clone())
+        self.fork_ref().readonly()
     }
 }
 
@@ -1024,6 +2568,22 @@ impl Snapshot for Box<dyn Snapshot> {
     fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
         self.as_ref().iter(name, from)
     }
+
+    fn iter_prefix(&self, name: &ResolvedAddress, prefix: &[u8], from: &[u8]) -> Iter<'_> {
+        self.as_ref().iter_prefix(name, prefix, from)
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        self.as_ref().last_before(name, before)
+    }
+
+    fn get_pinned(&self, name: &ResolvedAddress, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.as_ref().get_pinned(name, key)
+    }
 }
 
 impl<'a, T> ForkIter<'a, T>
@@ -1170,8 +2730,24 @@ pub const DB_VERSION: u8 = 0;
 pub const DB_METADATA: &str = "__DB_METADATA__";
 /// Version attribute name.
 pub const VERSION_NAME: &str = "version";
+/// Names of views used by the database internally. These views are not a part
+/// of any index and have no identifier.
+pub(crate) const SYSTEM_VIEW_NAMES: &[&str] = &[
+    DB_METADATA,
+    INDEXES_POOL_NAME,
+    PREFIX_LENGTHS_NAME,
+    SCHEMA_VERSION_NAME,
+];
 
 /// This function checks that the given database is compatible with the current `MerkleDB` version.
+/// If the database is empty, the current version is written to it.
+///
+/// Database backends should call this function when a database is opened.
+///
+/// # Errors
+///
+/// Returns an error if the database has an incompatible version or if writing
+/// the version fails.
 pub fn check_database(db: &mut dyn Database) -> Result<()> {
     let fork = db.fork();
     {
@@ -1195,12 +2771,24 @@ pub fn check_database(db: &mut dyn Database) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::{
-        AsReadonly, Change, Database, DatabaseExt, Fork, OwnedReadonlyFork, Patch, Rc,
-        ResolvedAddress, Snapshot, StdIterator, View,
+        AsReadonly, Change, ChangeObservers, Database, DatabaseExt, Fork, MergeError,
+        OwnedReadonlyFork, OwnedSnapshot, Patch, PatchStats, Rc, ResolvedAddress, Snapshot,
+        StdIterator, View,
+    };
+    use crate::{
+        access::{AccessExt, CopyAccessExt, Prefixed},
+        migration::Migration,
+        validation::snapshots_eq,
+        DBOptions, ErrorKind, IndexAddress, IndexType, RocksDB, TempDbOptions, TemporaryDB,
     };
-    use crate::{access::CopyAccessExt, TemporaryDB};
 
-    use std::{collections::HashSet, iter};
+    use std::{
+        cell::RefCell,
+        collections::HashSet,
+        iter,
+        sync::{Arc, Mutex},
+        thread,
+    };
 
     #[test]
     fn readonly_indexes_are_timely_dropped() {
@@ -1214,6 +2802,240 @@ mod tests {
         fork.into_patch();
     }
 
+    #[test]
+    fn checking_index_existence_in_snapshot() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").push(1_u32);
+        fork.get_map(("group", &1_u8)).put(&1_u8, 2_u8);
+        assert!(!db.snapshot().exists_index("list"));
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        assert!(snapshot.exists_index("list"));
+        assert!(snapshot.exists_index(("group", &1_u8)));
+        assert!(!snapshot.exists_index(("group", &2_u8)));
+        let patch = db.fork().into_patch();
+        let patch_snapshot: &dyn Snapshot = &patch;
+        assert!(patch_snapshot.exists_index("list"));
+        assert!(!patch_snapshot.exists_index("other_list"));
+        assert!(patch.changes.is_empty());
+    }
+
+    #[test]
+    fn reading_from_patch() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2, 3]);
+        let mut map = fork.get_map("map");
+        for i in 0_u32..5 {
+            map.put(&i, i);
+        }
+        drop(map);
+        fork.get_entry("entry").set(1_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        fork.get_list::<_, u32>("list").clear();
+        let mut map = fork.get_map::<_, u32, u32>("map");
+        map.remove(&1);
+        map.put(&3, 30);
+        map.put(&10, 10);
+        drop(map);
+        fork.get_entry::<_, u32>("entry").take();
+        fork.get_entry("new_entry").set(2_u32);
+        let patch = fork.into_patch();
+
+        assert!(patch.get_list::<_, u32>("list").is_empty());
+        assert_eq!(patch.get_list::<_, u32>("list").iter().next(), None);
+        let map = patch.get_map::<_, u32, u32>("map");
+        assert_eq!(map.get(&1), None);
+        assert!(!map.contains(&1));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![(0, 0), (2, 2), (3, 30), (4, 4), (10, 10)]
+        );
+        assert_eq!(patch.get_entry::<_, u32>("entry").get(), None);
+        assert_eq!(patch.get_entry::<_, u32>("new_entry").get(), Some(2));
+
+        // Reading from the patch does not modify it.
+        db.merge(patch).unwrap();
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, u32, u32>("map");
+        assert_eq!(map.values().collect::<Vec<_>>(), vec![0, 2, 30, 4, 10]);
+        assert_eq!(snapshot.get_entry::<_, u32>("entry").get(), None);
+    }
+
+    #[test]
+    fn reading_with_snapshot() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(1_u64);
+        db.merge(fork.into_patch()).unwrap();
+
+        let values = db.with_snapshot(|snapshot| {
+            let first = snapshot.get_entry::<_, u64>("entry").get();
+            // Changes merged concurrently are not visible in the snapshot.
+            let fork = db.fork();
+            fork.get_entry("entry").set(2_u64);
+            db.merge(fork.into_patch()).unwrap();
+            (first, snapshot.get_entry::<_, u64>("entry").get())
+        });
+        assert_eq!(values, (Some(1), Some(1)));
+
+        let res: Result<(), &str> = db.try_with_snapshot(|snapshot| {
+            assert_eq!(snapshot.get_entry::<_, u64>("entry").get(), Some(2));
+            Err("failure")
+        });
+        assert_eq!(res, Err("failure"));
+    }
+
+    fn check_fork_reading_latest_state(db: &dyn Database) {
+        let fork = db.fork();
+        let mut map = fork.get_map("map");
+        for i in 0_u32..600 {
+            map.put(&i, i);
+        }
+        drop(map);
+        fork.get_entry("entry").set(0_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork_latest();
+        {
+            let mut map = fork.get_map::<_, u32, u32>("map");
+            map.put(&1, 100);
+            map.remove(&2);
+        }
+
+        let other_fork = db.fork();
+        {
+            let mut map = other_fork.get_map::<_, u32, u32>("map");
+            map.put(&1, 1_000);
+            map.put(&2, 2_000);
+            map.put(&3, 3_000);
+            map.put(&599, 599_000);
+        }
+        other_fork.get_entry("entry").set(1_u32);
+        db.merge(other_fork.into_patch()).unwrap();
+
+        // Unwritten entries are read from the latest database state.
+        assert_eq!(fork.get_entry::<_, u32>("entry").get(), Some(1));
+        let map = fork.get_map::<_, u32, u32>("map");
+        assert_eq!(map.get(&3), Some(3_000));
+        // Written entries are read from the fork.
+        assert_eq!(map.get(&1), Some(100));
+        assert_eq!(map.get(&2), None);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.next(), Some((0, 0)));
+        let fork = db.fork();
+        fork.get_map("map").put(&598_u32, 598_000_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        // Changes merged during iteration may be observed by later batches.
+        let entries: Vec<_> = iter.collect();
+        assert_eq!(entries.len(), 598);
+        assert_eq!(entries[0], (1, 100));
+        assert_eq!(entries[1], (3, 3_000));
+        assert_eq!(entries[596], (598, 598_000));
+        assert_eq!(entries[597], (599, 599_000));
+    }
+
+    #[test]
+    fn fork_reading_latest_state() {
+        check_fork_reading_latest_state(&TemporaryDB::new());
+        let spilling_db = TemporaryDB::with_options(TempDbOptions::new(Some(1_024)));
+        check_fork_reading_latest_state(&spilling_db);
+        assert!(spilling_db.is_spilled());
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+        check_fork_reading_latest_state(&db);
+    }
+
+    #[test]
+    fn fork_from_earlier_snapshot() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2]);
+        fork.get_entry("entry").set(1_u32);
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+
+        let fork = db.fork();
+        fork.get_list("list").push(3_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork_from(snapshot);
+        assert_eq!(
+            fork.get_list::<_, u32>("list").iter().collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let mut entry = fork.get_entry::<_, u32>("entry");
+        entry.set(entry.get().unwrap() + 1);
+        drop(entry);
+        db.merge(fork.into_patch()).unwrap();
+
+        // Indexes not changed in the fork retain changes merged after the snapshot.
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_entry::<_, u32>("entry").get(), Some(2));
+        assert_eq!(
+            snapshot
+                .get_list::<_, u32>("list")
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn exporting_and_importing_database() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2, 3]);
+        fork.get_entry("entry").set("foo".to_owned());
+        fork.get_map(("group", &1_u8)).put(&2_u64, "bar".to_owned());
+        fork.get_key_set(("group", &3_u8)).insert(&4_u16);
+        fork.get_sparse_list("empty").clear::<u32>();
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut dump = vec![];
+        db.export(&mut dump).unwrap();
+        let snapshot = db.snapshot();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let rocksdb = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+        let other_db = TemporaryDB::new();
+        for target in [&rocksdb as &dyn Database, &other_db] {
+            crate::export::import(target, &dump[..]).unwrap();
+            let imported = target.snapshot();
+            assert!(snapshots_eq(&*snapshot, &*imported, None));
+            assert_eq!(
+                imported.index_type(("group", &3_u8)),
+                Some(IndexType::KeySet)
+            );
+            assert_eq!(imported.get_list::<_, u32>("list").len(), 3);
+
+            // The database can be exported again, yielding the same stream.
+            let mut other_dump = vec![];
+            crate::export::export(target, &mut other_dump).unwrap();
+            assert_eq!(other_dump, dump);
+            // Import into a non-empty database is rejected.
+            let err = crate::export::import(target, &dump[..]).unwrap_err();
+            assert!(err.to_string().contains("non-empty database"), "{}", err);
+        }
+
+        // A truncated stream is rejected.
+        let err = TemporaryDB::new()
+            .import(&dump[..dump.len() - 1])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        let err = TemporaryDB::new()
+            .import(&b"not a metaldb export"[..])
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid export stream"), "{}", err);
+    }
+
     /// Asserts that a patch contains only the specified changes.
     fn check_patch<'a, I>(patch: &Patch, changes: I)
     where
@@ -1490,4 +3312,463 @@ mod tests {
         // Since the index is already created, this should lead to a panic.
         let _readonly_entry = fork.readonly().get_entry::<_, u32>("entry");
     }
+
+    #[test]
+    fn discarding_fork_changes() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut fork = db.fork();
+        fork.get_list::<_, u32>("list").clear();
+        fork.get_entry("new_entry").set(1_u8);
+        fork.flush();
+        fork.get_list("list").push(3_u32);
+        fork.discard();
+
+        assert_eq!(fork.index_type("new_entry"), None);
+        let list = fork.get_list::<_, u32>("list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2]);
+        drop(list);
+        assert_eq!(fork.pending_changes(), PatchStats::default());
+
+        // The fork can be reused after discarding changes.
+        fork.get_list("list").push(4_u32);
+        db.merge(fork.into_patch()).unwrap();
+        let list = db.snapshot().get_list::<_, u32>("list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn clearing_prefix() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("tenant_42.list").extend(vec![1_u32, 2]);
+        fork.get_map(("tenant_42.map", &1_u8)).put(&1_u8, 2_u8);
+        fork.get_list("tenant_4.list").extend(vec![3_u32]);
+        fork.get_map(("tenant_4.map", &1_u8)).put(&3_u8, 4_u8);
+        fork.get_entry("tenant_42").set(5_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut fork = db.fork();
+        fork.get_entry("tenant_42.entry").set(6_u32);
+        assert_eq!(fork.clear_prefix("tenant_42"), 3);
+        assert_eq!(fork.clear_prefix("tenant_42"), 0);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.index_type("tenant_42.list"), None);
+        assert_eq!(snapshot.index_type(("tenant_42.map", &1_u8)), None);
+        assert_eq!(snapshot.index_type("tenant_42.entry"), None);
+        assert_eq!(snapshot.get_entry::<_, u32>("tenant_42").get(), Some(5));
+        let list = snapshot.get_list::<_, u32>("tenant_4.list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![3]);
+        let map = snapshot.get_map::<_, u8, u8>(("tenant_4.map", &1_u8));
+        assert_eq!(map.get(&3), Some(4));
+
+        // Indexes can be recreated in the cleared namespace.
+        let fork = db.fork();
+        assert!(fork.get_list::<_, u32>("tenant_42.list").is_empty());
+        assert!(fork
+            .get_map::<_, u8, u8>(("tenant_42.map", &1_u8))
+            .get(&1)
+            .is_none());
+    }
+
+    #[test]
+    fn listing_index_names() {
+        let db = TemporaryDB::new();
+        assert!(db.snapshot().index_names().is_empty());
+
+        let fork = db.fork();
+        fork.get_entry("ns.entry").set(1_u8);
+        fork.get_key_set(("sets", &2_u32)).insert(&1_u8);
+        fork.get_key_set(("sets", &1_u32)).insert(&1_u8);
+        Migration::new("ns", &fork).get_list("list").push(1_u8);
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+
+        // Indexes created after the snapshot are not listed.
+        let fork = db.fork();
+        fork.get_entry("other").set(1_u8);
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut migrated_list = IndexAddress::from_root("ns.list");
+        migrated_list.set_in_migration();
+        let expected = vec![
+            (migrated_list, IndexType::List),
+            (IndexAddress::from_root("ns.entry"), IndexType::Entry),
+            (
+                IndexAddress::from_root("sets").append_key(&1_u32),
+                IndexType::KeySet,
+            ),
+            (
+                IndexAddress::from_root("sets").append_key(&2_u32),
+                IndexType::KeySet,
+            ),
+        ];
+        assert_eq!(snapshot.index_names(), expected);
+        for (addr, index_type) in expected {
+            assert_eq!(snapshot.index_type(addr), Some(index_type));
+        }
+    }
+
+    #[test]
+    fn batched_fork_merges_when_threshold_is_exceeded() {
+        let db = TemporaryDB::new();
+        let mut batched = db.batched_fork(100);
+        batched.get_entry("small").set(1_u8);
+        assert!(!batched.merge_if_needed().unwrap());
+        assert!(db.snapshot().get_entry::<_, u8>("small").get().is_none());
+
+        batched.get_list("list").extend(vec![0_u64; 20]);
+        // Changes are readable within the batch.
+        assert_eq!(batched.get_list::<_, u64>("list").len(), 20);
+        assert!(batched.merge_if_needed().unwrap());
+        assert_eq!(batched.pending_bytes(), 0);
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_entry::<_, u8>("small").get(), Some(1));
+        assert_eq!(snapshot.get_list::<_, u64>("list").len(), 20);
+
+        // Merged changes are visible in the next batch.
+        batched.get_list("list").push(1_u64);
+        assert_eq!(batched.get_list::<_, u64>("list").len(), 21);
+        batched.finish().unwrap();
+        assert_eq!(db.snapshot().get_list::<_, u64>("list").len(), 21);
+    }
+
+    #[cfg(feature = "debug-tools")]
+    #[test]
+    fn dump_index_as_json() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2]);
+        fork.get_entry("entry").set("!".to_owned());
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut output = vec![];
+        db.dump_index::<_, u64, u32, _>("list", &mut output)
+            .unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({ "key_hex": "0000000000000000", "key": "0", "value": 1 }),
+                serde_json::json!({ "key_hex": "0000000000000001", "key": "1", "value": 2 }),
+            ]
+        );
+
+        let mut output = vec![];
+        db.dump_index::<_, (), String, _>("entry", &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"key\":\"()\",\"key_hex\":\"\",\"value\":\"!\"}\n"
+        );
+
+        let err = db
+            .dump_index::<_, u64, u32, _>("other", Vec::<u8>::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("No index"));
+    }
+
+    #[test]
+    fn renaming_indexes() {
+        use crate::{IndexAddress, IndexType};
+
+        let db = TemporaryDB::new();
+        let mut fork = db.fork();
+        fork.get_map(("group", &1_u8)).put(&1_u8, 10_u64);
+        fork.get_list("list").extend(vec![1_u8, 2, 3]);
+        fork.get_list("other").push(0_u8);
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut fork = db.fork();
+        // Renaming within a group.
+        let from = IndexAddress::from_root("group").append_key(&1_u8);
+        let to = IndexAddress::from_root("group").append_key(&2_u8);
+        fork.rename_index(&from, &to).unwrap();
+        // Renaming to another column family, with unflushed changes.
+        fork.get_list("list").push(4_u8);
+        let from = IndexAddress::from_root("list");
+        let to = IndexAddress::from_root("renamed.list");
+        fork.rename_index(&from, &to).unwrap();
+
+        // Invalid renames.
+        let other = IndexAddress::from_root("other");
+        assert!(fork.rename_index(&from, &other).is_err());
+        assert!(fork.rename_index(&to, &other).is_err());
+        let reserved = IndexAddress::from_root("__reserved");
+        assert!(fork.rename_index(&to, &reserved).is_err());
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.index_type(("group", &1_u8)), None);
+        assert_eq!(snapshot.index_type(("group", &2_u8)), Some(IndexType::Map));
+        let map = snapshot.get_map::<_, u8, u64>(("group", &2_u8));
+        assert_eq!(map.get(&1), Some(10));
+        assert_eq!(snapshot.index_type("list"), None);
+        let list = snapshot.get_list::<_, u8>("renamed.list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(snapshot.get_list::<_, u8>("other").len(), 1);
+
+        // The renamed index can be recreated at the old address.
+        let fork = db.fork();
+        assert!(fork.get_list::<_, u8>("list").is_empty());
+        fork.get_list("list").push(5_u8);
+        assert_eq!(fork.get_list::<_, u8>("renamed.list").len(), 4);
+    }
+
+    #[test]
+    fn change_callbacks() {
+        type Log = Arc<Mutex<Vec<(&'static str, ResolvedAddress, Vec<u8>, Change)>>>;
+
+        let db = TemporaryDB::new();
+        let mut fork = db.fork();
+        // Create the index beforehand, so that its metadata is not logged.
+        fork.get_map::<_, u8, u8>("map");
+
+        let log: Log = Arc::default();
+        for &tag in &["first", "second"] {
+            let log = Arc::clone(&log);
+            fork.on_change(Box::new(move |addr, key, change| {
+                log.lock()
+                    .unwrap()
+                    .push((tag, addr.clone(), key.to_vec(), change.clone()));
+            }));
+        }
+
+        {
+            let mut map = fork.get_map("map");
+            map.put(&1_u8, 2_u8);
+            map.remove(&1_u8);
+            // Clearing does not invoke callbacks.
+            map.clear();
+        }
+        // Readonly accesses do not invoke callbacks.
+        assert!(fork
+            .readonly()
+            .get_map::<_, u8, u8>("map")
+            .get(&1)
+            .is_none());
+
+        let map_address = log.lock().unwrap()[0].1.clone();
+        let expected = vec![
+            ("first", map_address.clone(), vec![1], Change::Put(vec![2])),
+            ("second", map_address.clone(), vec![1], Change::Put(vec![2])),
+            ("first", map_address.clone(), vec![1], Change::Delete),
+            ("second", map_address, vec![1], Change::Delete),
+        ];
+        assert_eq!(*log.lock().unwrap(), expected);
+
+        // Callbacks are retained after flushing.
+        fork.flush();
+        fork.get_entry("entry").set(1_u8);
+        assert!(log.lock().unwrap().len() > expected.len());
+    }
+
+    #[test]
+    fn change_observers_are_send() {
+        fn assert_send<T: Send>() {}
+
+        // `Fork` itself is not `Send` since it shares view changes via `Rc`s,
+        // but callbacks must not be the reason for this.
+        assert_send::<ChangeObservers>();
+        assert_send::<Patch>();
+    }
+
+    #[test]
+    fn change_callback_writing_to_same_fork() {
+        thread_local! {
+            static FORK: RefCell<Option<Rc<Fork>>> = RefCell::new(None);
+        }
+
+        let db = TemporaryDB::new();
+        let mut fork = db.fork();
+        // Create the indexes beforehand, so that their metadata is not logged.
+        fork.get_map::<_, u8, u8>("map");
+        fork.get_list::<_, u8>("log");
+
+        let calls = Arc::new(Mutex::new(0));
+        let calls_ = Arc::clone(&calls);
+        fork.on_change(Box::new(move |_addr, key, _change| {
+            *calls_.lock().unwrap() += 1;
+            FORK.with(|fork| {
+                if let Some(fork) = &*fork.borrow() {
+                    fork.get_list("log").push(key[0]);
+                }
+            });
+        }));
+
+        let fork = Rc::new(fork);
+        FORK.with(|cell| *cell.borrow_mut() = Some(Rc::clone(&fork)));
+        fork.get_map("map").put(&1_u8, 2_u8);
+        fork.get_map("map").put(&3_u8, 4_u8);
+        FORK.with(|cell| cell.borrow_mut().take());
+
+        // Changes made by the callback are not reported to the callbacks.
+        assert_eq!(*calls.lock().unwrap(), 2);
+        let log = fork.get_list::<_, u8>("log");
+        assert_eq!(log.iter().collect::<Vec<_>>(), vec![1, 3]);
+    }
+
+    #[test]
+    fn pending_changes_statistics() {
+        let db = TemporaryDB::new();
+        let mut fork = db.fork();
+        assert_eq!(fork.pending_changes(), PatchStats::default());
+
+        {
+            let mut view = View::new(&fork, "foo");
+            view.put(&1_u8, 10_u8);
+            view.put(&2_u8, 20_u8);
+        }
+        fork.flush();
+        let stats = fork.pending_changes();
+        assert_eq!((stats.put_count, stats.delete_count), (2, 0));
+        assert_eq!(stats.approx_bytes, 4);
+
+        {
+            let mut view = View::new(&fork, "foo");
+            view.put(&1_u8, 11_u8);
+            view.remove(&2_u8);
+        }
+        let stats = fork.pending_changes();
+        assert_eq!((stats.put_count, stats.delete_count), (1, 1));
+        assert_eq!(stats.approx_bytes, 3);
+
+        {
+            let mut view = View::new(&fork, "bar");
+            view.put(&1_u8, 1_u8);
+            // Changes to a mutably borrowed view are not visible yet.
+            assert_eq!(fork.pending_changes(), stats);
+        }
+        let stats = fork.pending_changes();
+        assert_eq!((stats.put_count, stats.delete_count), (2, 1));
+
+        // Writes made via derived accesses are accounted for.
+        Prefixed::new("ns", &fork).get_entry("entry").set(1_u8);
+        let prefixed_stats = fork.pending_changes();
+        assert!(prefixed_stats.put_count > stats.put_count);
+
+        fork.flush();
+        assert_eq!(fork.pending_changes(), prefixed_stats);
+        View::new(&fork, "foo").clear();
+        let stats = fork.pending_changes();
+        assert_eq!(stats.put_count, prefixed_stats.put_count - 1);
+        assert_eq!(stats.delete_count, 0);
+    }
+
+    #[test]
+    fn owned_snapshot_outlives_database_borrow() {
+        fn assert_send_sync_static<T: Send + Sync + 'static>(_: &T) {}
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(1_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = OwnedSnapshot::from(db.snapshot());
+        assert_send_sync_static(&snapshot);
+        let entry = snapshot.get_entry::<_, u32>("entry");
+        drop(db);
+        // The snapshot remains usable after the database is dropped.
+        assert_eq!(entry.get(), Some(1));
+        let value = thread::spawn(move || snapshot.get_entry::<_, u32>("entry").get())
+            .join()
+            .unwrap();
+        assert_eq!(value, Some(1));
+    }
+
+    #[test]
+    fn cloned_rocksdb_snapshots_share_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = crate::RocksDB::open(dir.path(), &crate::DBOptions::default()).unwrap();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = OwnedSnapshot::from(db.snapshot());
+        let clones: Vec<_> = (0..3).map(|_| snapshot.clone_snapshot()).collect();
+        let fork = db.fork();
+        fork.get_list("list").push(3_u32);
+        db.merge(fork.into_patch()).unwrap();
+        drop(snapshot);
+
+        for clone in &clones {
+            let list = clone.get_list::<_, u32>("list");
+            assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2]);
+        }
+        assert_eq!(db.snapshot().get_list::<_, u32>("list").len(), 3);
+    }
+
+    #[test]
+    fn optimistic_merges() {
+        fn check_merges(db: &dyn Database) {
+            let fork = db.fork();
+            fork.get_map("map").put(&1_u8, 1_u8);
+            fork.get_list("list").push(1_u8);
+            db.merge(fork.into_patch()).unwrap();
+
+            // A fork without tracking has an empty read set.
+            let fork = db.fork();
+            fork.get_map::<_, u8, u8>("map").get(&1);
+            assert!(fork.read_set().is_empty());
+
+            // Forks touching different data do not conflict.
+            let first = db.fork_tracking();
+            let first_value = first.get_map::<_, u8, u8>("map").get(&1).unwrap();
+            first.get_map("map").put(&2, first_value);
+            let second = db.fork_tracking();
+            second.get_entry("entry").set(5_u8);
+            let first_reads = first.read_set();
+            assert!(!first_reads.is_empty());
+            db.merge_checked(first.into_patch(), first_reads).unwrap();
+            let second_reads = second.read_set();
+            db.merge_checked(second.into_patch(), second_reads).unwrap();
+
+            // Concurrent pushes to the list conflict, since both forks read the list length.
+            let first = db.fork_tracking();
+            first.get_list("list").push(2_u8);
+            let second = db.fork_tracking();
+            second.get_list("list").push(3_u8);
+            let first_reads = first.read_set();
+            db.merge_checked(first.into_patch(), first_reads).unwrap();
+            let second_reads = second.read_set();
+            let err = db
+                .merge_checked(second.into_patch(), second_reads)
+                .unwrap_err();
+            assert!(matches!(err, MergeError::Conflict(_)), "{:?}", err);
+            let snapshot = db.snapshot();
+            let list = snapshot.get_list::<_, u8>("list");
+            assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2]);
+
+            // Iterated entries are tracked as well.
+            let first = db.fork_tracking();
+            let sum: u8 = first.get_map::<_, u8, u8>("map").values().sum();
+            first.get_entry("sum").set(sum);
+            let second = db.fork();
+            second.get_map("map").put(&2_u8, 10_u8);
+            db.merge(second.into_patch()).unwrap();
+            let first_reads = first.read_set();
+            let err = db
+                .merge_checked(first.into_patch(), first_reads)
+                .unwrap_err();
+            match err {
+                MergeError::Conflict(conflict) => assert_eq!(conflict.key, vec![2]),
+                other => panic!("Unexpected error: {:?}", other),
+            }
+            assert_eq!(db.snapshot().get_entry::<_, u8>("sum").get(), None);
+        }
+
+        check_merges(&TemporaryDB::new());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let rocksdb = crate::RocksDB::open(temp_dir.path(), &crate::DBOptions::default()).unwrap();
+        check_merges(&rocksdb);
+    }
 }
@@ -2,28 +2,138 @@
 
 use thiserror::Error;
 
+use std::io;
+
 /// The error type for I/O operations with the `Database`.
 ///
 /// Application code in most cases should consider these errors as fatal. At the same time,
 /// it may be possible to recover from an error after manual intervention (e.g., by restarting
-/// the process or freeing up more disc space).
+/// the process or freeing up more disc space). The [`kind`](#method.kind) of the error
+/// allows to distinguish between causes of the error programmatically.
 #[derive(Debug, Clone, Error)]
 #[error("{}", message)]
 pub struct Error {
+    kind: ErrorKind,
     message: String,
 }
 
 impl Error {
     /// Creates a new storage error with an information message about the reason.
+    /// The kind of the error is set to [`ErrorKind::Other`].
+    ///
+    /// [`ErrorKind::Other`]: enum.ErrorKind.html#variant.Other
     pub fn new<T: Into<String>>(message: T) -> Self {
+        Self::with_kind(ErrorKind::Other, message)
+    }
+
+    /// Creates a new storage error of the specified kind.
+    pub fn with_kind<T: Into<String>>(kind: ErrorKind, message: T) -> Self {
         Self {
+            kind,
             message: message.into(),
         }
     }
+
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// Category of a storage [`Error`].
+///
+/// [`Error`]: struct.Error.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// I/O error, e.g., a failure to read from or write to disk or insufficient disk space.
+    Io,
+    /// Stored data is corrupted.
+    Corruption,
+    /// The database or a resource it needs is busy, e.g., locked by another process.
+    Busy,
+    /// A requested entity (e.g., a file) is not found.
+    NotFound,
+    /// Any other error.
+    Other,
+}
+
+/// Prefixes of `RocksDB` error messages corresponding to the status codes.
+const ROCKSDB_STATUS_PREFIXES: &[(&str, ErrorKind)] = &[
+    ("IO error: ", ErrorKind::Io),
+    ("Corruption: ", ErrorKind::Corruption),
+    ("Resource busy: ", ErrorKind::Busy),
+    ("NotFound: ", ErrorKind::NotFound),
+];
+
+impl ErrorKind {
+    /// Infers the error kind from a `RocksDB` error message. The messages start with
+    /// the status code, e.g., `"IO error: No space left on device"`.
+    fn from_rocksdb_message(message: &str) -> Self {
+        ROCKSDB_STATUS_PREFIXES
+            .iter()
+            .find(|(prefix, _)| message.starts_with(prefix))
+            .map_or(Self::Other, |&(_, kind)| kind)
+    }
 }
 
 impl From<rocksdb::Error> for Error {
     fn from(err: rocksdb::Error) -> Self {
-        Self::new(err.to_string())
+        let message = err.into_string();
+        Self::with_kind(ErrorKind::from_rocksdb_message(&message), message)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        let kind = match err.kind() {
+            io::ErrorKind::NotFound => ErrorKind::NotFound,
+            _ => ErrorKind::Io,
+        };
+        Self::with_kind(kind, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind};
+
+    use std::io;
+
+    #[test]
+    fn error_kinds_of_rocksdb_messages() {
+        let messages = &[
+            ("IO error: No space left on device", ErrorKind::Io),
+            ("Corruption: block checksum mismatch", ErrorKind::Corruption),
+            ("Resource busy: ", ErrorKind::Busy),
+            ("NotFound: CURRENT", ErrorKind::NotFound),
+            (
+                "Invalid argument: Column family not found",
+                ErrorKind::Other,
+            ),
+            ("IO", ErrorKind::Other),
+        ];
+        for &(message, kind) in messages {
+            assert_eq!(
+                ErrorKind::from_rocksdb_message(message),
+                kind,
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn error_display_and_kind() {
+        let err = Error::new("Something went wrong");
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(err.to_string(), "Something went wrong");
+
+        let io_err = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let err = Error::from(io_err);
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.to_string(), "no such file");
+        let io_err = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        assert_eq!(Error::from(io_err).kind(), ErrorKind::Io);
     }
 }
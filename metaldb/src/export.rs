@@ -0,0 +1,159 @@
+//! Portable format for exporting and importing database contents.
+//!
+//! An export stream starts with the `MAGIC` bytes and a format version, followed by records.
+//! Each record is a `(column_family, key, value)` triple; every field is prefixed by its length
+//! as a little-endian `u32`. Keys of index views are prefixed by the index identifier,
+//! in the same way as `ResolvedAddress::keyed` does it. A record with an empty column family
+//! name terminates the stream.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    io::{self, BufReader, BufWriter, Read, Write},
+    mem,
+    num::NonZeroU64,
+};
+
+use crate::{
+    db::{Change, SYSTEM_VIEW_NAMES},
+    views::{IndexesPool, INDEXES_POOL_NAME},
+    Database, Error, ErrorKind, Patch, ResolvedAddress, Result, ViewChanges,
+};
+
+/// Bytes identifying an export stream.
+const MAGIC: &[u8] = b"metaldb-export";
+/// Version of the export format.
+const FORMAT_VERSION: u8 = 1;
+/// Approximate size of keys and values merged into the database at once during import.
+const IMPORT_BATCH_BYTES: usize = 16 << 20;
+/// Size of the index identifier prefixing keys of index views.
+const ID_SIZE: usize = mem::size_of::<u64>();
+
+pub(crate) fn export(db: &dyn Database, writer: impl Write) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(MAGIC)?;
+    writer.write_u8(FORMAT_VERSION)?;
+
+    let snapshot = db.snapshot();
+    let indexes: Vec<_> = IndexesPool::new(&*snapshot)
+        .indexes_by_prefix(&[])
+        .map(|(_, address, _)| address)
+        .collect();
+    for address in &indexes {
+        let mut iter = snapshot.iter(address, &[]);
+        while let Some((key, value)) = iter.next() {
+            write_record(&mut writer, &address.name, &address.keyed(key), value)?;
+        }
+    }
+
+    // System views are written last, so that an interrupted import does not leave
+    // metadata for indexes which were not imported.
+    for &name in SYSTEM_VIEW_NAMES {
+        let mut iter = snapshot.iter(&ResolvedAddress::system(name), &[]);
+        while let Some((key, value)) = iter.next() {
+            write_record(&mut writer, name, key, value)?;
+        }
+    }
+
+    write_field(&mut writer, &[])?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_record(writer: &mut impl Write, name: &str, key: &[u8], value: &[u8]) -> Result<()> {
+    write_field(writer, name.as_bytes())?;
+    write_field(writer, key)?;
+    write_field(writer, value)
+}
+
+fn write_field(writer: &mut impl Write, bytes: &[u8]) -> Result<()> {
+    let len = bytes.len().try_into().map_err(|_| {
+        Error::new(format!(
+            "Field is too long to export: {} bytes",
+            bytes.len()
+        ))
+    })?;
+    writer.write_u32::<LittleEndian>(len)?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+pub(crate) fn import(db: &dyn Database, reader: impl Read) -> Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut magic = [0_u8; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic[..] != *MAGIC {
+        return Err(invalid_stream("unknown header"));
+    }
+    let version = reader.read_u8()?;
+    if version != FORMAT_VERSION {
+        return Err(invalid_stream(format!("unsupported version {}", version)));
+    }
+
+    let pool_address = ResolvedAddress::system(INDEXES_POOL_NAME);
+    if db.snapshot().iter(&pool_address, &[]).next().is_some() {
+        return Err(Error::new("Cannot import into a non-empty database"));
+    }
+
+    let mut changes: HashMap<ResolvedAddress, ViewChanges> = HashMap::new();
+    let mut pending_bytes = 0;
+    loop {
+        let name = read_field(&mut reader)?;
+        if name.is_empty() {
+            break;
+        }
+        let name = String::from_utf8(name)
+            .map_err(|_| invalid_stream("column family name is not UTF-8"))?;
+        let mut key = read_field(&mut reader)?;
+        let value = read_field(&mut reader)?;
+
+        let address = if SYSTEM_VIEW_NAMES.contains(&name.as_str()) {
+            ResolvedAddress::system(name)
+        } else {
+            if key.len() < ID_SIZE {
+                return Err(invalid_stream("index key does not contain identifier"));
+            }
+            let id = u64::from_le_bytes(key[..ID_SIZE].try_into().unwrap());
+            let id = NonZeroU64::new(id).ok_or_else(|| invalid_stream("index identifier is 0"))?;
+            key.drain(..ID_SIZE);
+            ResolvedAddress::new(name, Some(id))
+        };
+
+        pending_bytes += key.len() + value.len();
+        changes
+            .entry(address)
+            .or_default()
+            .data
+            .insert(key, Change::Put(value));
+        if pending_bytes >= IMPORT_BATCH_BYTES {
+            db.merge(Patch::with_changes(db.snapshot(), changes))?;
+            changes = HashMap::new();
+            pending_bytes = 0;
+        }
+    }
+
+    if !changes.is_empty() {
+        db.merge(Patch::with_changes(db.snapshot(), changes))?;
+    }
+    Ok(())
+}
+
+fn read_field(reader: &mut impl Read) -> Result<Vec<u8>> {
+    let len = u64::from(reader.read_u32::<LittleEndian>()?);
+    let mut bytes = vec![];
+    // Reading through `take` avoids allocating a huge buffer for a corrupted length.
+    reader.by_ref().take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(bytes)
+}
+
+fn invalid_stream(message: impl AsRef<str>) -> Error {
+    Error::with_kind(
+        ErrorKind::Corruption,
+        format!("Invalid export stream: {}", message.as_ref()),
+    )
+}
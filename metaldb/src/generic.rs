@@ -8,10 +8,15 @@
 //!   or `Migration`)
 //! - [`ErasedAccess`], which combines the previous two types and thus is the most abstract kind
 //!   of access to the database.
+//! - [`check_schema_version`], a helper to enforce compatibility of the application data
+//!   with the database contents.
+//! - [`open_dynamic`], which opens an index without knowing its type at compile time.
 //!
 //! [`GenericRawAccess`]: enum.GenericRawAccess.html
 //! [`GenericAccess`]: enum.GenericAccess.html
 //! [`ErasedAccess`]: type.ErasedAccess.html
+//! [`check_schema_version`]: fn.check_schema_version.html
+//! [`open_dynamic`]: fn.open_dynamic.html
 //!
 //! # Examples
 //!
@@ -85,14 +90,18 @@
 use std::rc::Rc;
 
 use crate::{
-    access::{Access, AccessError, AsReadonly, Prefixed},
+    access::{Access, AccessError, AccessErrorKind, AsReadonly, FromAccess, Prefixed},
     db::{ChangesMut, ChangesRef, ViewChanges},
     migration::{Migration, Scratchpad},
-    views::{ChangeSet, GroupKeys, IndexMetadata, RawAccess, RawAccessMut, ViewWithMetadata},
-    BinaryKey, Fork, IndexAddress, IndexType, OwnedReadonlyFork, ReadonlyFork, ResolvedAddress,
-    Snapshot,
+    views::{ChangeSet, GroupKeys, IndexMetadata, RawAccess, RawAccessMut, View, ViewWithMetadata},
+    BinaryKey, Entry, Error, Fork, IndexAddress, IndexType, KeySetIndex, ListIndex, MapIndex,
+    OwnedReadonlyFork, ReadonlyFork, ResolvedAddress, Snapshot, SparseListIndex,
 };
 
+/// Name of the system view storing the application schema version. Names starting with `__`
+/// and not containing a dot are reserved, so this view cannot collide with user indexes.
+pub(crate) const SCHEMA_VERSION_NAME: &str = "__schema_version__";
+
 /// Container for an arbitrary raw access. For `Fork`s and `Snapshot`s, this type provides
 /// both owned and borrowed variants.
 ///
@@ -408,14 +417,195 @@ where
     }
 }
 
+/// Checks the application schema version stored in the database.
+///
+/// The version is stored in a reserved system view, which cannot be accessed via ordinary
+/// indexes. If the version is absent and the `access` is mutable (e.g., a `&Fork`),
+/// `expected` is written as the current version. If the version is present, it is compared
+/// to `expected`, and an error is returned on mismatch. An error is also returned if
+/// the version is absent and the `access` is immutable.
+///
+/// # Examples
+///
+/// ```
+/// use metaldb::{generic::check_schema_version, Database, TemporaryDB};
+///
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// // The version is absent, so it will be written to the fork.
+/// check_schema_version(&fork, 1).unwrap();
+/// db.merge(fork.into_patch()).unwrap();
+///
+/// let snapshot = db.snapshot();
+/// check_schema_version(snapshot.as_ref(), 1).unwrap();
+/// assert!(check_schema_version(snapshot.as_ref(), 2).is_err());
+/// ```
+pub fn check_schema_version<'a>(
+    access: impl Into<GenericRawAccess<'a>>,
+    expected: u32,
+) -> crate::Result<()> {
+    let access = access.into();
+    let is_mutable = access.is_mutable();
+    let mut view = View::new(access, ResolvedAddress::system(SCHEMA_VERSION_NAME));
+
+    match view.get::<_, u32>(&()) {
+        Some(actual) if actual == expected => Ok(()),
+        Some(actual) => Err(Error::new(format!(
+            "Schema version doesn't match: actual {}, expected {}",
+            actual, expected
+        ))),
+        None if is_mutable => {
+            view.put(&(), expected);
+            Ok(())
+        }
+        None => Err(Error::new(format!(
+            "Schema version is not set, expected {}",
+            expected
+        ))),
+    }
+}
+
+/// Index with the type determined at run time from the index metadata. Keys and values
+/// of the index are represented as raw bytes.
+///
+/// `DynIndex` is created with the [`open_dynamic`] function.
+///
+/// [`open_dynamic`]: fn.open_dynamic.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DynIndex<T: RawAccess> {
+    /// Entry index.
+    Entry(Entry<T, Vec<u8>>),
+    /// List index.
+    List(ListIndex<T, Vec<u8>>),
+    /// Sparse list index.
+    SparseList(SparseListIndex<T, Vec<u8>>),
+    /// Map index.
+    Map(MapIndex<T, [u8], Vec<u8>>),
+    /// Key set index.
+    KeySet(KeySetIndex<T, [u8]>),
+}
+
+impl<T: RawAccess> DynIndex<T> {
+    /// Returns the type of the index.
+    pub fn index_type(&self) -> IndexType {
+        match self {
+            Self::Entry(_) => IndexType::Entry,
+            Self::List(_) => IndexType::List,
+            Self::SparseList(_) => IndexType::SparseList,
+            Self::Map(_) => IndexType::Map,
+            Self::KeySet(_) => IndexType::KeySet,
+        }
+    }
+
+    /// Returns the number of elements in the index. For an entry, this is 1 if the entry
+    /// has a value and 0 otherwise.
+    ///
+    /// The operation takes linear time for maps and key sets.
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::Entry(entry) => u64::from(entry.exists()),
+            Self::List(list) => list.len(),
+            Self::SparseList(list) => list.len(),
+            Self::Map(map) => map.keys().count() as u64,
+            Self::KeySet(set) => set.iter().count() as u64,
+        }
+    }
+
+    /// Checks if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over raw keys and values of the index in ascending key order.
+    /// Keys are serialized in the same way as in the corresponding typed index (e.g.,
+    /// as big-endian `u64`s for lists); the entry key is empty. Values of key sets are empty.
+    pub fn iter_bytes(&self) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        match self {
+            Self::Entry(entry) => Box::new(entry.get().into_iter().map(|value| (vec![], value))),
+            Self::List(list) => Box::new(
+                (0_u64..)
+                    .zip(list.iter())
+                    .map(|(i, value)| (i.to_be_bytes().to_vec(), value)),
+            ),
+            Self::SparseList(list) => Box::new(
+                list.iter()
+                    .map(|(i, value)| (i.to_be_bytes().to_vec(), value)),
+            ),
+            Self::Map(map) => Box::new(map.iter()),
+            Self::KeySet(set) => Box::new(set.iter().map(|key| (key, vec![]))),
+        }
+    }
+}
+
+/// Opens an index at the specified address with the type determined from the index metadata.
+/// This allows tools to operate on indexes without compile-time type knowledge.
+///
+/// # Errors
+///
+/// Returns an error if there is no index at the specified address, or if the index type
+/// cannot be represented by [`DynIndex`] (e.g., for tombstones in migrations).
+///
+/// [`DynIndex`]: enum.DynIndex.html
+///
+/// # Examples
+///
+/// ```
+/// use metaldb::{access::CopyAccessExt, generic::{open_dynamic, DynIndex}, Database, IndexType, TemporaryDB};
+///
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// fork.get_list("list").extend(vec![1_u8, 2]);
+///
+/// let index = open_dynamic(&fork, "list").unwrap();
+/// assert_eq!(index.index_type(), IndexType::List);
+/// assert_eq!(index.len(), 2);
+/// let entries: Vec<_> = index.iter_bytes().collect();
+/// assert_eq!(entries[1], (1_u64.to_be_bytes().to_vec(), vec![2]));
+///
+/// assert!(open_dynamic(&fork, "other").is_err());
+/// ```
+pub fn open_dynamic<T, I>(access: T, addr: I) -> Result<DynIndex<T::Base>, AccessError>
+where
+    T: Access,
+    I: Into<IndexAddress>,
+{
+    let addr = addr.into();
+    let metadata = access.clone().get_index_metadata(addr.clone())?;
+    let index_type = if let Some(metadata) = metadata {
+        metadata.index_type()
+    } else {
+        return Err(AccessError {
+            addr,
+            kind: AccessErrorKind::Custom(anyhow::anyhow!("Index is not initialized")),
+        });
+    };
+
+    match index_type {
+        IndexType::Entry => FromAccess::from_access(access, addr).map(DynIndex::Entry),
+        IndexType::List => FromAccess::from_access(access, addr).map(DynIndex::List),
+        IndexType::SparseList => FromAccess::from_access(access, addr).map(DynIndex::SparseList),
+        IndexType::Map => FromAccess::from_access(access, addr).map(DynIndex::Map),
+        IndexType::KeySet => FromAccess::from_access(access, addr).map(DynIndex::KeySet),
+        other => Err(AccessError {
+            addr,
+            kind: AccessErrorKind::Custom(anyhow::anyhow!(
+                "Index type {:?} is not supported",
+                other
+            )),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        AsReadonly, GenericRawAccess, IntoErased, Migration, Prefixed, Rc, Scratchpad, Snapshot,
+        check_schema_version, open_dynamic, AsReadonly, DynIndex, GenericRawAccess, IntoErased,
+        Migration, Prefixed, Rc, Scratchpad, Snapshot,
     };
     use crate::{
-        access::{AccessExt, CopyAccessExt},
-        Database, TemporaryDB,
+        access::{AccessExt, CopyAccessExt, FromAccess},
+        Database, Entry, TemporaryDB,
     };
 
     #[test]
@@ -594,4 +784,81 @@ mod tests {
         assert!(!access.is_mutable());
         assert_eq!(erased.get_entry::<_, u32>("iter_position").get(), Some(123));
     }
+
+    #[test]
+    fn schema_version_check() {
+        let db = TemporaryDB::new();
+        let snapshot = db.snapshot();
+        let err = check_schema_version(snapshot.as_ref(), 1).unwrap_err();
+        assert!(err.to_string().contains("not set"));
+
+        let fork = db.fork();
+        check_schema_version(&fork, 3).unwrap();
+        check_schema_version(fork.readonly(), 3).unwrap();
+        let err = check_schema_version(&fork, 4).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Schema version doesn't match: actual 3, expected 4"));
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        check_schema_version(snapshot.as_ref(), 3).unwrap();
+        assert!(check_schema_version(snapshot.as_ref(), 2).is_err());
+        // The reserved name is not accessible via ordinary indexes.
+        let res = Entry::<_, u32>::from_access(snapshot.as_ref(), "__schema_version__".into());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn opening_dynamic_indexes() {
+        use crate::IndexType;
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(1_u8);
+        fork.get_list("list").extend(vec![1_u8, 2]);
+        fork.get_sparse_list("sparse").set(3, 4_u8);
+        fork.get_map("map").put("foo", 5_u8);
+        fork.get_key_set("set").insert(&6_u8);
+        fork.get_entry::<_, u8>("empty_entry");
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let check = |name: &str, index_type: IndexType, expected: Vec<(Vec<u8>, Vec<u8>)>| {
+            let index = open_dynamic(snapshot.as_ref(), name).unwrap();
+            assert_eq!(index.index_type(), index_type);
+            assert_eq!(index.len(), expected.len() as u64);
+            assert_eq!(index.iter_bytes().collect::<Vec<_>>(), expected);
+        };
+        check("entry", IndexType::Entry, vec![(vec![], vec![1])]);
+        check(
+            "list",
+            IndexType::List,
+            vec![
+                (0_u64.to_be_bytes().to_vec(), vec![1]),
+                (1_u64.to_be_bytes().to_vec(), vec![2]),
+            ],
+        );
+        check(
+            "sparse",
+            IndexType::SparseList,
+            vec![(3_u64.to_be_bytes().to_vec(), vec![4])],
+        );
+        check("map", IndexType::Map, vec![(b"foo".to_vec(), vec![5])]);
+        check("set", IndexType::KeySet, vec![(vec![6], vec![])]);
+        check("empty_entry", IndexType::Entry, vec![]);
+
+        // Uninitialized indexes cannot be opened.
+        let err = open_dynamic(snapshot.as_ref(), "other").unwrap_err();
+        assert!(err.to_string().contains("not initialized"), "{}", err);
+
+        // Dynamic indexes can be used with mutable accesses, too.
+        let fork = db.fork();
+        if let DynIndex::List(mut list) = open_dynamic(&fork, "list").unwrap() {
+            list.push(vec![3]);
+        } else {
+            panic!("Unexpected index type");
+        }
+        assert_eq!(fork.get_list::<_, u8>("list").len(), 3);
+    }
 }
@@ -65,6 +65,9 @@ where
 
     /// Returns `true` if a value of the entry exists.
     ///
+    /// This method only checks whether the value is present; unlike `get().is_some()`,
+    /// it neither decodes the value nor copies it from the database.
+    ///
     /// # Examples
     ///
     /// ```
@@ -81,6 +84,35 @@ where
     pub fn exists(&self) -> bool {
         self.base.contains(&())
     }
+
+    /// Returns `true` if the value of the entry was set or removed in the fork the entry
+    /// is based on, including by clearing the entry or via other instances of the same entry.
+    /// Setting the entry to its current value also marks it as dirty.
+    ///
+    /// Only the changes made since the fork was created or last [flushed] are considered;
+    /// entries based on snapshots are never dirty.
+    ///
+    /// [flushed]: ../struct.Fork.html#method.flush
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, Entry};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_entry("name").set(1_u32);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let fork = db.fork();
+    /// let mut index = fork.get_entry::<_, u32>("name");
+    /// assert!(!index.is_dirty());
+    /// index.set(1);
+    /// assert!(index.is_dirty());
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        self.base.is_changed(&())
+    }
 }
 
 impl<T, V> Entry<T, V>
@@ -154,6 +186,9 @@ where
     }
 
     /// Replaces the value in the entry with the given one, returning the previously stored value.
+    /// If the entry is empty, returns `None`. This is analogous to [`Option::replace`].
+    ///
+    /// [`Option::replace`]: https://doc.rust-lang.org/std/option/enum.Option.html#method.replace
     ///
     /// # Examples
     ///
@@ -176,4 +211,246 @@ where
         self.set(value);
         previous
     }
+
+    /// Sets the value of the entry to `new` if the current value is equal to `expected`.
+    /// `None` as `expected` means that the entry must be empty. Returns `true` if the value
+    /// has been written. Values are compared in their serialized form.
+    ///
+    /// # Isolation
+    ///
+    /// The comparison is performed against the state of the fork, which is isolated
+    /// from changes merged into the database after the fork was created. Thus, the check
+    /// alone does not protect against concurrent writers. To make the compare-and-set atomic
+    /// with respect to the database, create the fork with [`Database::fork_tracking`]
+    /// and merge it with [`Database::merge_checked`]: the merge fails with a conflict
+    /// without applying any changes if the entry (or any other value read via the fork)
+    /// has changed in the database since it was read.
+    ///
+    /// [`Database::fork_tracking`]: ../trait.Database.html#method.fork_tracking
+    /// [`Database::merge_checked`]: ../trait.Database.html#method.merge_checked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MergeError};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let forks = vec![db.fork_tracking(), db.fork_tracking()];
+    /// // Both forks try to acquire a lock.
+    /// for (i, fork) in forks.iter().enumerate() {
+    ///     let mut lock = fork.get_entry("lock");
+    ///     assert!(lock.compare_and_set(None, i as u64));
+    ///     assert!(!lock.compare_and_set(None, 100));
+    /// }
+    ///
+    /// let mut results = forks.into_iter().map(|fork| {
+    ///     let read_set = fork.read_set();
+    ///     db.merge_checked(fork.into_patch(), read_set)
+    /// });
+    /// assert!(results.next().unwrap().is_ok());
+    /// // The lock has been acquired by the first fork in the meantime.
+    /// assert!(matches!(results.next().unwrap(), Err(MergeError::Conflict(_))));
+    /// assert_eq!(db.snapshot().get_entry::<_, u64>("lock").get(), Some(0));
+    /// ```
+    pub fn compare_and_set(&mut self, expected: Option<&V>, new: V) -> bool {
+        let current = self.base.get_bytes(&());
+        let expected = expected.map(BinaryValue::to_bytes);
+        if current == expected {
+            self.set(new);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Exchanges values of this entry and `other`. If one of the entries is empty,
+    /// the other one becomes empty after the exchange.
+    ///
+    /// Values are exchanged in their serialized form, without being deserialized.
+    /// If the entries contain equal values (or are both empty), no changes are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, Entry};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut active = fork.get_entry("active");
+    /// let mut standby = fork.get_entry("standby");
+    /// active.set("config_a".to_owned());
+    /// standby.set("config_b".to_owned());
+    ///
+    /// active.swap_with(&mut standby);
+    /// assert_eq!(active.get().unwrap(), "config_b");
+    /// assert_eq!(standby.get().unwrap(), "config_a");
+    ///
+    /// standby.remove();
+    /// active.swap_with(&mut standby);
+    /// assert!(!active.exists());
+    /// assert_eq!(standby.get().unwrap(), "config_b");
+    /// ```
+    pub fn swap_with(&mut self, other: &mut Self) {
+        let this_bytes = self.base.get_bytes(&());
+        let other_bytes = other.base.get_bytes(&());
+        if this_bytes == other_bytes {
+            return;
+        }
+        Self::set_bytes(&mut self.base, other_bytes);
+        Self::set_bytes(&mut other.base, this_bytes);
+    }
+
+    fn set_bytes(base: &mut View<T>, bytes: Option<Vec<u8>>) {
+        if let Some(bytes) = bytes {
+            base.put(&(), bytes);
+        } else {
+            base.remove(&());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{access::CopyAccessExt, Database, TemporaryDB};
+
+    #[test]
+    fn swapping_value() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut entry = fork.get_entry::<_, u32>("entry");
+        assert_eq!(entry.swap(1), None);
+        assert_eq!(entry.get(), Some(1));
+        assert_eq!(entry.swap(2), Some(1));
+        assert_eq!(entry.get(), Some(2));
+        assert_eq!(entry.take(), Some(2));
+        assert_eq!(entry.swap(3), None);
+        assert_eq!(entry.get(), Some(3));
+    }
+
+    #[test]
+    fn compare_and_set() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut entry = fork.get_entry::<_, String>("entry");
+        assert!(!entry.compare_and_set(Some(&"foo".to_owned()), "bar".to_owned()));
+        assert!(!entry.exists());
+        assert!(entry.compare_and_set(None, "foo".to_owned()));
+        assert!(!entry.compare_and_set(None, "bar".to_owned()));
+        assert!(entry.compare_and_set(Some(&"foo".to_owned()), "bar".to_owned()));
+        assert_eq!(entry.get().unwrap(), "bar");
+        drop(entry);
+        db.merge(fork.into_patch()).unwrap();
+
+        // The value is compared against the committed state.
+        let fork = db.fork_tracking();
+        let mut entry = fork.get_entry::<_, String>("entry");
+        assert!(entry.compare_and_set(Some(&"bar".to_owned()), "baz".to_owned()));
+        drop(entry);
+        let other_fork = db.fork();
+        other_fork.get_entry("entry").set("qux".to_owned());
+        db.merge(other_fork.into_patch()).unwrap();
+        let read_set = fork.read_set();
+        assert!(db.merge_checked(fork.into_patch(), read_set).is_err());
+        let snapshot = db.snapshot();
+        assert_eq!(
+            snapshot.get_entry::<_, String>("entry").get().unwrap(),
+            "qux"
+        );
+    }
+
+    #[test]
+    fn swapping_entries() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut first = fork.get_entry::<_, u32>("first");
+        let mut second = fork.get_entry::<_, u32>("second");
+
+        // Both entries are empty.
+        first.swap_with(&mut second);
+        assert!(!first.exists());
+        assert!(!second.exists());
+
+        // One of entries is empty.
+        first.set(1);
+        first.swap_with(&mut second);
+        assert!(!first.exists());
+        assert_eq!(second.get(), Some(1));
+        first.swap_with(&mut second);
+        assert_eq!(first.get(), Some(1));
+        assert!(!second.exists());
+
+        // Both entries are present.
+        second.set(2);
+        first.swap_with(&mut second);
+        assert_eq!(first.get(), Some(2));
+        assert_eq!(second.get(), Some(1));
+
+        drop((first, second));
+        let stats = fork.pending_changes();
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_entry::<_, u32>("first").get(), Some(2));
+        assert_eq!(snapshot.get_entry::<_, u32>("second").get(), Some(1));
+        assert_eq!(stats.delete_count, 0);
+    }
+
+    #[test]
+    fn swapping_entries_produces_minimal_writes() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("first").set(1_u32);
+        fork.get_entry("second").set(1_u32);
+        // Create empty entries so that their metadata is not counted below.
+        fork.get_entry::<_, u32>("empty");
+        fork.get_entry::<_, u32>("other_empty");
+        let stats = fork.pending_changes();
+
+        {
+            let mut first = fork.get_entry::<_, u32>("first");
+            let mut second = fork.get_entry::<_, u32>("second");
+            first.swap_with(&mut second);
+            let mut empty = fork.get_entry::<_, u32>("empty");
+            let mut other_empty = fork.get_entry::<_, u32>("other_empty");
+            empty.swap_with(&mut other_empty);
+        }
+        assert_eq!(fork.pending_changes(), stats);
+
+        {
+            let mut first = fork.get_entry::<_, u32>("first");
+            let mut empty = fork.get_entry::<_, u32>("empty");
+            first.swap_with(&mut empty);
+        }
+        let new_stats = fork.pending_changes();
+        assert_eq!(new_stats.put_count, stats.put_count);
+        assert_eq!(new_stats.delete_count, stats.delete_count + 1);
+    }
+
+    #[test]
+    fn detecting_dirty_entries() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("entry").set(1_u32);
+        fork.get_entry("other").set(2_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut fork = db.fork();
+        {
+            let entry = fork.get_entry::<_, u32>("entry");
+            assert_eq!(entry.get(), Some(1));
+            assert!(!entry.is_dirty());
+        }
+        fork.get_entry::<_, u32>("entry").remove();
+        {
+            // Changes made via other instances of the entry are taken into account.
+            let entry = fork.get_entry::<_, u32>("entry");
+            assert!(entry.is_dirty());
+            assert!(!fork.get_entry::<_, u32>("other").is_dirty());
+        }
+        assert!(fork.readonly().get_entry::<_, u32>("entry").is_dirty());
+
+        fork.flush();
+        assert!(!fork.get_entry::<_, u32>("entry").is_dirty());
+        db.merge(fork.into_patch()).unwrap();
+        assert!(!db.snapshot().get_entry::<_, u32>("other").is_dirty());
+    }
 }
@@ -1,9 +1,9 @@
-use std::marker::PhantomData;
+use std::{borrow::Borrow, marker::PhantomData, vec};
 
 use crate::{
     access::{Access, AccessError, FromAccess},
-    views::{AsReadonly, GroupKeys, IndexAddress},
-    BinaryKey,
+    views::{AsReadonly, GroupKeys, IndexAddress, IndexesPool, RawAccess, RawAccessMut, View},
+    BinaryKey, BinaryValue, MapIndex,
 };
 
 // cspell:ignore foob
@@ -108,6 +108,35 @@ where
         I::from_access(self.access.clone(), addr)
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
     }
+
+    /// Returns `true` if the group contains an index with the specified key.
+    ///
+    /// Unlike [`get`](#method.get), this method does not create the index; it only
+    /// looks up the index metadata. Thus, `contains` is usable with any access.
+    ///
+    /// # Panics
+    ///
+    /// If the index address is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, Group, ListIndex, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let group: Group<_, u64, ListIndex<_, u64>> = fork.get_group("group");
+    /// group.get(&1).push(1);
+    /// assert!(group.contains(&1));
+    /// assert!(!group.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        let addr = self.prefix.clone().append_key(key);
+        self.access
+            .clone()
+            .get_index_metadata(addr)
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+            .is_some()
+    }
 }
 
 impl<T, K, I> Group<T, K, I>
@@ -136,6 +165,179 @@ where
     pub fn keys(&self) -> GroupKeys<T::Base, K> {
         self.access.clone().group_keys(self.prefix.clone())
     }
+
+    /// Returns the number of indexes in this group.
+    ///
+    /// This method iterates over all keys in the group, so it takes time linear
+    /// in the number of indexes. The same caveats as for [`keys`](#method.keys) apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, Group, ListIndex, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let group: Group<_, u64, ListIndex<_, u64>> = fork.get_group("group");
+    /// group.get(&1).push(1);
+    /// group.get(&5).push(2);
+    /// let group: Group<_, u64, ListIndex<_, u64>> = fork.readonly().get_group("group");
+    /// assert_eq!(group.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.keys().count()
+    }
+
+    /// Returns `true` if the group contains no indexes.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+}
+
+impl<T, K, I> Group<T, K, I>
+where
+    T: RawAccessMut,
+    K: BinaryKey + ?Sized,
+{
+    /// Removes all indexes in the group together with their metadata. Indexes outside
+    /// the group are not affected, even if their names start with the name of the group.
+    ///
+    /// The method is only available for groups created directly from a raw access
+    /// (e.g., `&Fork`), rather than from a `Prefixed` access or a migration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the removed indexes is borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, Group, ListIndex, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut group: Group<_, u64, ListIndex<_, u64>> = fork.get_group("group");
+    /// group.get(&1).push(1);
+    /// group.get(&2).extend(vec![1, 2, 3]);
+    /// fork.get_list("group_list").push(1_u64);
+    ///
+    /// group.clear();
+    /// assert!(!group.contains(&1));
+    /// assert_eq!(fork.get_list::<_, u64>(("group", &2_u64)).len(), 0);
+    /// assert_eq!(fork.get_list::<_, u64>("group_list").len(), 1);
+    /// ```
+    pub fn clear(&mut self) {
+        let removed = IndexesPool::new(self.access.clone()).remove_group(&self.prefix);
+        for resolved_addr in removed {
+            View::new(self.access.clone(), resolved_addr).clear();
+        }
+    }
+}
+
+impl<T, K, K2, V> Group<T, K, MapIndex<T::Base, K2, V>>
+where
+    T: Access,
+    T::Base: AsReadonly<Readonly = T::Base>,
+    K: BinaryKey + ?Sized,
+    K::Owned: Clone,
+    K2: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    /// Iterates over entries of all maps in this group, yielding group keys together with
+    /// keys and values of the maps. Entries are ordered by the group key first and then by
+    /// the key in the map.
+    ///
+    /// Each map in the group is stored as a separate index, so group keys of any length
+    /// (including variable-length ones, such as strings) are unambiguously separated from
+    /// the keys in the maps. Map entries are read in chunks, so the memory consumption
+    /// does not depend on the size of the maps. The same caveats as for [`keys`](#method.keys)
+    /// apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, Group, MapIndex, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let group: Group<_, str, MapIndex<_, u8, u64>> = fork.get_group("balances");
+    /// group.get("bob").put(&2, 20);
+    /// group.get("alice").put(&1, 10);
+    /// group.get("alice").put(&3, 30);
+    ///
+    /// let group: Group<_, str, MapIndex<_, u8, u64>> = fork.readonly().get_group("balances");
+    /// let entries: Vec<_> = group.flat_iter().collect();
+    /// assert_eq!(
+    ///     entries,
+    ///     vec![
+    ///         ("alice".to_owned(), 1, 10),
+    ///         ("alice".to_owned(), 3, 30),
+    ///         ("bob".to_owned(), 2, 20),
+    ///     ]
+    /// );
+    /// ```
+    pub fn flat_iter(&self) -> impl Iterator<Item = (K::Owned, K2::Owned, V)> + '_ {
+        self.keys().flat_map(move |key| {
+            let map = self.get(key.borrow());
+            MapChunks::new(map).map(move |(map_key, value)| (key.clone(), map_key, value))
+        })
+    }
+}
+
+/// Iterator over the entries of an owned `MapIndex`, which reads entries in chunks.
+struct MapChunks<T: RawAccess, K: BinaryKey + ?Sized, V> {
+    map: MapIndex<T, K, V>,
+    buffer: vec::IntoIter<(K::Owned, V)>,
+    next_key: Option<K::Owned>,
+    is_first_chunk: bool,
+}
+
+impl<T, K, V> MapChunks<T, K, V>
+where
+    T: RawAccess,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    const CHUNK_SIZE: usize = 1_000;
+
+    fn new(map: MapIndex<T, K, V>) -> Self {
+        Self {
+            map,
+            buffer: Vec::new().into_iter(),
+            next_key: None,
+            is_first_chunk: true,
+        }
+    }
+
+    fn fill_buffer(&mut self) {
+        let iter = match self.next_key.take() {
+            Some(key) => self.map.iter_from(key.borrow()),
+            None if self.is_first_chunk => self.map.iter(),
+            None => return,
+        };
+        self.is_first_chunk = false;
+
+        let mut chunk: Vec<_> = iter.take(Self::CHUNK_SIZE + 1).collect();
+        if chunk.len() > Self::CHUNK_SIZE {
+            // The last entry is read again as the first entry of the next chunk.
+            self.next_key = chunk.pop().map(|(key, _)| key);
+        }
+        self.buffer = chunk.into_iter();
+    }
+}
+
+impl<T, K, V> Iterator for MapChunks<T, K, V>
+where
+    T: RawAccess,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    type Item = (K::Owned, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry) = self.buffer.next() {
+            return Some(entry);
+        }
+        self.fill_buffer();
+        self.buffer.next()
+    }
 }
 
 #[cfg(test)]
@@ -144,9 +346,52 @@ mod tests {
     use crate::{
         access::{AccessExt, CopyAccessExt, Prefixed, RawAccessMut},
         migration::{Migration, Scratchpad},
-        Database, ListIndex, TemporaryDB,
+        Database, ListIndex, MapIndex, TemporaryDB,
     };
 
+    #[test]
+    fn clearing_group() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        {
+            let group: Group<_, str, ListIndex<_, u32>> = fork.get_group("g");
+            group.get("foo").extend(vec![1, 2, 3]);
+            group.get("bar").push(4);
+            let neighbor: Group<_, str, ListIndex<_, u32>> = fork.get_group("g2");
+            neighbor.get("foo").push(5);
+        }
+        fork.get_list("g").push(6_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        fork.get_list(("g", "baz")).push(7_u32);
+        let mut group: Group<_, str, ListIndex<_, u32>> = fork.get_group("g");
+        group.clear();
+        assert!(!group.contains("foo"));
+        assert!(!group.contains("baz"));
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let group: Group<_, str, ListIndex<_, u32>> = snapshot.get_group("g");
+        assert!(group.is_empty());
+        assert_eq!(snapshot.index_type(("g", "foo")), None);
+        assert_eq!(snapshot.get_list::<_, u32>(("g", "foo")).len(), 0);
+        // Indexes outside the group are not affected.
+        let neighbor: Group<_, str, ListIndex<_, u32>> = snapshot.get_group("g2");
+        assert_eq!(neighbor.keys().collect::<Vec<_>>(), vec!["foo".to_owned()]);
+        assert_eq!(
+            snapshot
+                .get_list::<_, u32>(("g2", "foo"))
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![5]
+        );
+        assert_eq!(
+            snapshot.get_list::<_, u32>("g").iter().collect::<Vec<_>>(),
+            vec![6]
+        );
+    }
+
     #[test]
     fn group() {
         let db = TemporaryDB::new();
@@ -221,8 +466,16 @@ mod tests {
         );
 
         let group: Group<_, u32, ListIndex<_, String>> =
-            Group::from_access(snapshot, ("prefixed", &0_u8).into()).unwrap();
+            Group::from_access(snapshot.clone(), ("prefixed", &0_u8).into()).unwrap();
         assert_eq!(group.keys().collect::<Vec<_>>(), vec![1, 2, 5, 100_000]);
+        assert_eq!(group.len(), 4);
+        assert!(group.contains(&5));
+        assert!(!group.contains(&3));
+        assert!(!group.contains(&42));
+
+        let group: Group<_, str, ListIndex<_, String>> = snapshot.get_group("grou");
+        assert!(group.is_empty());
+        assert!(!group.contains("p"));
     }
 
     #[test]
@@ -270,4 +523,44 @@ mod tests {
         db.merge(patch).unwrap();
         test_key_iter(Scratchpad::new("namespace", &db.snapshot()));
     }
+
+    #[test]
+    fn flat_iter_over_maps() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let group: Group<_, str, MapIndex<_, str, u32>> = fork.get_group("group");
+        // Variable-length keys, where a group key is a prefix of another one.
+        group.get("a").put("bc", 1);
+        group.get("ab").put("c", 2);
+        group.get("ab").put("", 3);
+        let mut big_map = group.get("big");
+        for i in 0..2_500_u32 {
+            big_map.put(&format!("{:05}", i), i);
+        }
+        drop(big_map);
+        // Unrelated index with a similar name.
+        fork.get_map(("group_", "a")).put("x", 5_u32);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let group: Group<_, str, MapIndex<_, str, u32>> = snapshot.get_group("group");
+        let entries: Vec<_> = group.flat_iter().collect();
+        assert_eq!(entries.len(), 2_503);
+        assert_eq!(
+            entries[..3],
+            [
+                ("a".to_owned(), "bc".to_owned(), 1),
+                ("ab".to_owned(), String::new(), 3),
+                ("ab".to_owned(), "c".to_owned(), 2),
+            ]
+        );
+        for (i, (group_key, key, value)) in entries[3..].iter().enumerate() {
+            assert_eq!(group_key, "big");
+            assert_eq!(*key, format!("{:05}", i));
+            assert_eq!(*value as usize, i);
+        }
+
+        let group: Group<_, str, MapIndex<_, str, u32>> = snapshot.get_group("other");
+        assert_eq!(group.flat_iter().count(), 0);
+    }
 }
@@ -10,7 +10,51 @@ use crate::{
 /// This structure is returned by the [`IndexIterator`] trait and by inherent methods
 /// of some indexes.
 ///
+/// Since `Entries` is a standard iterator, it can be wrapped into [`Peekable`] with
+/// the [`peekable()`] method in order to look at the next entry without consuming it. This is
+/// useful for algorithms processing several indexes in lockstep, such as merge joins.
+///
 /// [`IndexIterator`]: trait.IndexIterator.html
+/// [`Peekable`]: https://doc.rust-lang.org/std/iter/struct.Peekable.html
+/// [`peekable()`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.peekable
+///
+/// # Examples
+///
+/// Inner join of two maps with the same key type:
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+/// # use std::cmp::Ordering;
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// let mut names = fork.get_map("names");
+/// names.put(&1_u64, "Alice".to_owned());
+/// names.put(&2_u64, "Bob".to_owned());
+/// names.put(&4_u64, "Carol".to_owned());
+/// let mut balances = fork.get_map("balances");
+/// balances.put(&2_u64, 100_u64);
+/// balances.put(&3_u64, 50_u64);
+/// balances.put(&4_u64, 10_u64);
+///
+/// let mut left = names.iter().peekable();
+/// let mut right = balances.iter().peekable();
+/// let mut joined = vec![];
+/// while let (Some((left_key, _)), Some((right_key, _))) = (left.peek(), right.peek()) {
+///     match left_key.cmp(right_key) {
+///         Ordering::Less => { left.next(); }
+///         Ordering::Greater => { right.next(); }
+///         Ordering::Equal => {
+///             let (key, name) = left.next().unwrap();
+///             let (_, balance) = right.next().unwrap();
+///             joined.push((key, name, balance));
+///         }
+///     }
+/// }
+/// assert_eq!(
+///     joined,
+///     vec![(2, "Bob".to_owned(), 100), (4, "Carol".to_owned(), 10)]
+/// );
+/// ```
 #[derive(Debug)]
 pub struct Entries<'a, K: ?Sized, V> {
     base_iter: Iter<'a, K, V>,
@@ -31,12 +75,45 @@ where
     pub(crate) fn with_prefix<T, P>(view: &'a View<T>, prefix: &P, from: Option<&K>) -> Self
     where
         T: RawAccess,
-        P: BinaryKey,
+        P: BinaryKey + ?Sized,
     {
         let base_iter = from.map_or_else(|| view.iter(prefix), |from| view.iter_from(prefix, from));
         Self { base_iter }
     }
 
+    /// Creates a new iterator over raw keys in the `[start, end)` range. If `end` is `None`,
+    /// the range is unbounded from above.
+    pub(crate) fn with_range<T: RawAccess>(
+        view: &'a View<T>,
+        start: &[u8],
+        end: Option<Vec<u8>>,
+    ) -> Self {
+        let base_iter = view.iter_from(&(), start).with_end(end);
+        Self { base_iter }
+    }
+
+    /// Returns raw keys as stored in the database instead of parsing them. This is useful
+    /// when the stored key encoding differs from the key type of the index,
+    /// e.g., when re-keying entries during a migration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut map = fork.get_map("map");
+    /// map.put(&0x0102_u16, "foo".to_owned());
+    ///
+    /// let entries: Vec<_> = map.iter().with_raw_keys().collect();
+    /// assert_eq!(entries, vec![(vec![1, 2], "foo".to_owned())]);
+    /// ```
+    pub fn with_raw_keys(self) -> Entries<'a, [u8], V> {
+        Entries {
+            base_iter: self.base_iter.raw_key_type(),
+        }
+    }
+
     /// Skips values in the iterator output without parsing them.
     pub fn skip_values(self) -> Keys<'a, K> {
         Keys {
@@ -4,11 +4,14 @@
 //! The given section contains information on the methods related to `KeySetIndex`
 //! and the iterator over the items of this set.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::RangeBounds};
 
 use crate::{
     access::{Access, AccessError, FromAccess},
-    indexes::iter::{Entries, IndexIterator, Keys},
+    indexes::{
+        iter::{Entries, IndexIterator, Keys},
+        map::raw_key_range,
+    },
     views::{IndexAddress, IndexType, RawAccess, RawAccessMut, View, ViewWithMetadata},
     BinaryKey,
 };
@@ -89,6 +92,10 @@ where
 
     /// Returns an iterator over set elements starting from the specified value.
     ///
+    /// # Panics
+    ///
+    /// Panics for [sets with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
     /// # Examples
     ///
     /// ```
@@ -105,6 +112,43 @@ where
     pub fn iter_from(&self, from: &K) -> Keys<'_, K> {
         self.index_iter(Some(from)).skip_values()
     }
+
+    /// Returns an iterator over set elements within the specified range.
+    ///
+    /// Similar to [`MapIndex::keys_range`], the range bounds are compared by the binary
+    /// representation of the elements, which coincides with the natural order for most
+    /// [`BinaryKey`] implementations.
+    ///
+    /// [`MapIndex::keys_range`]: struct.MapIndex.html#method.keys_range
+    /// [`BinaryKey`]: ../trait.BinaryKey.html
+    ///
+    /// # Panics
+    ///
+    /// Panics for [sets with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, KeySetIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_key_set("name");
+    /// for i in 0_u64..10 {
+    ///     index.insert(&i);
+    /// }
+    ///
+    /// assert_eq!(index.range(3..6).collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// assert_eq!(index.range(..=2).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert_eq!(index.range(8..).collect::<Vec<_>>(), vec![8, 9]);
+    /// ```
+    pub fn range<R>(&self, range: R) -> Keys<'_, K>
+    where
+        R: RangeBounds<K>,
+    {
+        let (start, end) = raw_key_range(&range);
+        Entries::with_range(&self.base, &start, end).skip_values()
+    }
 }
 
 impl<T, K> KeySetIndex<T, K>
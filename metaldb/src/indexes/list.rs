@@ -3,14 +3,16 @@
 //! The given section contains methods related to `ListIndex` and the iterator
 //! over the items of this list.
 
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::iter;
 use std::marker::PhantomData;
 
 use crate::{
     access::{Access, AccessError, FromAccess},
     indexes::iter::{Entries, IndexIterator, Values},
     views::{IndexAddress, IndexState, IndexType, RawAccess, RawAccessMut, View, ViewWithMetadata},
-    BinaryValue,
+    BinaryKey, BinaryValue,
 };
 
 /// A list of items where elements are added to the end of the list and are
@@ -100,6 +102,63 @@ where
         self.base.multi_get(indexes)
     }
 
+    /// Returns elements at the supplied positions in the order of the positions. `None` is
+    /// returned for positions which are out of bounds.
+    ///
+    /// Unlike [`multi_get`], this method sorts and deduplicates the requested positions
+    /// before reading them from the database. This makes reads of scattered elements
+    /// more efficient with backends such as `RocksDB`. Each element requested several times
+    /// is read once and deserialized for every occurrence.
+    ///
+    /// [`multi_get`]: #method.multi_get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_list("name");
+    /// index.extend(vec![10, 20, 30]);
+    /// assert_eq!(
+    ///     index.get_many(vec![2, 0, 5, 2]),
+    ///     vec![Some(30), Some(10), None, Some(30)]
+    /// );
+    /// ```
+    pub fn get_many<I>(&self, indexes: I) -> Vec<Option<V>>
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let indexes: Vec<u64> = indexes.into_iter().collect();
+        let len = self.len();
+        let mut unique_indexes: Vec<u64> = indexes
+            .iter()
+            .copied()
+            .filter(|&index| index < len)
+            .collect();
+        unique_indexes.sort_unstable();
+        unique_indexes.dedup();
+
+        let raw_keys = unique_indexes.iter().map(|index| {
+            let mut key = vec![0; index.size()];
+            index.write(&mut key);
+            key
+        });
+        let values = self.base.multi_get_bytes(raw_keys);
+
+        indexes
+            .into_iter()
+            .map(|index| {
+                let pos = unique_indexes.binary_search(&index).ok()?;
+                let bytes = values[pos].as_ref()?;
+                let value =
+                    V::from_bytes(Cow::Borrowed(bytes)).expect("Error while deserializing value");
+                Some(value)
+            })
+            .collect()
+    }
+
     /// Returns the last element of the list or `None` if the list is empty.
     ///
     /// # Examples
@@ -185,25 +244,147 @@ where
     }
 
     /// Returns an iterator over the list values starting from the specified position.
+    /// If `from` is greater than or equal to the list length, the iterator is empty.
     ///
     /// # Examples
     ///
+    /// Paging through a list:
+    ///
     /// ```
     /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
     ///
     /// let db = TemporaryDB::new();
     /// let fork = db.fork();
     /// let mut index = fork.get_list("name");
+    /// index.extend(1_u32..=5);
     ///
-    /// index.extend([1, 2, 3, 4, 5].iter().cloned());
+    /// const PAGE_SIZE: usize = 2;
+    /// let pages: Vec<Vec<u32>> = (0..index.len())
+    ///     .step_by(PAGE_SIZE)
+    ///     .map(|start| index.iter_from(start).take(PAGE_SIZE).collect())
+    ///     .collect();
+    /// assert_eq!(pages, vec![vec![1, 2], vec![3, 4], vec![5]]);
     ///
-    /// for val in index.iter_from(3) {
-    ///     println!("{}", val);
-    /// }
+    /// assert_eq!(index.iter_from(5).count(), 0);
     /// ```
     pub fn iter_from(&self, from: u64) -> Values<'_, V> {
         self.index_iter(Some(&from)).skip_keys()
     }
+
+    /// Returns an iterator over the list values paired with their positions in the list.
+    ///
+    /// Unlike `iter().enumerate()`, positions are taken from the stored keys rather than
+    /// counted from zero, so they remain correct when combined with iterator adapters
+    /// or if the list does not start from position 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_list("name");
+    /// index.extend(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]);
+    ///
+    /// let items: Vec<_> = index.iter_indexed().skip(1).collect();
+    /// assert_eq!(items, vec![(1, "b".to_owned()), (2, "c".to_owned())]);
+    /// ```
+    pub fn iter_indexed(&self) -> Entries<'_, u64, V> {
+        self.index_iter(None)
+    }
+
+    /// Returns an iterator over the list values split into chunks of `size` elements.
+    /// The last chunk may be shorter if the list length is not divisible by `size`.
+    ///
+    /// The iterator is lazy: values are read from the database one chunk at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0, similarly to [`slice::chunks`].
+    ///
+    /// [`slice::chunks`]: https://doc.rust-lang.org/std/primitive.slice.html#method.chunks
+    ///
+    /// # Examples
+    ///
+    /// Processing a list in batches:
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_list("name");
+    /// index.extend(1_u32..=5);
+    ///
+    /// let mut batch_sums = vec![];
+    /// for batch in index.chunks(2) {
+    ///     assert!(batch.len() <= 2);
+    ///     batch_sums.push(batch.iter().sum::<u32>());
+    /// }
+    /// assert_eq!(batch_sums, vec![3, 7, 5]);
+    /// ```
+    pub fn chunks(&self, size: u64) -> impl Iterator<Item = Vec<V>> + '_ {
+        assert_ne!(size, 0, "Chunk size must be non-zero");
+        let size = size as usize;
+        let mut values = self.iter();
+        iter::from_fn(move || {
+            let chunk: Vec<_> = values.by_ref().take(size).collect();
+            if chunk.is_empty() {
+                None
+            } else {
+                Some(chunk)
+            }
+        })
+    }
+
+    /// Binary searches the list with a comparator function, mirroring
+    /// [`slice::binary_search_by`]. The comparator should return whether the probed element
+    /// is `Less`, `Equal` or `Greater` than the target.
+    ///
+    /// If a matching element is found, returns `Ok` with its index. If there are multiple
+    /// matches, any one of them may be returned. If there is no match, returns `Err` with
+    /// the index at which a matching element could be inserted while keeping the list sorted.
+    ///
+    /// Each probe reads a single element, so the search performs `O(log n)` reads.
+    /// The result is only meaningful if the list is sorted according to the comparator;
+    /// the list does not check this, so it is the caller's responsibility to keep
+    /// the elements sorted.
+    ///
+    /// [`slice::binary_search_by`]: https://doc.rust-lang.org/std/primitive.slice.html#method.binary_search_by
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_list("name");
+    /// index.extend(vec![1_u32, 3, 5, 7]);
+    ///
+    /// assert_eq!(index.binary_search_by(|probe| probe.cmp(&5)), Ok(2));
+    /// assert_eq!(index.binary_search_by(|probe| probe.cmp(&4)), Err(2));
+    /// assert_eq!(index.binary_search_by(|probe| probe.cmp(&8)), Err(4));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<u64, u64>
+    where
+        F: FnMut(&V) -> Ordering,
+    {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let value = self
+                .get(mid)
+                .expect("BUG: list element is missing within list bounds");
+            match f(&value) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
 }
 
 impl<T, V> ListIndex<T, V>
@@ -259,8 +440,51 @@ where
         }
     }
 
+    /// Removes an element at the indicated position and returns it, or `None` if the position
+    /// is out of bounds. The removed element is replaced by the last element of the list.
+    ///
+    /// This does not preserve ordering, but is O(1), similar to [`Vec::swap_remove`].
+    ///
+    /// [`Vec::swap_remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.swap_remove
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, ListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_list("name");
+    /// index.extend([1, 2, 3, 4].iter().cloned());
+    ///
+    /// assert_eq!(Some(2), index.swap_remove(1));
+    /// assert_eq!(vec![1, 4, 3], index.iter().collect::<Vec<_>>());
+    /// assert_eq!(None, index.swap_remove(3));
+    /// ```
+    pub fn swap_remove(&mut self, index: u64) -> Option<V> {
+        let len = self.len();
+        if index >= len {
+            return None;
+        }
+
+        let last_index = len - 1;
+        if index == last_index {
+            return self.pop();
+        }
+
+        let removed = self.base.get(&index);
+        let last = self.base.get::<_, V>(&last_index)?;
+        self.base.put(&index, last);
+        self.base.remove(&last_index);
+        self.set_len(last_index);
+        removed
+    }
+
     /// Extends the list with the contents of an iterator.
     ///
+    /// The length of the list is updated once per call rather than once per element,
+    /// so extending the list is preferable to pushing elements one by one.
+    ///
     /// # Examples
     ///
     /// ```
@@ -478,6 +702,15 @@ mod tests {
         assert_eq!(list_index.iter_from(0).collect::<Vec<_>>(), vec![1, 2, 3]);
         assert_eq!(list_index.iter_from(1).collect::<Vec<_>>(), vec![2, 3]);
         assert_eq!(list_index.iter_from(3).count(), 0);
+        assert_eq!(list_index.iter_from(u64::MAX).count(), 0);
+
+        // Positions freed by removing elements are not iterated over.
+        list_index.pop();
+        assert_eq!(list_index.iter_from(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(list_index.iter_from(2).count(), 0);
+        list_index.truncate(1);
+        assert_eq!(list_index.iter_from(1).count(), 0);
+        assert_eq!(list_index.iter_from(0).collect::<Vec<_>>(), vec![1]);
     }
 
     fn list_index_clear_in_family(db: &dyn Database, x: u32, y: u32, merge_before_clear: bool) {
@@ -618,4 +851,125 @@ mod tests {
         assert_eq!(list.get(1), None);
         assert_eq!(list.iter().collect::<Vec<_>>(), vec![3]);
     }
+
+    #[test]
+    fn swap_remove() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut list = fork.get_list::<_, u32>(IDX_NAME);
+        assert_eq!(list.swap_remove(0), None);
+
+        list.extend(vec![1, 2, 3]);
+        assert_eq!(list.swap_remove(3), None);
+        assert_eq!(list.swap_remove(0), Some(1));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![3, 2]);
+        // Removing the last element is equivalent to `pop`.
+        assert_eq!(list.swap_remove(1), Some(2));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(list.swap_remove(0), Some(3));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn get_many() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![0_u32, 1, 2, 3, 4]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut list = fork.get_list::<_, u32>("list");
+        list.set(3, 30);
+        list.push(5);
+        assert_eq!(list.get_many(vec![]), vec![]);
+        assert_eq!(
+            list.get_many(vec![5, 3, 0, 6, 3, u64::MAX, 0]),
+            vec![Some(5), Some(30), Some(0), None, Some(30), None, Some(0)]
+        );
+        let indexes = vec![4, 1, 1, 2];
+        assert_eq!(list.get_many(indexes.clone()), list.multi_get(&indexes));
+    }
+
+    #[test]
+    fn chunks() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut list = fork.get_list::<_, u32>(IDX_NAME);
+        assert_eq!(list.chunks(3).count(), 0);
+
+        list.extend(0..6);
+        let chunks: Vec<_> = list.chunks(3).collect();
+        assert_eq!(chunks, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        let chunks: Vec<_> = list.chunks(10).collect();
+        assert_eq!(chunks, vec![vec![0, 1, 2, 3, 4, 5]]);
+        assert_eq!(list.chunks(1).count(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chunk size must be non-zero")]
+    fn chunks_with_zero_size() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let list = fork.get_list::<_, u32>(IDX_NAME);
+        let _ = list.chunks(0);
+    }
+
+    #[test]
+    fn iter_indexed() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut list = fork.get_list("list");
+        assert_eq!(list.iter_indexed().count(), 0);
+        list.extend(vec![10_u32, 20, 30, 40]);
+        list.pop();
+        let items: Vec<_> = list.iter_indexed().collect();
+        assert_eq!(items, vec![(0, 10), (1, 20), (2, 30)]);
+
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        let list = snapshot.get_list::<_, u32>("list");
+        let items: Vec<_> = list
+            .iter_indexed()
+            .filter(|(_, value)| *value > 10)
+            .collect();
+        assert_eq!(items, vec![(1, 20), (2, 30)]);
+    }
+
+    #[test]
+    fn binary_search() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut list = fork.get_list::<_, u32>(IDX_NAME);
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&1)), Err(0));
+
+        list.push(5);
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&5)), Ok(0));
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&1)), Err(0));
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&7)), Err(1));
+
+        list.extend(vec![5, 5, 8, 10, 10]);
+        let found = list.binary_search_by(|probe| probe.cmp(&5)).unwrap();
+        assert!(found <= 2, "{}", found);
+        let found = list.binary_search_by(|probe| probe.cmp(&10)).unwrap();
+        assert!(found == 4 || found == 5, "{}", found);
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&8)), Ok(3));
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&6)), Err(3));
+        assert_eq!(list.binary_search_by(|probe| probe.cmp(&11)), Err(6));
+
+        // Results are consistent with slices.
+        let items: Vec<_> = list.iter().collect();
+        for target in 0..12 {
+            let expected = items.binary_search_by(|probe| probe.cmp(&target));
+            let actual = list.binary_search_by(|probe| probe.cmp(&target));
+            assert_eq!(
+                actual.ok().map(|i| items[i as usize]),
+                expected.ok().map(|i| items[i]),
+                "{}",
+                target
+            );
+            if let Err(pos) = expected {
+                assert_eq!(actual, Err(pos as u64));
+            }
+        }
+    }
 }
@@ -4,13 +4,18 @@
 //! the [`BinaryValue`] trait. The given section contains methods related to
 //! `MapIndex` and iterators over the items of this map.
 
-use std::{borrow::Borrow, marker::PhantomData};
+use std::{
+    borrow::Borrow,
+    fmt, iter,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
 
 use crate::{
     access::{Access, AccessError, FromAccess},
     indexes::iter::{Entries, IndexIterator, Keys, Values},
     views::{IndexAddress, IndexType, RawAccess, RawAccessMut, View, ViewWithMetadata},
-    BinaryKey, BinaryValue,
+    BinaryKey, BinaryValue, PinnedValue,
 };
 
 /// A map of keys and values. Access to the elements of this map is obtained using the keys.
@@ -73,6 +78,33 @@ where
         self.base.get(key)
     }
 
+    /// Returns the raw value corresponding to the key without deserializing it.
+    /// The value is borrowed from the storage if possible, which avoids copying large values.
+    ///
+    /// The returned value is tied to the lifetime of the index, and thus, to the snapshot
+    /// or fork the index is based on. Currently, values are borrowed from changes staged
+    /// in a fork or patch and from `TemporaryDB` snapshots; values read from `RocksDB`
+    /// are copied, since its bindings do not support pinned reads from snapshots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// assert!(index.get_pinned(&1).is_none());
+    ///
+    /// index.put(&1, vec![2_u8; 1_024]);
+    /// let value = index.get_pinned(&1).unwrap();
+    /// assert!(value.is_borrowed());
+    /// assert_eq!(&value[..], &[2; 1_024][..]);
+    /// ```
+    pub fn get_pinned(&self, key: &K) -> Option<PinnedValue<'_>> {
+        self.base.get_pinned(key)
+    }
+
     /// Returns values corresponding to the keys.
     ///
     /// # Examples
@@ -116,6 +148,57 @@ where
         self.base.contains(key)
     }
 
+    /// Returns the entry with the smallest key in the map, or `None` if the map is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// assert_eq!(index.first(), None);
+    ///
+    /// index.put(&2, 20);
+    /// index.put(&1, 10);
+    /// assert_eq!(index.first(), Some((1, 10)));
+    /// ```
+    pub fn first(&self) -> Option<(K::Owned, V)> {
+        self.base.assert_ordered();
+        self.iter().next()
+    }
+
+    /// Returns the entry with the greatest key in the map, or `None` if the map is empty.
+    ///
+    /// Unlike iterating over the entire map, this method seeks directly to the last key.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// assert_eq!(index.last(), None);
+    ///
+    /// index.put(&2, 20);
+    /// index.put(&1, 10);
+    /// assert_eq!(index.last(), Some((2, 20)));
+    /// ```
+    pub fn last(&self) -> Option<(K::Owned, V)> {
+        self.base.last::<K, V>()
+    }
+
     /// Returns an iterator over the entries of the map in ascending order.
     ///
     /// # Examples
@@ -176,6 +259,10 @@ where
     /// Returns an iterator over the entries of a map in ascending order starting from the
     /// specified key.
     ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
     /// # Examples
     ///
     /// ```
@@ -196,6 +283,10 @@ where
     /// Returns an iterator over the keys of a map in ascending order starting from the
     /// specified key.
     ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
     /// # Examples
     ///
     /// ```
@@ -216,6 +307,10 @@ where
     /// Returns an iterator over the values of a map in ascending order of keys starting from the
     /// specified key.
     ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
     /// # Examples
     ///
     /// ```
@@ -231,6 +326,174 @@ where
     pub fn values_from(&self, from: &K) -> Values<'_, V> {
         self.iter_from(from).skip_keys()
     }
+
+    /// Returns an iterator over the entries of a map in ascending order starting from the first
+    /// key strictly greater than `after`. Unlike [`iter_from`], the entry with the `after` key
+    /// is never yielded, which is convenient for cursor-based pagination.
+    ///
+    /// [`iter_from`]: #method.iter_from
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// for i in 0_u64..10 {
+    ///     index.put(&i, i.to_string());
+    /// }
+    ///
+    /// let mut pages = vec![];
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page: Vec<_> = match cursor {
+    ///         Some(key) => index.iter_after(&key).take(4).collect(),
+    ///         None => index.iter().take(4).collect(),
+    ///     };
+    ///     match page.last() {
+    ///         Some((key, _)) => cursor = Some(*key),
+    ///         None => break,
+    ///     }
+    ///     pages.push(page.len());
+    /// }
+    /// assert_eq!(pages, vec![4, 4, 2]);
+    /// ```
+    pub fn iter_after(&self, after: &K) -> Entries<'_, K, V> {
+        let (start, end) = raw_key_range(&(Bound::Excluded(after), Bound::Unbounded));
+        Entries::with_range(&self.base, &start, end)
+    }
+
+    /// Returns an iterator over the entries of a map with keys starting with the serialized
+    /// `prefix`, in ascending order. The iteration starts from the first key with
+    /// the prefix and stops as soon as a key does not start with it.
+    ///
+    /// The prefix is matched on the serialized keys. Thus, to iterate over a group of keys
+    /// with the same leading component (e.g., keys `(group_id, item_id)` with the given
+    /// `group_id`), the leading component must have a fixed-size serialization, such as
+    /// an integer or a fixed-size byte array. Otherwise, the prefix may match keys
+    /// from other groups; for example, a string prefix `"ab"` matches keys
+    /// with the leading component `"abc"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, BinaryKey, TemporaryDB, Database, MapIndex};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct ItemKey {
+    ///     group_id: u32,
+    ///     item_id: u32,
+    /// }
+    ///
+    /// impl BinaryKey for ItemKey {
+    ///     fn size(&self) -> usize {
+    ///         8
+    ///     }
+    ///
+    ///     fn write(&self, buffer: &mut [u8]) -> usize {
+    ///         self.group_id.write(&mut buffer[0..4]);
+    ///         self.item_id.write(&mut buffer[4..8]);
+    ///         8
+    ///     }
+    ///
+    ///     fn read(buffer: &[u8]) -> Self {
+    ///         let group_id = u32::read(&buffer[0..4]);
+    ///         let item_id = u32::read(&buffer[4..8]);
+    ///         Self { group_id, item_id }
+    ///     }
+    /// }
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("items");
+    /// for group_id in 0..3 {
+    ///     for item_id in 0..5 {
+    ///         index.put(&ItemKey { group_id, item_id }, group_id * 10 + item_id);
+    ///     }
+    /// }
+    ///
+    /// let values: Vec<_> = index.iter_prefix(&1_u32).map(|(_, value)| value).collect();
+    /// assert_eq!(values, vec![10, 11, 12, 13, 14]);
+    /// ```
+    pub fn iter_prefix<P>(&self, prefix: &P) -> Entries<'_, K, V>
+    where
+        P: BinaryKey + ?Sized,
+    {
+        Entries::with_prefix(&self.base, prefix, None)
+    }
+
+    /// Returns an iterator over the keys of a map within the specified range in ascending order.
+    /// Values are not deserialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// for i in 0_u64..10 {
+    ///     index.put(&i, i.to_string());
+    /// }
+    ///
+    /// assert_eq!(index.keys_range(3..6).collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// assert_eq!(index.keys_range(..=2).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert_eq!(index.keys_range(8..).collect::<Vec<_>>(), vec![8, 9]);
+    /// ```
+    pub fn keys_range<R>(&self, range: R) -> Keys<'_, K>
+    where
+        R: RangeBounds<K>,
+    {
+        let (start, end) = raw_key_range(&range);
+        Entries::with_range(&self.base, &start, end).skip_values()
+    }
+
+    /// Converts the map into a read-only view, which transforms values with `f` when they
+    /// are read. Values are transformed lazily, i.e., on each `get` or during iteration;
+    /// no additional data is stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("prices");
+    /// index.put("apple", 125_u64);
+    /// index.put("pear", 80_u64);
+    ///
+    /// let display = index.map_values(|cents| format!("${}.{:02}", cents / 100, cents % 100));
+    /// assert_eq!(display.get("apple").unwrap(), "$1.25");
+    /// assert_eq!(
+    ///     display.iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         ("apple".to_owned(), "$1.25".to_owned()),
+    ///         ("pear".to_owned(), "$0.80".to_owned()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn map_values<W, F>(self, f: F) -> MappedMap<T, K, V, F>
+    where
+        F: Fn(V) -> W,
+    {
+        MappedMap { index: self, f }
+    }
 }
 
 impl<T, K, V> MapIndex<T, K, V>
@@ -282,6 +545,52 @@ where
         self.base.remove(key);
     }
 
+    /// Exchanges values at the specified keys. If one of the keys is absent from the map,
+    /// the value at the other key is moved to it, and the other key is removed.
+    /// If both keys are absent or coincide, the map is not changed.
+    ///
+    /// Values are moved in the serialized form, i.e., without deserializing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// index.put(&1, "foo".to_owned());
+    /// index.put(&2, "bar".to_owned());
+    ///
+    /// index.swap(&1, &2);
+    /// assert_eq!(index.get(&1), Some("bar".to_owned()));
+    /// assert_eq!(index.get(&2), Some("foo".to_owned()));
+    ///
+    /// // The value is moved to an absent key.
+    /// index.swap(&2, &3);
+    /// assert_eq!(index.get(&2), None);
+    /// assert_eq!(index.get(&3), Some("foo".to_owned()));
+    /// ```
+    pub fn swap(&mut self, a: &K, b: &K) {
+        if concat_keys!(a) == concat_keys!(b) {
+            return;
+        }
+        let a_value = self.base.get::<K, Vec<u8>>(a);
+        let b_value = self.base.get::<K, Vec<u8>>(b);
+        if a_value.is_none() && b_value.is_none() {
+            return;
+        }
+
+        match b_value {
+            Some(value) => self.base.put(a, value),
+            None => self.base.remove(a),
+        }
+        match a_value {
+            Some(value) => self.base.put(b, value),
+            None => self.base.remove(b),
+        }
+    }
+
     /// Clears a map, removing all entries.
     ///
     /// # Notes
@@ -307,6 +616,125 @@ where
     pub fn clear(&mut self) {
         self.base.clear();
     }
+
+    /// Removes entries from the map in ascending order of keys, yielding the removed entries.
+    ///
+    /// Entries are removed lazily, as they are yielded by the returned iterator. If the iterator
+    /// is dropped before it is exhausted, the entries which have not been yielded are left
+    /// intact. Each step of the iterator seeks to the entry following the previously removed
+    /// one, so the removed entries do not slow down subsequent steps.
+    ///
+    /// Draining is supported for maps with hashed keys as well; for such maps, the entries
+    /// are removed in an unspecified order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// for i in 0_u64..5 {
+    ///     index.put(&i, i.to_string());
+    /// }
+    ///
+    /// let drained: Vec<_> = index.drain().take(2).collect();
+    /// assert_eq!(drained, vec![(0, "0".to_owned()), (1, "1".to_owned())]);
+    /// // Entries which have not been yielded remain in the map.
+    /// assert_eq!(index.keys().collect::<Vec<_>>(), vec![2, 3, 4]);
+    ///
+    /// let other: Vec<_> = index.drain().map(|(_, value)| value).collect();
+    /// assert_eq!(other, vec!["2", "3", "4"]);
+    /// assert!(index.iter().next().is_none());
+    /// ```
+    pub fn drain(&mut self) -> impl Iterator<Item = (K::Owned, V)> + '_ {
+        // Stored key to seek to on the next step. Seeking by stored keys rather than
+        // by raw keys makes draining work for maps with hashed keys as well.
+        let mut cursor = vec![];
+        iter::from_fn(move || {
+            let (key, value) = self.base.iter_from_stored::<K, V>(&cursor).next()?;
+            let key_ref: &K = key.borrow();
+            cursor = self.base.stored_key(key_ref);
+            self.base.remove(key_ref);
+            Some((key, value))
+        })
+    }
+
+    /// Removes all entries with keys in the specified range. The range is interpreted
+    /// in terms of the binary key encoding, which coincides with the natural key order
+    /// for keys with an order-preserving [`BinaryKey`] implementation.
+    ///
+    /// Unlike removing keys one by one, this method does not read the removed entries.
+    /// The range is removed by the database backend on merge, using a single
+    /// range deletion for RocksDB.
+    ///
+    /// [`BinaryKey`]: ../trait.BinaryKey.html
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, MapIndex};
+    ///
+    /// let db = TemporaryDB::default();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_map("name");
+    /// for i in 0_u64..10 {
+    ///     index.put(&i, i.to_string());
+    /// }
+    ///
+    /// index.clear_range(3..7);
+    /// assert_eq!(
+    ///     index.keys().collect::<Vec<_>>(),
+    ///     vec![0, 1, 2, 7, 8, 9]
+    /// );
+    /// index.clear_range(8..);
+    /// assert_eq!(index.keys().collect::<Vec<_>>(), vec![0, 1, 2, 7]);
+    /// ```
+    pub fn clear_range<R>(&mut self, range: R)
+    where
+        R: RangeBounds<K>,
+    {
+        let (start, end) = raw_key_range(&range);
+        if end.as_ref().map_or(false, |end| *end <= start) {
+            // The range is empty.
+            return;
+        }
+        self.base.remove_range(start, end);
+    }
+}
+
+/// Converts a range of keys into the `[start, end)` range of raw keys. If `end` is `None`,
+/// the range is unbounded from above.
+pub(super) fn raw_key_range<K, R>(range: &R) -> (Vec<u8>, Option<Vec<u8>>)
+where
+    K: BinaryKey + ?Sized,
+    R: RangeBounds<K>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(key) => concat_keys!(key),
+        Bound::Excluded(key) => {
+            let mut start = concat_keys!(key);
+            // The smallest key greater than `key`.
+            start.push(0);
+            start
+        }
+        Bound::Unbounded => vec![],
+    };
+    let end = match range.end_bound() {
+        Bound::Included(key) => {
+            let mut end = concat_keys!(key);
+            end.push(0);
+            Some(end)
+        }
+        Bound::Excluded(key) => Some(concat_keys!(key)),
+        Bound::Unbounded => None,
+    };
+    (start, end)
 }
 
 impl<'a, T, K, V> IntoIterator for &'a MapIndex<T, K, V>
@@ -337,9 +765,91 @@ where
     }
 }
 
+/// Read-only view of a [`MapIndex`], which transforms values when they are read.
+///
+/// This structure is created by the [`MapIndex::map_values`] method.
+///
+/// [`MapIndex`]: struct.MapIndex.html
+/// [`MapIndex::map_values`]: struct.MapIndex.html#method.map_values
+pub struct MappedMap<T: RawAccess, K: ?Sized, V, F> {
+    index: MapIndex<T, K, V>,
+    f: F,
+}
+
+impl<T, K, V, F> fmt::Debug for MappedMap<T, K, V, F>
+where
+    T: RawAccess + fmt::Debug,
+    K: ?Sized + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("MappedMap")
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl<T, K, V, W, F> MappedMap<T, K, V, F>
+where
+    T: RawAccess,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+    F: Fn(V) -> W,
+{
+    /// Returns a transformed value corresponding to the key.
+    pub fn get(&self, key: &K) -> Option<W> {
+        self.index.get(key).map(&self.f)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains(&self, key: &K) -> bool {
+        self.index.contains(key)
+    }
+
+    /// Returns an iterator over the entries of the map in ascending order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (K::Owned, W)> + '_ {
+        self.index
+            .iter()
+            .map(move |(key, value)| (key, (self.f)(value)))
+    }
+
+    /// Returns an iterator over the entries of the map in ascending order starting from the
+    /// specified key.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [maps with hashed keys](../struct.IndexAddress.html#method.with_key_hashing).
+    pub fn iter_from(&self, from: &K) -> impl Iterator<Item = (K::Owned, W)> + '_ {
+        self.index
+            .iter_from(from)
+            .map(move |(key, value)| (key, (self.f)(value)))
+    }
+
+    /// Returns an iterator over the keys of the map in ascending order. Values are neither
+    /// deserialized nor transformed.
+    pub fn keys(&self) -> Keys<'_, K> {
+        self.index.keys()
+    }
+
+    /// Returns an iterator over the transformed values of the map in ascending order of keys.
+    pub fn values(&self) -> impl Iterator<Item = W> + '_ {
+        self.index.values().map(&self.f)
+    }
+
+    /// Returns the underlying map.
+    pub fn into_inner(self) -> MapIndex<T, K, V> {
+        self.index
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{access::CopyAccessExt, Database, TemporaryDB};
+    use super::{Bound, MapIndex};
+    use crate::{
+        access::{CopyAccessExt, RawAccess},
+        DBOptions, Database, Fork, RocksDB, TemporaryDB,
+    };
 
     const IDX_NAME: &str = "idx_name";
 
@@ -357,6 +867,128 @@ mod tests {
         assert!(!index.contains(KEY));
     }
 
+    #[test]
+    fn swapping_values() {
+        let db = TemporaryDB::default();
+        let fork = db.fork();
+        let mut index = fork.get_map(IDX_NAME);
+        index.put(&1_u8, 10_u32);
+        index.put(&2, 20);
+        index.put(&5, 50);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut index = fork.get_map::<_, u8, u32>(IDX_NAME);
+        // Both keys are present.
+        index.swap(&1, &2);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![(1, 20), (2, 10), (5, 50)]
+        );
+        // Only the first key is present.
+        index.swap(&5, &3);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![(1, 20), (2, 10), (3, 50)]
+        );
+        // Only the second key is present.
+        index.swap(&4, &1);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![(2, 10), (3, 50), (4, 20)]
+        );
+        // Both keys are absent.
+        index.swap(&0, &1);
+        // The same key.
+        index.swap(&2, &2);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![(2, 10), (3, 50), (4, 20)]
+        );
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let index = snapshot.get_map::<_, u8, u32>(IDX_NAME);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![(2, 10), (3, 50), (4, 20)]
+        );
+    }
+
+    #[test]
+    fn iterating_with_raw_keys() {
+        let db = TemporaryDB::default();
+        let fork = db.fork();
+        let mut index = fork.get_map(("group", &1_u8));
+        index.put("foo", 1_u64);
+        index.put("bar", 2_u64);
+        fork.get_map(("group", &2_u8)).put("baz", 3_u64);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut index = fork.get_map::<_, str, u64>(("group", &1_u8));
+        index.put("qux", 4);
+        let entries: Vec<_> = index.iter().with_raw_keys().collect();
+        assert_eq!(
+            entries,
+            vec![
+                (b"bar".to_vec(), 2),
+                (b"foo".to_vec(), 1),
+                (b"qux".to_vec(), 4),
+            ]
+        );
+        let entries: Vec<_> = index.iter_from("c").with_raw_keys().collect();
+        assert_eq!(entries, vec![(b"foo".to_vec(), 1), (b"qux".to_vec(), 4)]);
+    }
+
+    #[test]
+    fn iterating_over_prefix() {
+        let db = TemporaryDB::default();
+        let fork = db.fork();
+        let mut index = fork.get_map(IDX_NAME);
+        // The most significant 4 bytes of the keys correspond to `u32` prefixes.
+        let keys = [0_u64, 1, 0xffff_ffff, 1 << 32, (1 << 32) + 5, 2 << 32];
+        for &key in &keys {
+            index.put(&key, key);
+        }
+
+        let entries: Vec<_> = index.iter_prefix(&0_u32).collect();
+        assert_eq!(entries, vec![(0, 0), (1, 1), (0xffff_ffff, 0xffff_ffff)]);
+        let keys: Vec<_> = index.iter_prefix(&1_u32).skip_values().collect();
+        assert_eq!(keys, vec![1 << 32, (1 << 32) + 5]);
+        assert_eq!(index.iter_prefix(&3_u32).count(), 0);
+        // An empty prefix matches all keys.
+        assert_eq!(index.iter_prefix(&()).count(), 6);
+    }
+
+    #[test]
+    fn mapped_values() {
+        let db = TemporaryDB::default();
+        let fork = db.fork();
+        let mut index = fork.get_map(IDX_NAME);
+        for i in 0_u8..5 {
+            index.put(&i, u64::from(i));
+        }
+
+        let mapped = index.map_values(|value| value.to_string());
+        assert_eq!(mapped.get(&3).unwrap(), "3");
+        assert_eq!(mapped.get(&5), None);
+        assert!(mapped.contains(&4));
+        assert_eq!(mapped.keys().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            mapped.values().collect::<Vec<_>>(),
+            ["0", "1", "2", "3", "4"]
+        );
+        assert_eq!(
+            mapped.iter_from(&3).collect::<Vec<_>>(),
+            vec![(3, "3".to_owned()), (4, "4".to_owned())]
+        );
+
+        let mut index = mapped.into_inner();
+        index.put(&5, 5);
+        assert_eq!(index.map_values(|value| value * 2).get(&5), Some(10));
+    }
+
     #[test]
     fn test_u8_slice_key() {
         const KEY: &[u8] = &[1, 2, 3];
@@ -471,6 +1103,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn iter_after_adjacent_keys() {
+        let db = TemporaryDB::default();
+        let fork = db.fork();
+        let mut map_index = fork.get_map(IDX_NAME);
+        for i in [0_u8, 1, 2, 254, 255] {
+            map_index.put(&i, i);
+        }
+        drop(map_index);
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut map_index = fork.get_map::<_, u8, u8>(IDX_NAME);
+        map_index.put(&3, 3);
+        map_index.remove(&2);
+
+        let keys_after = |key| {
+            map_index
+                .iter_after(&key)
+                .map(|(k, _)| k)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys_after(0), vec![1, 3, 254, 255]);
+        assert_eq!(keys_after(1), vec![3, 254, 255]);
+        assert_eq!(keys_after(2), vec![3, 254, 255]);
+        assert_eq!(keys_after(254), vec![255]);
+        assert!(keys_after(255).is_empty());
+
+        // Keys sharing a prefix with the cursor key are yielded if they are greater.
+        let mut str_index = fork.get_map::<_, str, u8>("strings");
+        for key in ["a", "ab", "ab\0", "ab\0\0", "abc", "b"] {
+            str_index.put(key, 0);
+        }
+        let keys: Vec<_> = str_index.iter_after("ab").map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["ab\0", "ab\0\0", "abc", "b"]);
+        let keys: Vec<_> = str_index.iter_after("ab\0").map(|(k, _)| k).collect();
+        assert_eq!(keys, vec!["ab\0\0", "abc", "b"]);
+        assert!(str_index.iter_after("b").next().is_none());
+    }
+
     #[test]
     fn index_as_iterator() {
         let db = TemporaryDB::default();
@@ -509,4 +1181,300 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn keys_range_bounds() {
+        use std::ops::Bound::{Excluded, Included, Unbounded};
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        {
+            let mut map = fork.get_map::<_, str, u8>(IDX_NAME);
+            for (i, key) in ["a", "ab", "abc", "b", "ba"].iter().enumerate() {
+                map.put(*key, i as u8);
+            }
+        }
+
+        fn check<T: RawAccess>(
+            map: &MapIndex<T, str, u8>,
+            range: (Bound<&str>, Bound<&str>),
+            expected: &[&str],
+        ) {
+            assert_eq!(map.keys_range(range).collect::<Vec<_>>(), expected);
+        }
+
+        let map = fork.get_map::<_, str, u8>(IDX_NAME);
+        check(&map, (Included("a"), Excluded("ab")), &["a"]);
+        check(&map, (Included("a"), Included("ab")), &["a", "ab"]);
+        check(&map, (Excluded("a"), Excluded("b")), &["ab", "abc"]);
+        check(&map, (Excluded("ab"), Unbounded), &["abc", "b", "ba"]);
+        check(&map, (Unbounded, Excluded("abc")), &["a", "ab"]);
+        check(&map, (Unbounded, Included("b")), &["a", "ab", "abc", "b"]);
+        check(&map, (Unbounded, Unbounded), &["a", "ab", "abc", "b", "ba"]);
+        // Empty ranges.
+        check(&map, (Included("b"), Excluded("b")), &[]);
+        check(&map, (Included("b"), Included("a")), &[]);
+        check(&map, (Included("c"), Unbounded), &[]);
+        drop(map);
+
+        // Ranges respect both the snapshot and the fork changes.
+        db.merge(fork.into_patch()).unwrap();
+        let fork = db.fork();
+        fork.get_map::<_, str, u8>(IDX_NAME).remove("ab");
+        fork.get_map::<_, str, u8>(IDX_NAME).put("aa", 5);
+        let map = fork.get_map::<_, str, u8>(IDX_NAME);
+        check(&map, (Included("a"), Included("abc")), &["a", "aa", "abc"]);
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, str, u8>(IDX_NAME);
+        assert_eq!(
+            map.keys_range("a".."b").collect::<Vec<_>>(),
+            vec!["a", "ab", "abc"]
+        );
+    }
+
+    #[test]
+    fn clear_range_bounds() {
+        use std::ops::Bound::{Excluded, Included, Unbounded};
+
+        fn check(fork: &Fork, range: (Bound<&u8>, Bound<&u8>), expected: &[u8]) {
+            let mut map = fork.get_map::<_, u8, u8>(IDX_NAME);
+            map.clear();
+            for i in 0..10 {
+                map.put(&i, i);
+            }
+            map.clear_range(range);
+            assert_eq!(map.keys().collect::<Vec<_>>(), expected);
+        }
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        check(&fork, (Included(&2), Excluded(&5)), &[0, 1, 5, 6, 7, 8, 9]);
+        check(&fork, (Excluded(&2), Included(&5)), &[0, 1, 2, 6, 7, 8, 9]);
+        check(&fork, (Unbounded, Excluded(&3)), &[3, 4, 5, 6, 7, 8, 9]);
+        check(&fork, (Included(&7), Unbounded), &[0, 1, 2, 3, 4, 5, 6]);
+        check(&fork, (Unbounded, Unbounded), &[]);
+        // Empty ranges.
+        check(
+            &fork,
+            (Included(&3), Excluded(&3)),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        );
+        check(
+            &fork,
+            (Included(&5), Included(&3)),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        );
+        check(
+            &fork,
+            (Excluded(&3), Included(&3)),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        );
+
+        let mut map = fork.get_map::<_, u8, u8>(IDX_NAME);
+        map.clear_range(2..=4);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![0, 1, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn clear_range_in_merged_map() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        {
+            let mut map = fork.get_map((IDX_NAME, &1_u8));
+            for i in 0_u8..10 {
+                map.put(&i, i);
+            }
+            fork.get_map((IDX_NAME, &2_u8)).put(&5_u8, 5_u8);
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        {
+            let mut map = fork.get_map::<_, u8, u8>((IDX_NAME, &1_u8));
+            map.clear_range(3..8);
+            assert_eq!(map.get(&5), None);
+            assert!(!map.contains(&3));
+            assert_eq!(map.values().collect::<Vec<_>>(), vec![0, 1, 2, 8, 9]);
+            map.put(&4, 40);
+            map.clear_range(9..);
+            assert_eq!(map.last(), Some((8, 8)));
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, u8, u8>((IDX_NAME, &1_u8));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (2, 2), (4, 40), (8, 8)]
+        );
+        // The neighboring map is not affected.
+        let neighbor = snapshot.get_map::<_, u8, u8>((IDX_NAME, &2_u8));
+        assert_eq!(neighbor.get(&5), Some(5));
+    }
+
+    fn check_first_and_last_isolation(db: &dyn Database) {
+        let fork = db.fork();
+        // Neighboring indexes in the same column family.
+        fork.get_entry((IDX_NAME, &0_u8)).set(0_u8);
+        let mut map = fork.get_map((IDX_NAME, &1_u8));
+        assert_eq!(map.first(), None);
+        assert_eq!(map.last(), None);
+        map.put(&1_u8, 10_u8);
+        map.put(&2_u8, 20_u8);
+        fork.get_entry((IDX_NAME, &2_u8)).set(42_u8);
+        fork.get_map(IDX_NAME).put(&255_u8, 0_u8);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, u8, u8>((IDX_NAME, &1_u8));
+        assert_eq!(map.first(), Some((1, 10)));
+        assert_eq!(map.last(), Some((2, 20)));
+        let empty_map = snapshot.get_map::<_, u8, u8>((IDX_NAME, &3_u8));
+        assert_eq!(empty_map.last(), None);
+
+        let fork = db.fork();
+        let mut map = fork.get_map::<_, u8, u8>((IDX_NAME, &1_u8));
+        // Deleted entries from the snapshot are skipped.
+        map.remove(&2);
+        assert_eq!(map.last(), Some((1, 10)));
+        map.put(&0, 0);
+        map.remove(&1);
+        assert_eq!(map.first(), Some((0, 0)));
+        assert_eq!(map.last(), Some((0, 0)));
+        map.remove(&0);
+        assert_eq!(map.last(), None);
+        map.put(&5, 50);
+        assert_eq!(map.last(), Some((5, 50)));
+        map.clear();
+        assert_eq!(map.last(), None);
+        map.put(&3, 30);
+        assert_eq!(map.last(), Some((3, 30)));
+        drop(map);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, u8, u8>((IDX_NAME, &1_u8));
+        assert_eq!(map.first(), Some((3, 30)));
+        assert_eq!(map.last(), Some((3, 30)));
+    }
+
+    #[test]
+    fn first_and_last_do_not_read_neighboring_indexes() {
+        let db = TemporaryDB::new();
+        check_first_and_last_isolation(&db);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = RocksDB::open(dir.path(), &DBOptions::default()).unwrap();
+        check_first_and_last_isolation(&db);
+    }
+
+    #[test]
+    fn pinned_values() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut map = fork.get_map::<_, str, Vec<u8>>("map");
+        map.put("foo", vec![1, 2, 3]);
+        map.put("bar", vec![4]);
+        assert_eq!(map.get_pinned("foo").unwrap().into_vec(), vec![1, 2, 3]);
+        drop(map);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let map = snapshot.get_map::<_, str, Vec<u8>>("map");
+        let value = map.get_pinned("foo").unwrap();
+        assert!(value.is_borrowed());
+        assert_eq!(&*value, &[1, 2, 3]);
+        assert!(map.get_pinned("baz").is_none());
+
+        // Staged changes override values in the database.
+        let fork = db.fork();
+        let mut map = fork.get_map::<_, str, Vec<u8>>("map");
+        map.remove("foo");
+        map.put("bar", vec![5]);
+        assert!(map.get_pinned("foo").is_none());
+        assert_eq!(map.get_pinned("bar").unwrap().as_ref(), &[5]);
+    }
+
+    #[test]
+    fn hashed_keys() {
+        use crate::{
+            access::{AccessErrorKind, FromAccess},
+            IndexAddress, IndexType, ListIndex,
+        };
+
+        fn check_hashed_keys(db: &dyn Database) {
+            let fork = db.fork();
+            let addr = IndexAddress::from_root("events").with_key_hashing();
+            let mut map = fork.get_map(addr);
+            for i in 0_u64..100 {
+                map.put(&i, i * 2);
+            }
+            map.remove(&50);
+            assert_eq!(map.get(&10), Some(20));
+            assert!(!map.contains(&50));
+            assert_eq!(map.multi_get(&[1, 50]), vec![Some(2), None]);
+            db.merge(fork.into_patch()).unwrap();
+
+            let snapshot = db.snapshot();
+            let map = snapshot.get_map::<_, u64, u64>("events");
+            assert_eq!(map.get(&99), Some(198));
+            assert_eq!(map.get(&100), None);
+            let mut keys: Vec<_> = map.keys().collect();
+            assert_ne!(keys, (0..100).filter(|&i| i != 50).collect::<Vec<_>>());
+            keys.sort_unstable();
+            assert_eq!(keys, (0..100).filter(|&i| i != 50).collect::<Vec<_>>());
+            assert!(map.iter().all(|(key, value)| value == key * 2));
+        }
+
+        check_hashed_keys(&TemporaryDB::new());
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = RocksDB::open(dir.path(), &DBOptions::default()).unwrap();
+        check_hashed_keys(&db);
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let addr = IndexAddress::from_root("list").with_key_hashing();
+        let err = ListIndex::<_, u64>::from_access(&fork, addr).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            AccessErrorKind::UnsupportedKeyHashing {
+                index_type: IndexType::List
+            }
+        ));
+    }
+
+    #[test]
+    fn draining_map_with_hashed_keys() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let addr = crate::IndexAddress::from_root("events").with_key_hashing();
+        let mut map = fork.get_map(addr);
+        for i in 0_u64..10 {
+            map.put(&i, i * 2);
+        }
+
+        let drained: Vec<_> = map.drain().take(4).collect();
+        assert_eq!(drained.len(), 4);
+        assert!(drained
+            .iter()
+            .all(|&(key, value)| value == key * 2 && !map.contains(&key)));
+        assert_eq!(map.keys().count(), 6);
+
+        let mut keys: Vec<_> = drained.into_iter().map(|(key, _)| key).collect();
+        keys.extend(map.drain().map(|(key, _)| key));
+        keys.sort_unstable();
+        assert_eq!(keys, (0..10).collect::<Vec<_>>());
+        assert!(map.iter().next().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "not supported for indexes with hashed keys")]
+    fn ordered_iteration_over_hashed_keys() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let addr = crate::IndexAddress::from_root("events").with_key_hashing();
+        let mut map = fork.get_map(addr);
+        map.put(&1_u64, 1_u64);
+        let _ = map.iter_from(&1);
+    }
 }
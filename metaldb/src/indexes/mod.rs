@@ -6,7 +6,7 @@ pub use self::{
     iter::{Entries, IndexIterator, Keys, Values},
     key_set::KeySetIndex,
     list::ListIndex,
-    map::MapIndex,
+    map::{MapIndex, MappedMap},
     sparse_list::SparseListIndex,
 };
 
@@ -236,7 +236,8 @@ where
         self.index_iter(None)
     }
 
-    /// Returns an iterator over the indexes of the `SparseListIndex`.
+    /// Returns an iterator over the occupied indexes of the `SparseListIndex`
+    /// in ascending order. Values are not deserialized.
     ///
     /// # Examples
     ///
@@ -248,10 +249,10 @@ where
     /// let mut index = fork.get_sparse_list("name");
     ///
     /// index.extend([1, 2, 3, 4, 5].iter().cloned());
+    /// index.remove(1);
     ///
-    /// for val in index.indexes() {
-    ///     println!("{}", val);
-    /// }
+    /// let indexes: Vec<u64> = index.indexes().collect();
+    /// assert_eq!(indexes, [0, 2, 3, 4]);
     /// ```
     pub fn indexes(&self) -> Keys<'_, u64> {
         self.iter().skip_values()
@@ -417,20 +418,84 @@ where
     /// assert_eq!(Some(10), index.get(0));
     /// ```
     pub fn set(&mut self, index: u64, value: V) -> Option<V> {
-        let mut size = self.size();
         // Update items count
         let old_value = self.base.get::<u64, V>(&index);
         if old_value.is_none() {
-            size.length += 1;
-            if index >= size.capacity {
-                size.capacity = index + 1;
-            }
-            self.set_size(size);
+            self.increment_size(index);
         }
         self.base.put(&index, value);
         old_value
     }
 
+    /// Sets a value at the specified position if the position is empty. Returns `true`
+    /// if the value was written, or `false` if the position already contained an element.
+    ///
+    /// Similar to [`set`], writing at a position greater than or equal to the current capacity
+    /// of the list increases the capacity to `index + 1`.
+    ///
+    /// [`set`]: #method.set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, SparseListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_sparse_list("name");
+    /// index.push(1);
+    ///
+    /// assert!(!index.set_if_absent(0, 10));
+    /// assert_eq!(Some(1), index.get(0));
+    /// assert!(index.set_if_absent(5, 10));
+    /// assert_eq!(Some(10), index.get(5));
+    /// assert_eq!(6, index.capacity());
+    /// assert_eq!(2, index.len());
+    /// ```
+    pub fn set_if_absent(&mut self, index: u64, value: V) -> bool {
+        if self.base.contains(&index) {
+            return false;
+        }
+        self.increment_size(index);
+        self.base.put(&index, value);
+        true
+    }
+
+    /// Returns the element at the specified position, or inserts the value computed by `f`
+    /// at this position if it is empty and returns the value.
+    ///
+    /// Capacity of the list is updated in the same way as with [`set_if_absent`].
+    ///
+    /// [`set_if_absent`]: #method.set_if_absent
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use metaldb::{access::CopyAccessExt, TemporaryDB, Database, SparseListIndex};
+    ///
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut index = fork.get_sparse_list("name");
+    ///
+    /// assert_eq!(3, index.get_or_insert_with(2, || 3_u32));
+    /// assert_eq!(3, index.get_or_insert_with(2, || 4));
+    /// assert_eq!(vec![2], index.indexes().collect::<Vec<_>>());
+    /// assert_eq!(3, index.capacity());
+    /// ```
+    pub fn get_or_insert_with<F>(&mut self, index: u64, f: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        if let Some(value) = self.get(index) {
+            return value;
+        }
+        let value = f();
+        self.increment_size(index);
+        // Store the serialized value in order to return the value without cloning.
+        self.base.put(&index, value.to_bytes());
+        value
+    }
+
     /// Clears the list, removing all values.
     ///
     /// # Notes
@@ -492,6 +557,16 @@ where
     fn set_size(&mut self, size: SparseListSize) {
         self.state.set(size);
     }
+
+    /// Updates the list size after an element is written at an empty position.
+    fn increment_size(&mut self, index: u64) {
+        let mut size = self.size();
+        size.length += 1;
+        if index >= size.capacity {
+            size.capacity = index + 1;
+        }
+        self.set_size(size);
+    }
 }
 
 impl<'a, T, V> IntoIterator for &'a SparseListIndex<T, V>
@@ -652,6 +727,23 @@ mod tests {
         assert_eq!(list_index.values().collect::<Vec<_>>(), vec![1_u8, 2, 3]);
     }
 
+    #[test]
+    fn indexes_with_gaps() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut list_index = fork.get_sparse_list(IDX_NAME);
+        list_index.set(100, 3_u32);
+        list_index.set(0, 1);
+        list_index.set(5, 2);
+
+        assert_eq!(list_index.indexes().collect::<Vec<_>>(), vec![0, 5, 100]);
+        assert_eq!(list_index.len(), 3);
+        assert_eq!(list_index.capacity(), 101);
+
+        list_index.remove(5);
+        assert_eq!(list_index.indexes().collect::<Vec<_>>(), vec![0, 100]);
+    }
+
     #[test]
     fn restore_after_no_op_initialization() {
         let db = TemporaryDB::new();
@@ -188,18 +188,20 @@ impl BinaryKey for [u8] {
     }
 }
 
-impl BinaryKey for [u8; 32] {
+/// Writes the bytes as is, so the keys are ordered lexicographically. This is suitable
+/// for hashes, addresses and other fixed-size byte keys.
+impl<const N: usize> BinaryKey for [u8; N] {
     fn size(&self) -> usize {
-        self.len()
+        N
     }
 
     fn write(&self, buffer: &mut [u8]) -> usize {
-        buffer[..self.size()].copy_from_slice(self);
-        self.size()
+        buffer[..N].copy_from_slice(self);
+        N
     }
 
     fn read(buffer: &[u8]) -> Self::Owned {
-        let mut value = [0_u8; 32];
+        let mut value = [0_u8; N];
         value.copy_from_slice(buffer);
         value
     }
@@ -518,6 +520,64 @@ mod tests {
         }
     }
 
+    fn check_byte_array_key<const N: usize>(values: &mut [[u8; N]]) {
+        // Roundtrip
+        for value in values.iter() {
+            let mut buffer = get_buffer(value);
+            assert_eq!(value.write(&mut buffer), N);
+            assert_eq!(<[u8; N]>::read(&buffer), *value);
+        }
+
+        // Ordering
+        values.sort_unstable();
+        for window in values.windows(2) {
+            let (mut x_buffer, mut y_buffer) = (get_buffer(&window[0]), get_buffer(&window[1]));
+            window[0].write(&mut x_buffer);
+            window[1].write(&mut y_buffer);
+            assert_eq!(x_buffer.cmp(&y_buffer), window[0].cmp(&window[1]));
+        }
+    }
+
+    #[test]
+    fn test_byte_array_keys() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        let mut values: Vec<[u8; 20]> = (0..1_000).map(|_| rng.gen()).collect();
+        values.extend_from_slice(&[[0; 20], [255; 20]]);
+        check_byte_array_key(&mut values);
+
+        let mut values: Vec<[u8; 64]> = (0..1_000)
+            .map(|_| {
+                let mut value = [0; 64];
+                rng.fill(&mut value[..]);
+                value
+            })
+            .collect();
+        values.extend_from_slice(&[[0; 64], [255; 64]]);
+        check_byte_array_key(&mut values);
+
+        check_byte_array_key::<0>(&mut [[]]);
+    }
+
+    #[test]
+    fn test_byte_array_key_in_index() {
+        use crate::{Database, TemporaryDB};
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut map = fork.get_map::<_, [u8; 20], u32>("map");
+        map.put(&[2; 20], 2);
+        map.put(&[1; 20], 1);
+        let mut key = [1; 20];
+        key[19] = 2;
+        map.put(&key, 3);
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![([1; 20], 1), (key, 3), ([2; 20], 2)]
+        );
+    }
+
     #[test]
     fn test_uuid_round_trip() {
         let uuids = [
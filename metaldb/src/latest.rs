@@ -0,0 +1,141 @@
+//! Reading the latest database state for forks created with `Database::fork_latest`.
+
+use std::{fmt, iter};
+
+use crate::{Iter, Iterator as BytesIterator, ResolvedAddress, Snapshot};
+
+/// Number of entries read from the database at once when iterating over the latest state.
+const BATCH_SIZE: usize = 256;
+
+/// Latest committed state of a database. Unlike a `Snapshot`, each read observes
+/// the state of the database at the time of the read.
+pub(crate) trait LatestState: Send + Sync + 'static {
+    /// Returns the current value for the specified address and key.
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// Appends up to `limit` current entries of the specified address starting
+    /// from the key `from` (inclusive) to `buffer`.
+    fn read_entries(
+        &self,
+        name: &ResolvedAddress,
+        from: &[u8],
+        limit: usize,
+        buffer: &mut Vec<(Vec<u8>, Vec<u8>)>,
+    );
+
+    /// Returns the current entry with the greatest key less than `before`, or the last entry
+    /// if `before` is `None`.
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)>;
+}
+
+/// Appends up to `limit` entries from `iter` to `buffer`.
+pub(crate) fn copy_entries(mut iter: Iter<'_>, limit: usize, buffer: &mut Vec<(Vec<u8>, Vec<u8>)>) {
+    for _ in 0..limit {
+        match iter.next() {
+            Some((key, value)) => buffer.push((key.to_vec(), value.to_vec())),
+            None => break,
+        }
+    }
+}
+
+/// Snapshot reading through to the latest state of the database.
+pub(crate) struct LatestSnapshot {
+    state: Box<dyn LatestState>,
+}
+
+impl LatestSnapshot {
+    pub fn new(state: impl LatestState) -> Self {
+        Self {
+            state: Box::new(state),
+        }
+    }
+}
+
+impl Snapshot for LatestSnapshot {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        self.state.get(name, key)
+    }
+
+    fn multi_get<'a>(
+        &self,
+        name: &ResolvedAddress,
+        keys: &'a mut dyn iter::Iterator<Item = &'a [u8]>,
+    ) -> Vec<Option<Vec<u8>>> {
+        keys.map(|key| self.state.get(name, key)).collect()
+    }
+
+    fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
+        Box::new(LatestIter {
+            state: &*self.state,
+            address: name.clone(),
+            next_from: Some(from.to_vec()),
+            entries: Vec::with_capacity(BATCH_SIZE),
+            position: 0,
+        })
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        self.state.last_before(name, before)
+    }
+}
+
+impl fmt::Debug for LatestSnapshot {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("LatestSnapshot").finish()
+    }
+}
+
+/// Iterator over the latest state of the database. Entries are read in batches; each batch
+/// reflects the state of the database at the time it is read.
+struct LatestIter<'a> {
+    state: &'a dyn LatestState,
+    address: ResolvedAddress,
+    /// Key to read the next batch from, or `None` if all entries have been read.
+    next_from: Option<Vec<u8>>,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    position: usize,
+}
+
+impl LatestIter<'_> {
+    /// Reads the next batch if all entries in the current one are consumed.
+    fn fill_entries(&mut self) {
+        if self.position < self.entries.len() {
+            return;
+        }
+        if let Some(from) = self.next_from.take() {
+            self.entries.clear();
+            self.position = 0;
+            self.state
+                .read_entries(&self.address, &from, BATCH_SIZE, &mut self.entries);
+            if self.entries.len() == BATCH_SIZE {
+                // The least key greater than the last read one.
+                let mut next_from = self.entries[BATCH_SIZE - 1].0.clone();
+                next_from.push(0);
+                self.next_from = Some(next_from);
+            }
+        }
+    }
+}
+
+impl BytesIterator for LatestIter<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        self.fill_entries();
+        let (key, value) = self.entries.get(self.position)?;
+        self.position += 1;
+        Some((key, value))
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        self.fill_entries();
+        let (key, value) = self.entries.get(self.position)?;
+        Some((key, value))
+    }
+}
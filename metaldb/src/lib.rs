@@ -51,8 +51,7 @@
 //! - [`SparseListIndex`] is a list of items stored in a sequential order. Similar to `ListIndex`,
 //!   but may contain indexes without elements.
 //! - [`MapIndex`] is a map of keys and values. Similar to [`BTreeMap`].
-//! - [`KeySetIndex`] and [`ValueSetIndex`] are sets of items, similar to [`BTreeSet`] and
-//!   [`HashSet`] accordingly.
+//! - [`KeySetIndex`] is a set of items, similar to [`BTreeSet`].
 //!
 //! # Migrations
 //!
@@ -76,14 +75,11 @@
 //! [`SparseListIndex`]: indexes/struct.SparseListIndex.html
 //! [`MapIndex`]: indexes/struct.MapIndex.html
 //! [`KeySetIndex`]: indexes/struct.KeySetIndex.html
-//! [`ValueSetIndex`]: indexes/struct.ValueSetIndex.html
-//! [`ObjectHash`]: trait.ObjectHash.html
 //! [`Option`]: https://doc.rust-lang.org/std/option/enum.Option.html
 //! [`Box`]: https://doc.rust-lang.org/std/boxed/struct.Box.html
 //! [`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
 //! [`BTreeMap`]: https://doc.rust-lang.org/std/collections/struct.BTreeMap.html
 //! [`BTreeSet`]: https://doc.rust-lang.org/std/collections/struct.BTreeSet.html
-//! [`HashSet`]: https://doc.rust-lang.org/std/collections/struct.HashSet.html
 //! [`Group`]: indexes/group/struct.Group.html
 
 #![warn(
@@ -112,19 +108,24 @@ pub mod _reexports {
     pub use anyhow::Error;
 }
 
+#[cfg(feature = "zstd")]
+pub use self::values::CompressedValue;
 pub use self::{
     backends::{
         rocksdb::{self, RocksDB},
         temporarydb::TemporaryDB,
     },
     db::{
-        Database, DatabaseExt, Fork, Iter, Iterator, OwnedReadonlyFork, Patch, ReadonlyFork,
-        Snapshot,
+        check_database, BatchedFork, Change, Database, DatabaseExt, Fork, Iter, Iterator, KeyRange,
+        OwnedReadonlyFork, OwnedSnapshot, Patch, PatchStats, PinnedValue, ReadonlyFork, Snapshot,
+        ViewChanges,
     },
-    error::Error,
+    error::{Error, ErrorKind},
     keys::BinaryKey,
     lazy::Lazy,
-    options::DBOptions,
+    metrics::{MetricsEvent, MetricsSink},
+    options::{CorruptionMode, DBOptions, TempDbOptions},
+    read_set::{MergeConflict, MergeError, ReadSet},
     values::BinaryValue,
     views::{AsReadonly, IndexAddress, IndexType, ResolvedAddress},
 };
@@ -139,12 +140,16 @@ pub mod access;
 mod backends;
 mod db;
 mod error;
+mod export;
 pub mod generic;
 pub mod indexes;
 mod keys;
+mod latest;
 mod lazy;
+mod metrics;
 pub mod migration;
 mod options;
+mod read_set;
 pub mod validation;
 mod values;
 mod views;
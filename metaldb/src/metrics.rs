@@ -0,0 +1,121 @@
+//! Hooks for observing database operations.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::Patch;
+
+/// Event emitted by a database to a [`MetricsSink`].
+///
+/// [`MetricsSink`]: trait.MetricsSink.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MetricsEvent {
+    /// A patch has been successfully merged into the database.
+    MergeCompleted {
+        /// Approximate total size of keys and values written in the patch, in bytes.
+        bytes: usize,
+        /// Time spent merging the patch, including waiting for concurrent merges.
+        duration: Duration,
+    },
+    /// A snapshot of the database has been created. Note that creating a fork
+    /// creates a snapshot as well.
+    SnapshotCreated,
+}
+
+/// Receiver of [`MetricsEvent`]s emitted by a database, which can be installed with
+/// [`Database::set_metrics_sink`].
+///
+/// Events are recorded synchronously in the thread performing the operation, so
+/// implementations should be cheap, e.g., update atomic counters or send events to a channel.
+///
+/// [`MetricsEvent`]: enum.MetricsEvent.html
+/// [`Database::set_metrics_sink`]: trait.Database.html#method.set_metrics_sink
+///
+/// # Examples
+///
+/// ```
+/// use metaldb::{access::CopyAccessExt, Database, MetricsEvent, MetricsSink, TemporaryDB};
+/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+///
+/// #[derive(Default)]
+/// struct MergedBytes(AtomicUsize);
+///
+/// impl MetricsSink for Arc<MergedBytes> {
+///     fn record(&self, event: MetricsEvent) {
+///         if let MetricsEvent::MergeCompleted { bytes, .. } = event {
+///             self.0.fetch_add(bytes, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let merged_bytes = Arc::new(MergedBytes::default());
+/// let db = TemporaryDB::new();
+/// db.set_metrics_sink(Box::new(Arc::clone(&merged_bytes)));
+/// let fork = db.fork();
+/// fork.get_entry("entry").set(1_u64);
+/// db.merge(fork.into_patch()).unwrap();
+/// assert!(merged_bytes.0.load(Ordering::Relaxed) > 0);
+/// ```
+pub trait MetricsSink: Send + Sync + 'static {
+    /// Records an event.
+    fn record(&self, event: MetricsEvent);
+}
+
+/// Optional metrics sink used by the backends in this crate. The sink is shared among
+/// all clones of the hook, so installing a sink affects all handles to the database.
+/// If no sink is installed, observing an operation amounts to a single check.
+#[derive(Clone, Default)]
+pub(crate) struct MetricsHook {
+    sink: Arc<RwLock<Option<Arc<dyn MetricsSink>>>>,
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsHook")
+            .field("is_set", &self.sink().is_some())
+            .finish()
+    }
+}
+
+impl MetricsHook {
+    pub fn set(&self, sink: Box<dyn MetricsSink>) {
+        *self.sink.write().expect("Couldn't get metrics sink lock") = Some(Arc::from(sink));
+    }
+
+    fn sink(&self) -> Option<Arc<dyn MetricsSink>> {
+        self.sink
+            .read()
+            .expect("Couldn't get metrics sink lock")
+            .clone()
+    }
+
+    pub fn snapshot_created(&self) {
+        if let Some(sink) = self.sink() {
+            sink.record(MetricsEvent::SnapshotCreated);
+        }
+    }
+
+    /// Merges `patch` with the provided closure, recording the merge if it succeeds.
+    pub fn observe_merge<T, E>(
+        &self,
+        patch: Patch,
+        merge: impl FnOnce(Patch) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let sink = match self.sink() {
+            Some(sink) => sink,
+            None => return merge(patch),
+        };
+        let bytes = patch.pending_bytes();
+        let start = Instant::now();
+        let output = merge(patch)?;
+        sink.record(MetricsEvent::MergeCompleted {
+            bytes,
+            duration: start.elapsed(),
+        });
+        Ok(output)
+    }
+}
@@ -67,6 +67,7 @@ pub use self::persistent_iter::{PersistentIter, PersistentIters, PersistentKeys}
 use thiserror::Error;
 
 use std::{
+    borrow::Borrow,
     fmt,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -75,19 +76,24 @@ use std::{
 };
 
 use crate::{
-    access::{Access, AccessError, Prefixed, RawAccess},
+    access::{Access, AccessError, AccessExt, Prefixed, RawAccess},
     validation::{assert_valid_name_component, check_index_valid_full_name},
     views::{
         AsReadonly, GroupKeys, IndexAddress, IndexMetadata, IndexType, IndexesPool, RawAccessMut,
         View, ViewWithMetadata,
     },
-    BinaryKey, Database, Fork, ReadonlyFork,
+    BinaryKey, BinaryValue, Database, Fork, ReadonlyFork,
 };
 
 mod persistent_iter;
 
 /// Name of the column family used to store `Scratchpad`s.
-const SCRATCHPAD_NAME: &str = "__scratchpad__";
+pub(crate) const SCRATCHPAD_NAME: &str = "__scratchpad__";
+/// Prefix for the scratchpad entries storing positions of iterators used
+/// by `MigrationHelper::migrate_index`.
+const MIGRATE_INDEX_PREFIX: &str = "__migrate_index__";
+/// Number of entries migrated by `MigrationHelper::migrate_index` between merges.
+const MIGRATE_INDEX_CHUNK_SIZE: usize = 1_000;
 
 /// Access to migrated indexes.
 ///
@@ -463,6 +469,82 @@ impl MigrationHelper {
         Ok(())
     }
 
+    /// Migrates a map from the old data to the new data, transforming its entries
+    /// with the provided closure. Entries for which `transform` returns `None` are skipped.
+    ///
+    /// The source map is read from the address `from` in the [old data](#method.old_data),
+    /// and the transformed entries are written into the map at the address `to`
+    /// in the [new data](#method.new_data). Changes are merged to the database after each
+    /// chunk of entries; an error is returned if a merge fails. The position of migration
+    /// is stored in the [scratchpad](#method.scratchpad), so if the process is restarted,
+    /// calling this method with a fresh helper resumes the migration from the entry
+    /// following the last merged chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::{AccessExt, CopyAccessExt}, Database, TemporaryDB};
+    /// # use metaldb::migration::{MigrationError, MigrationHelper};
+    /// # fn main() -> Result<(), MigrationError> {
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let mut balances = fork.get_map("test.balances");
+    /// balances.put("Alice", 100_u64);
+    /// balances.put("Bob", 0_u64);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let mut helper = MigrationHelper::new(db, "test");
+    /// // Remove empty accounts and convert balances to `u32`.
+    /// helper.migrate_index::<str, u64, u32, _>("balances", "balances", |name, balance| {
+    ///     if balance == 0 {
+    ///         None
+    ///     } else {
+    ///         Some((name, balance as u32))
+    ///     }
+    /// })?;
+    /// let balances = helper.new_data().get_map::<_, str, u32>("balances");
+    /// assert_eq!(balances.get("Alice"), Some(100));
+    /// assert!(!balances.contains("Bob"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn migrate_index<K, V1, V2, F>(
+        &mut self,
+        from: impl Into<IndexAddress>,
+        to: impl Into<IndexAddress>,
+        mut transform: F,
+    ) -> Result<(), MigrationError>
+    where
+        K: BinaryKey + ?Sized,
+        V1: BinaryValue,
+        V2: BinaryValue,
+        F: FnMut(K::Owned, V1) -> Option<(K::Owned, V2)>,
+    {
+        let from = from.into();
+        let to = to.into();
+        let position_addr = to.clone().prepend_name(MIGRATE_INDEX_PREFIX);
+
+        loop {
+            let processed = {
+                let source = self.old_data().get_map::<_, K, V1>(from.clone());
+                let mut target = self.new_data().get_map::<_, K, V2>(to.clone());
+                let iter = PersistentIter::new(&self.scratchpad(), position_addr.clone(), &source);
+                let mut processed = 0;
+                for (key, value) in iter.take(MIGRATE_INDEX_CHUNK_SIZE) {
+                    if let Some((key, value)) = transform(key, value) {
+                        target.put(key.borrow(), value);
+                    }
+                    processed += 1;
+                }
+                processed
+            };
+            self.merge()?;
+            if processed < MIGRATE_INDEX_CHUNK_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
     /// Merges the changes to the migrated data and the migration scratchpad to the database.
     /// Returns hash representing migrated data state, or an error if the merge has failed.
     ///
@@ -947,6 +1029,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn migrating_index_with_transform() -> Result<(), MigrationError> {
+        const ENTRY_COUNT: u64 = 2_500;
+
+        let db = Arc::new(TemporaryDB::new());
+        let fork = db.fork();
+        {
+            let mut map = fork.get_map(("test.values", &1_u8));
+            for i in 0..ENTRY_COUNT {
+                map.put(&i, i.to_string());
+            }
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let mut helper = MigrationHelper::new(Arc::clone(&db) as Arc<dyn Database>, "test");
+        helper.migrate_index::<u64, String, u64, _>(
+            ("values", &1_u8),
+            "numbers",
+            |key, value| {
+                if key % 2 == 0 {
+                    Some((key / 2, value.parse().unwrap()))
+                } else {
+                    None
+                }
+            },
+        )?;
+        helper.finish()?;
+
+        let snapshot = db.snapshot();
+        let numbers = Migration::new("test", &snapshot).get_map::<_, u64, u64>("numbers");
+        assert_eq!(numbers.keys().count() as u64, ENTRY_COUNT / 2);
+        assert!(numbers.iter().all(|(key, value)| value == key * 2));
+
+        // The migration position is persisted, so the migration is not repeated.
+        let mut helper = MigrationHelper::new(Arc::clone(&db) as Arc<dyn Database>, "test");
+        helper.migrate_index::<u64, String, u64, _>(("values", &1_u8), "numbers", |_, _| {
+            panic!("Migration should not be repeated")
+        })?;
+        Ok(())
+    }
+
     struct MigrationRig {
         thread_handle: thread::JoinHandle<Result<(), MigrationError>>,
         abort_handle: AbortHandle,
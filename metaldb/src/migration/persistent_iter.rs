@@ -12,6 +12,7 @@ use std::{
 use crate::{
     access::{Access, AccessExt, RawAccess, RawAccessMut},
     indexes::{Entries, IndexIterator},
+    views::IndexAddress,
     BinaryKey, BinaryValue, Entry,
 };
 
@@ -172,8 +173,9 @@ where
     T: RawAccessMut,
     I: IndexIterator,
 {
-    /// Creates a new persistent iterator.
-    pub fn new<A>(access: &A, name: &str, index: &'a I) -> Self
+    /// Creates a new persistent iterator. The iterator position is stored in an entry
+    /// with the specified address.
+    pub fn new<A>(access: &A, name: impl Into<IndexAddress>, index: &'a I) -> Self
     where
         A: Access<Base = T>,
     {
@@ -254,7 +256,7 @@ where
     I: IndexIterator,
 {
     /// Creates a new persistent iterator.
-    pub fn new<A>(access: &A, name: &str, index: &'a I) -> Self
+    pub fn new<A>(access: &A, name: impl Into<IndexAddress>, index: &'a I) -> Self
     where
         A: Access<Base = T>,
     {
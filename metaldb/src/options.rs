@@ -1,6 +1,6 @@
 //! Abstract settings for databases.
 
-use rocksdb::DBCompressionType;
+use rocksdb::{DBCompressionType, DBRecoveryMode};
 use serde::{Deserialize, Serialize};
 
 /// Options for the database.
@@ -41,10 +41,75 @@ pub struct DBOptions {
     ///
     /// Defaults to `None`, meaning that there will be no cache used.
     pub max_cache_size: Option<usize>,
+    /// Size of a single memtable in bytes. Larger memtables improve write throughput
+    /// at the cost of memory usage and longer recovery after a crash.
+    ///
+    /// Note that memtables are allocated for each column family separately, and the database
+    /// may keep up to `max_write_buffer_number` memtables per column family. Thus, the memory
+    /// consumed by memtables may reach `write_buffer_size * max_write_buffer_number`
+    /// multiplied by the number of column families.
+    ///
+    /// Defaults to `None`, meaning that the `RocksDB` default is used (64 MB).
+    pub write_buffer_size: Option<usize>,
+    /// Maximum number of memtables per column family, including the one being written to.
+    /// Increasing this number allows to continue writing while memtables are being flushed
+    /// to disk. See [`write_buffer_size`] for memory implications.
+    ///
+    /// Defaults to `None`, meaning that the `RocksDB` default is used (2 memtables).
+    ///
+    /// [`write_buffer_size`]: #structfield.write_buffer_size
+    pub max_write_buffer_number: Option<i32>,
+    /// Approximate size of data blocks in SST files, in bytes. Blocks are the unit
+    /// of reading data from disk and of compression.
+    ///
+    /// Larger blocks improve throughput of scans (e.g., iteration over large indexes)
+    /// and compression ratio, and reduce the size of block indexes. The tradeoff is
+    /// read amplification for point lookups: reading a single value requires reading
+    /// and decompressing the entire block containing it.
+    ///
+    /// Defaults to `None`, meaning that the `RocksDB` default is used (4 KB).
+    pub block_size: Option<usize>,
+    /// Whether to collect internal `RocksDB` statistics, such as cache hit rates
+    /// and compaction stats. The collected statistics can be retrieved with
+    /// [`RocksDB::statistics`]. Collecting statistics incurs a small performance overhead.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`RocksDB::statistics`]: ../struct.RocksDB.html#method.statistics
+    #[serde(default)]
+    pub enable_statistics: bool,
+    /// Whether the database should aggressively check consistency of the data, e.g.,
+    /// verify checksums of files when opening the database. If a check fails,
+    /// the database refuses to open or returns errors on reads and writes.
+    ///
+    /// Defaults to `None`, meaning that the `RocksDB` default is used (checks are enabled).
+    #[serde(default)]
+    pub paranoid_checks: Option<bool>,
+    /// Behavior on detecting corrupted records in the write-ahead log (WAL) when recovering
+    /// the database after a crash. See [`CorruptionMode`] for details.
+    ///
+    /// Defaults to `CorruptionMode::TolerateTail`, which corresponds to the `RocksDB` default.
+    ///
+    /// [`CorruptionMode`]: enum.CorruptionMode.html
+    #[serde(default)]
+    pub on_corruption: CorruptionMode,
+    /// Limit on the rate of disk writes performed by background flushes and compactions,
+    /// in bytes per second. Limiting background IO prevents compactions from saturating
+    /// the disk and starving foreground reads, at the cost of compactions taking longer.
+    /// Non-positive values are treated as no limit.
+    ///
+    /// Defaults to `None`, meaning that background IO is unlimited.
+    #[serde(default)]
+    pub background_io_bytes_per_sec: Option<i64>,
 }
 
 impl DBOptions {
     /// Creates a new `DBOptions` object.
+    ///
+    /// Memtable options (`write_buffer_size` and `max_write_buffer_number`), `block_size`,
+    /// `paranoid_checks` and `background_io_bytes_per_sec` are set to `None`, statistics
+    /// are disabled, and corruption handling is set to `CorruptionMode::TolerateTail`.
+    /// These options can be changed afterwards.
     pub fn new(
         max_open_files: Option<i32>,
         create_if_missing: bool,
@@ -58,10 +123,42 @@ impl DBOptions {
             compression_type,
             max_total_wal_size,
             max_cache_size,
+            write_buffer_size: None,
+            max_write_buffer_number: None,
+            block_size: None,
+            enable_statistics: false,
+            paranoid_checks: None,
+            on_corruption: CorruptionMode::default(),
+            background_io_bytes_per_sec: None,
         }
     }
 }
 
+/// Options for the [`TemporaryDB`].
+///
+/// [`TemporaryDB`]: ../struct.TemporaryDB.html
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct TempDbOptions {
+    /// Approximate amount of memory in bytes that the database may occupy before its contents
+    /// are spilled to a `RocksDB` instance in a temporary directory.
+    ///
+    /// The amount is estimated as the total size of keys and values stored in the database.
+    /// Once spilled, the database stays on disk until it is [cleared].
+    ///
+    /// Defaults to `None`, meaning that the database is always kept in memory.
+    ///
+    /// [cleared]: ../struct.TemporaryDB.html#method.clear
+    pub max_memory_bytes: Option<usize>,
+}
+
+impl TempDbOptions {
+    /// Creates a new `TempDbOptions` object.
+    pub fn new(max_memory_bytes: Option<usize>) -> Self {
+        Self { max_memory_bytes }
+    }
+}
+
 /// Algorithms of compression for the database.
 ///
 /// Database contents are stored in a set of blocks, each of which holds a
@@ -94,8 +191,38 @@ impl From<CompressionType> for DBCompressionType {
     }
 }
 
+/// Behavior of the database on detecting corrupted records in the write-ahead log (WAL)
+/// during recovery after a crash.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CorruptionMode {
+    /// Fail to open the database if any corruption is detected in the WAL, including
+    /// incomplete records at the end of the log, which may be left by a crash.
+    Fail,
+    /// Recover the database to the last consistent point in time before the first
+    /// corrupted record in the WAL, discarding the records after it. This tolerates
+    /// incomplete records at the end of the log, which are commonly left by a crash.
+    #[default]
+    TolerateTail,
+}
+
+impl From<CorruptionMode> for DBRecoveryMode {
+    fn from(mode: CorruptionMode) -> Self {
+        match mode {
+            CorruptionMode::Fail => Self::AbsoluteConsistency,
+            CorruptionMode::TolerateTail => Self::PointInTime,
+        }
+    }
+}
+
 impl Default for DBOptions {
     fn default() -> Self {
         Self::new(None, true, CompressionType::None, None, None)
     }
 }
+
+impl Default for TempDbOptions {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
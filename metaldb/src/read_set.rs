@@ -0,0 +1,235 @@
+//! Tracking of database reads for optimistic concurrency control.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, iter,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Error, Iter, Iterator as BytesIterator, ResolvedAddress, Snapshot};
+
+/// Set of database entries read by a fork together with the values observed at the time
+/// of reading. A read set is used to check that the data a patch was computed against
+/// has not changed before merging the patch; see [`Database::merge_checked`].
+///
+/// Read sets are accumulated by forks created with [`Database::fork_tracking`]
+/// and can be retrieved with [`Fork::read_set`]. A read set records point reads
+/// (including reads of index metadata, such as list lengths) and entries returned
+/// by iterators. Insertions of new entries into a range scanned by an iterator
+/// are not detected unless they change a recorded entry or index metadata.
+///
+/// [`Database::merge_checked`]: trait.Database.html#method.merge_checked
+/// [`Database::fork_tracking`]: trait.Database.html#method.fork_tracking
+/// [`Fork::read_set`]: struct.Fork.html#method.read_set
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadSet {
+    reads: HashMap<ResolvedAddress, BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+impl ReadSet {
+    /// Creates an empty read set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of recorded reads.
+    pub fn len(&self) -> usize {
+        self.reads.values().map(BTreeMap::len).sum()
+    }
+
+    /// Checks if the read set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if the read set contains a read of the `key` in the view at `address`.
+    pub fn contains(&self, address: &ResolvedAddress, key: &[u8]) -> bool {
+        self.reads
+            .get(address)
+            .map_or(false, |reads| reads.contains_key(key))
+    }
+
+    /// Iterates over the addresses of views and keys within these views that have been read.
+    /// Keys are yielded in no particular order.
+    pub fn keys(&self) -> impl iter::Iterator<Item = (&ResolvedAddress, &[u8])> + '_ {
+        self.reads
+            .iter()
+            .flat_map(|(address, reads)| reads.keys().map(move |key| (address, key.as_slice())))
+    }
+
+    /// Records a read of the `key` in the view at `address`. Only the first read of a key
+    /// is recorded, since it reflects the database state the fork was created from.
+    fn record(&mut self, address: &ResolvedAddress, key: &[u8], value: Option<Vec<u8>>) {
+        if let Some(reads) = self.reads.get_mut(address) {
+            reads.entry(key.to_vec()).or_insert(value);
+        } else {
+            let mut reads = BTreeMap::new();
+            reads.insert(key.to_vec(), value);
+            self.reads.insert(address.clone(), reads);
+        }
+    }
+
+    /// Checks that the recorded values are up to date. The current values are retrieved
+    /// with the `get` closure.
+    pub(crate) fn check<F>(&self, mut get: F) -> Result<(), MergeConflict>
+    where
+        F: FnMut(&ResolvedAddress, &[u8]) -> Option<Vec<u8>>,
+    {
+        for (address, reads) in &self.reads {
+            for (key, value) in reads {
+                if get(address, key) != *value {
+                    return Err(MergeConflict {
+                        address: address.clone(),
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Conflict detected by [`Database::merge_checked`]: a value read by the fork has been
+/// changed in the database since the fork was created.
+///
+/// [`Database::merge_checked`]: trait.Database.html#method.merge_checked
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error(
+    "Entry with key {:?} in view {:?} was changed concurrently",
+    key,
+    address
+)]
+pub struct MergeConflict {
+    /// Address of the view containing the changed entry.
+    pub address: ResolvedAddress,
+    /// Key of the changed entry within the view.
+    pub key: Vec<u8>,
+}
+
+/// Error returned by [`Database::merge_checked`].
+///
+/// [`Database::merge_checked`]: trait.Database.html#method.merge_checked
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// Data read by the fork was changed concurrently; the patch was not applied.
+    /// The patch should be recomputed based on a fresh fork.
+    #[error("{0}")]
+    Conflict(#[from] MergeConflict),
+    /// Error merging the patch into the database.
+    #[error("{0}")]
+    Storage(#[from] Error),
+}
+
+/// Snapshot recording all reads from the underlying snapshot into a `ReadSet`.
+pub(crate) struct TrackingSnapshot {
+    snapshot: Box<dyn Snapshot>,
+    reads: Arc<Mutex<ReadSet>>,
+}
+
+impl TrackingSnapshot {
+    pub fn new(snapshot: Box<dyn Snapshot>, reads: Arc<Mutex<ReadSet>>) -> Self {
+        Self { snapshot, reads }
+    }
+
+    fn record(&self, address: &ResolvedAddress, key: &[u8], value: Option<Vec<u8>>) {
+        record_read(&self.reads, address, key, value);
+    }
+}
+
+pub(crate) fn record_read(
+    reads: &Mutex<ReadSet>,
+    address: &ResolvedAddress,
+    key: &[u8],
+    value: Option<Vec<u8>>,
+) {
+    reads
+        .lock()
+        .expect("Failed to lock read set")
+        .record(address, key, value);
+}
+
+impl Snapshot for TrackingSnapshot {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.snapshot.get(name, key);
+        self.record(name, key, value.clone());
+        value
+    }
+
+    fn multi_get<'a>(
+        &self,
+        name: &ResolvedAddress,
+        keys: &'a mut dyn iter::Iterator<Item = &'a [u8]>,
+    ) -> Vec<Option<Vec<u8>>> {
+        let keys: Vec<_> = keys.collect();
+        let values = self
+            .snapshot
+            .multi_get(name, &mut keys.iter().map(|key| &key[..]));
+        for (key, value) in keys.into_iter().zip(&values) {
+            self.record(name, key, value.clone());
+        }
+        values
+    }
+
+    fn contains(&self, name: &ResolvedAddress, key: &[u8]) -> bool {
+        self.get(name, key).is_some()
+    }
+
+    fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
+        Box::new(TrackingIter::new(
+            self.snapshot.iter(name, from),
+            name,
+            &self.reads,
+        ))
+    }
+
+    fn last_before(
+        &self,
+        name: &ResolvedAddress,
+        before: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        let entry = self.snapshot.last_before(name, before);
+        if let Some((key, value)) = &entry {
+            self.record(name, key, Some(value.clone()));
+        }
+        entry
+    }
+}
+
+impl fmt::Debug for TrackingSnapshot {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("TrackingSnapshot")
+            .field("reads", &self.reads)
+            .finish()
+    }
+}
+
+/// Iterator recording the returned entries into a `ReadSet`.
+pub(crate) struct TrackingIter<'a> {
+    inner: Iter<'a>,
+    address: ResolvedAddress,
+    reads: &'a Mutex<ReadSet>,
+}
+
+impl<'a> TrackingIter<'a> {
+    pub fn new(inner: Iter<'a>, address: &ResolvedAddress, reads: &'a Mutex<ReadSet>) -> Self {
+        Self {
+            inner,
+            address: address.clone(),
+            reads,
+        }
+    }
+}
+
+impl BytesIterator for TrackingIter<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        let (key, value) = self.inner.next()?;
+        record_read(self.reads, &self.address, key, Some(value.to_vec()));
+        Some((key, value))
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        self.inner.peek()
+    }
+}
@@ -1,18 +1,94 @@
-//! Validation helpers for index names.
+//! Validation helpers for index names, keys and database invariants.
+//!
+//! # Examples
+//!
+//! Checking that a hand-written [`BinaryKey`] implementation preserves the ordering of keys:
+//!
+//! ```
+//! # use metaldb::{validation::assert_key_ordering, BinaryKey};
+//! #[derive(Debug, Clone)]
+//! struct Timestamp(u32);
+//!
+//! impl BinaryKey for Timestamp {
+//!     fn size(&self) -> usize {
+//!         4
+//!     }
+//!
+//!     fn write(&self, buffer: &mut [u8]) -> usize {
+//!         // Big-endian encoding preserves the ordering of integers.
+//!         buffer[..4].copy_from_slice(&self.0.to_be_bytes());
+//!         4
+//!     }
+//!
+//!     fn read(buffer: &[u8]) -> Self {
+//!         let mut bytes = [0; 4];
+//!         bytes.copy_from_slice(&buffer[..4]);
+//!         Timestamp(u32::from_be_bytes(bytes))
+//!     }
+//! }
+//!
+//! assert_key_ordering(&[Timestamp(1), Timestamp(255), Timestamp(256), Timestamp(65_536)]);
+//! ```
+//!
+//! With a little-endian encoding, the check fails:
+//!
+//! ```should_panic
+//! # use metaldb::{validation::assert_key_ordering, BinaryKey};
+//! #[derive(Debug, Clone)]
+//! struct Timestamp(u32);
+//!
+//! impl BinaryKey for Timestamp {
+//!     // ...
+//! #    fn size(&self) -> usize {
+//! #        4
+//! #    }
+//!     fn write(&self, buffer: &mut [u8]) -> usize {
+//!         buffer[..4].copy_from_slice(&self.0.to_le_bytes());
+//!         4
+//!     }
+//! #
+//! #    fn read(buffer: &[u8]) -> Self {
+//! #        let mut bytes = [0; 4];
+//! #        bytes.copy_from_slice(&buffer[..4]);
+//! #        Timestamp(u32::from_le_bytes(bytes))
+//! #    }
+//! }
+//!
+//! // Panics: 256 is encoded as `00010000`, which is less than `ff000000` encoding 255.
+//! assert_key_ordering(&[Timestamp(1), Timestamp(255), Timestamp(256)]);
+//! ```
+//!
+//! [`BinaryKey`]: ../trait.BinaryKey.html
 
-use crate::access::AccessErrorKind;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Write as _},
+};
 
-/// Validates that an index `name` consists of allowed chars. This method does not check
-/// if `name` is empty.
+use crate::{
+    access::AccessErrorKind,
+    views::{IndexesPool, View},
+    BinaryKey, ResolvedAddress, Snapshot,
+};
+
+/// Validates that an index `name` consists of allowed chars. Besides chars allowed
+/// in [name components], a full name may contain separators: a dot `'.'` or a slash `'/'`.
+/// A slash may only separate non-empty parts of the name, i.e., the name cannot start or end
+/// with a slash or contain consecutive slashes. This method does not check if `name` is empty.
+///
+/// [name components]: fn.is_valid_index_name_component.html
 pub fn is_valid_identifier(name: &str) -> bool {
-    name.as_bytes()
+    let has_valid_chars = name
+        .as_bytes()
         .iter()
-        .all(|&c| is_allowed_index_name_char(c) || c == b'.')
+        .all(|&c| is_allowed_index_name_char(c) || c == b'.' || c == b'/');
+    has_valid_chars && (!name.contains('/') || name.split('/').all(|part| !part.is_empty()))
 }
 
 /// Validates that a `prefix` consists of chars allowed for an index prefix.
 ///
-/// Unlike [full names], prefixes are not allowed to contain a dot char `'.'`.
+/// Unlike [full names], prefixes are not allowed to contain separators (a dot char `'.'`
+/// or a slash `'/'`).
 ///
 /// [full names]: fn.is_valid_identifier.html
 pub fn is_valid_index_name_component(prefix: &str) -> bool {
@@ -58,19 +134,303 @@ pub(crate) fn check_index_valid_full_name(name: &str) -> Result<(), AccessErrorK
     if name.starts_with("__") && !name.contains('.') {
         return Err(AccessErrorKind::ReservedName);
     }
-    check_valid_name(name, is_valid_identifier, "a-zA-Z0-9 and _-.")
+    check_valid_name(
+        name,
+        is_valid_identifier,
+        "a-zA-Z0-9 and _-. (and / between non-empty parts)",
+    )
 }
 
 pub(crate) fn assert_valid_name_component(name: &str) {
     check_valid_name(name, is_valid_index_name_component, "a-zA-Z0-9 and _-").unwrap();
 }
 
+/// Checks that binary encodings of `keys` are sorted in the same order as `keys` themselves.
+/// `keys` are assumed to be sorted in the ascending order; equal keys are allowed.
+///
+/// This helper is intended for use in tests of [`BinaryKey`] implementations. Indexes
+/// iterate over keys in the lexicographic order of their encodings, so an implementation
+/// that does not preserve the key ordering (e.g., one encoding integers as little-endian)
+/// leads to unexpected results of iteration.
+///
+/// # Panics
+///
+/// Panics if the encoding of a key is greater than the encoding of the following key.
+/// The panic message contains both keys and their hex-encoded serializations.
+///
+/// [`BinaryKey`]: ../trait.BinaryKey.html
+pub fn assert_key_ordering<K>(keys: &[K])
+where
+    K: BinaryKey + fmt::Debug,
+{
+    let encoded: Vec<_> = keys.iter().map(|key| concat_keys!(key)).collect();
+    for (i, window) in encoded.windows(2).enumerate() {
+        if window[0] > window[1] {
+            panic!(
+                "Binary encoding does not preserve key ordering at position {}: \
+                 key {:?} is encoded as {}, but the next key {:?} is encoded as {}",
+                i,
+                keys[i],
+                to_hex(&window[0]),
+                keys[i + 1],
+                to_hex(&window[1])
+            );
+        }
+    }
+}
+
+/// Location of the first difference between two snapshots, as returned by [`snapshots_diff`].
+///
+/// [`snapshots_diff`]: fn.snapshots_diff.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotsDiff {
+    /// Fully qualified name of the differing index, i.e., its name together with the key
+    /// in the group for indexes in groups.
+    pub full_name: Vec<u8>,
+    /// Resolved address of the differing index. If the index is present in the first snapshot,
+    /// the address is resolved in it; otherwise, the address is resolved in the second snapshot.
+    pub address: ResolvedAddress,
+    /// First differing key within the index, or `None` if the index is absent in one
+    /// of the snapshots or has a different type.
+    pub key: Option<Vec<u8>>,
+}
+
+/// Checks whether two snapshots contain the same indexes with the same contents.
+/// If `prefix` is specified, only indexes with the fully qualified name starting with
+/// the prefix are compared.
+///
+/// See [`snapshots_diff`] for more details.
+///
+/// [`snapshots_diff`]: fn.snapshots_diff.html
+pub fn snapshots_eq(first: &dyn Snapshot, second: &dyn Snapshot, prefix: Option<&str>) -> bool {
+    snapshots_diff(first, second, prefix).is_none()
+}
+
+/// Returns the first difference between two snapshots, or `None` if the snapshots contain
+/// the same indexes with the same contents. If `prefix` is specified, only indexes
+/// with the fully qualified name starting with the prefix are compared. Note that the prefix
+/// is matched bytewise, so the `foo` prefix matches both `foo.bar` and `foobar` indexes.
+///
+/// Indexes are compared by their fully qualified names, types and raw key-value pairs.
+/// Identifiers of indexes are not compared, so the snapshots may come from different
+/// databases in which indexes were created in a different order.
+///
+/// The snapshots are scanned in a single pass, so the method is suitable for comparing
+/// large databases, e.g., in tests.
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, validation::snapshots_diff, Database, TemporaryDB};
+/// let first_db = TemporaryDB::new();
+/// let second_db = TemporaryDB::new();
+/// for db in &[&first_db, &second_db] {
+///     let fork = db.fork();
+///     fork.get_list("list").push(1_u32);
+///     fork.get_entry("other.entry").set(2_u8);
+///     db.merge(fork.into_patch()).unwrap();
+/// }
+/// let fork = second_db.fork();
+/// fork.get_entry("other.entry").set(3_u8);
+/// second_db.merge(fork.into_patch()).unwrap();
+///
+/// let (first, second) = (first_db.snapshot(), second_db.snapshot());
+/// let diff = snapshots_diff(&*first, &*second, None).unwrap();
+/// assert_eq!(diff.full_name, b"other.entry");
+/// assert_eq!(diff.key, Some(vec![]));
+/// assert!(snapshots_diff(&*first, &*second, Some("list")).is_none());
+/// ```
+pub fn snapshots_diff(
+    first: &dyn Snapshot,
+    second: &dyn Snapshot,
+    prefix: Option<&str>,
+) -> Option<SnapshotsDiff> {
+    let prefix = prefix.unwrap_or_default().as_bytes();
+    let first_pool = IndexesPool::new(first);
+    let second_pool = IndexesPool::new(second);
+    let mut first_indexes = first_pool.indexes_by_prefix(prefix).peekable();
+    let mut second_indexes = second_pool.indexes_by_prefix(prefix).peekable();
+
+    loop {
+        let ordering = match (first_indexes.peek(), second_indexes.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((first_name, ..)), Some((second_name, ..))) => first_name.cmp(second_name),
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let (full_name, address, _) = first_indexes.next().unwrap();
+                return Some(SnapshotsDiff {
+                    full_name,
+                    address,
+                    key: None,
+                });
+            }
+            Ordering::Greater => {
+                let (full_name, address, _) = second_indexes.next().unwrap();
+                return Some(SnapshotsDiff {
+                    full_name,
+                    address,
+                    key: None,
+                });
+            }
+            Ordering::Equal => {
+                let (full_name, address, first_type) = first_indexes.next().unwrap();
+                let (_, second_address, second_type) = second_indexes.next().unwrap();
+                let key = if first_type == second_type {
+                    let first_view = View::new(first, address.clone());
+                    let second_view = View::new(second, second_address);
+                    if let Some(key) = first_differing_key(&first_view, &second_view) {
+                        Some(key)
+                    } else {
+                        continue;
+                    }
+                } else {
+                    None
+                };
+                return Some(SnapshotsDiff {
+                    full_name,
+                    address,
+                    key,
+                });
+            }
+        }
+    }
+}
+
+/// Returns the first differing key of two views, or `None` if the views have the same contents.
+fn first_differing_key(
+    first: &View<&dyn Snapshot>,
+    second: &View<&dyn Snapshot>,
+) -> Option<Vec<u8>> {
+    let mut first_iter = first.iter::<_, Vec<u8>, Vec<u8>>(&());
+    let mut second_iter = second.iter::<_, Vec<u8>, Vec<u8>>(&());
+    loop {
+        let key = match (first_iter.next(), second_iter.next()) {
+            (None, None) => return None,
+            (Some((key, _)), None) | (None, Some((key, _))) => key,
+            (Some((first_key, first_value)), Some((second_key, second_value))) => {
+                if first_key != second_key {
+                    first_key.min(second_key)
+                } else if first_value != second_value {
+                    first_key
+                } else {
+                    continue;
+                }
+            }
+        };
+        return Some(key);
+    }
+}
+
+/// Constraint checked by a [`ConstraintSet`].
+///
+/// [`ConstraintSet`]: struct.ConstraintSet.html
+type Constraint = Box<dyn Fn(&dyn Snapshot) -> Result<(), String> + Send + Sync>;
+
+/// Set of invariants spanning one or more indexes, which must hold in the database
+/// after each merge, e.g., that the sum of wallet balances equals the total supply.
+///
+/// Constraints are checked against the post-merge state of the database by
+/// [`Database::merge_validated`]. Each constraint is a closure receiving a snapshot
+/// and returning a human-readable description of the violation, if any.
+///
+/// [`Database::merge_validated`]: ../trait.Database.html#method.merge_validated
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, validation::ConstraintSet, Database, TemporaryDB};
+/// let mut constraints = ConstraintSet::new();
+/// constraints.add(|snapshot| {
+///     let total = snapshot.get_entry::<_, u64>("total").get().unwrap_or(0);
+///     let sum: u64 = snapshot.get_map::<_, str, u64>("balances").values().sum();
+///     if sum == total {
+///         Ok(())
+///     } else {
+///         Err(format!("sum of balances {} differs from total {}", sum, total))
+///     }
+/// });
+///
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// fork.get_map::<_, str, u64>("balances").put("alice", 10);
+/// fork.get_entry("total").set(10_u64);
+/// db.merge_validated(fork.into_patch(), &constraints).unwrap();
+///
+/// // This patch violates the constraint and is not merged.
+/// let fork = db.fork();
+/// fork.get_map::<_, str, u64>("balances").put("bob", 5);
+/// assert!(db.merge_validated(fork.into_patch(), &constraints).is_err());
+/// assert!(!db.snapshot().get_map::<_, str, u64>("balances").contains("bob"));
+/// ```
+#[derive(Default)]
+pub struct ConstraintSet {
+    constraints: Vec<Constraint>,
+}
+
+impl fmt::Debug for ConstraintSet {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ConstraintSet")
+            .field("len", &self.constraints.len())
+            .finish()
+    }
+}
+
+impl ConstraintSet {
+    /// Creates an empty constraint set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a constraint to the set.
+    pub fn add<F>(&mut self, constraint: F) -> &mut Self
+    where
+        F: Fn(&dyn Snapshot) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.constraints.push(Box::new(constraint));
+        self
+    }
+
+    /// Returns the number of constraints in the set.
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Checks if the set contains no constraints.
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    /// Checks all constraints against the `snapshot` in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns the description of the first violated constraint.
+    pub fn check(&self, snapshot: &dyn Snapshot) -> Result<(), String> {
+        self.constraints
+            .iter()
+            .try_for_each(|constraint| constraint(snapshot))
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut output, byte| {
+        write!(output, "{:02x}", byte).unwrap();
+        output
+    })
+}
+
 #[cfg(test)]
 mod test {
     use assert_matches::assert_matches;
 
+    use super::{assert_key_ordering, snapshots_diff, snapshots_eq, ConstraintSet};
     use crate::{
-        access::{AccessErrorKind, FromAccess},
+        access::{AccessErrorKind, CopyAccessExt, FromAccess},
         Database, ListIndex, TemporaryDB,
     };
 
@@ -96,4 +456,139 @@ mod test {
         .unwrap_err();
         assert_matches!(e.kind, AccessErrorKind::InvalidCharsInName { .. });
     }
+
+    #[test]
+    fn key_ordering_for_builtin_keys() {
+        assert_key_ordering::<u64>(&[0, 1, 255, 256, 65_536, u64::MAX]);
+        assert_key_ordering::<i32>(&[i32::MIN, -256, -1, 0, 1, 255, 256, i32::MAX]);
+        assert_key_ordering(&[
+            "".to_owned(),
+            "a".to_owned(),
+            "a".to_owned(),
+            "ab".to_owned(),
+        ]);
+        assert_key_ordering::<u8>(&[]);
+        assert_key_ordering(&[vec![], vec![0_u8], vec![0, 255], vec![1]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "key ordering at position 1")]
+    fn key_ordering_mismatch() {
+        assert_key_ordering::<u32>(&[1, 3, 2]);
+    }
+
+    #[test]
+    fn comparing_snapshots() {
+        let first_db = TemporaryDB::new();
+        let fork = first_db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2, 3]);
+        fork.get_map(("group", &1_u8)).put(&1_u64, "foo".to_owned());
+        fork.get_entry("entry").set(5_u8);
+        first_db.merge(fork.into_patch()).unwrap();
+
+        // Create the same indexes in a different order, so that their IDs differ.
+        let second_db = TemporaryDB::new();
+        let fork = second_db.fork();
+        fork.get_entry("entry").set(5_u8);
+        fork.get_map(("group", &1_u8)).put(&1_u64, "foo".to_owned());
+        fork.get_list("list").extend(vec![1_u32, 2, 3]);
+        second_db.merge(fork.into_patch()).unwrap();
+
+        let first = first_db.snapshot();
+        let second = second_db.snapshot();
+        assert!(snapshots_eq(&*first, &*second, None));
+        assert!(snapshots_eq(&*second, &*first, Some("gr")));
+
+        // Change the value in a group index.
+        let fork = second_db.fork();
+        fork.get_map(("group", &1_u8)).put(&2_u64, "bar".to_owned());
+        second_db.merge(fork.into_patch()).unwrap();
+        let second = second_db.snapshot();
+        let diff = snapshots_diff(&*first, &*second, None).unwrap();
+        assert_eq!(diff.full_name, b"group\0\x01");
+        assert_eq!(diff.address.name, "group");
+        assert_eq!(diff.key, Some(2_u64.to_be_bytes().to_vec()));
+        assert!(snapshots_eq(&*first, &*second, Some("list")));
+
+        // Add an index to the first database.
+        let fork = first_db.fork();
+        fork.get_entry("entry2").set(0_u8);
+        first_db.merge(fork.into_patch()).unwrap();
+        let first = first_db.snapshot();
+        let diff = snapshots_diff(&*first, &*second, Some("entry")).unwrap();
+        assert_eq!(diff.full_name, b"entry2");
+        assert_eq!(diff.key, None);
+        assert!(snapshots_eq(&*first, &*second, Some("list")));
+
+        // Indexes with different types are not equal.
+        let fork = first_db.fork();
+        fork.get_list::<_, u8>("empty");
+        first_db.merge(fork.into_patch()).unwrap();
+        let fork = second_db.fork();
+        fork.get_key_set::<_, u8>("empty");
+        second_db.merge(fork.into_patch()).unwrap();
+        let (first, second) = (first_db.snapshot(), second_db.snapshot());
+        let diff = snapshots_diff(&*first, &*second, Some("empty")).unwrap();
+        assert_eq!(diff.full_name, b"empty");
+        assert_eq!(diff.key, None);
+    }
+
+    #[test]
+    fn merging_with_constraints() {
+        let mut constraints = ConstraintSet::new();
+        constraints
+            .add(|snapshot| {
+                let total = snapshot.get_entry::<_, u64>("total").get().unwrap_or(0);
+                let sum: u64 = snapshot.get_map::<_, u8, u64>("balances").values().sum();
+                if sum == total {
+                    Ok(())
+                } else {
+                    Err(format!("sum {} != total {}", sum, total))
+                }
+            })
+            .add(|snapshot| {
+                if snapshot.get_list::<_, u8>("log").len() <= 2 {
+                    Ok(())
+                } else {
+                    Err("log is too long".to_owned())
+                }
+            });
+        assert_eq!(constraints.len(), 2);
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_map("balances").put(&1_u8, 10_u64);
+        fork.get_entry("total").set(10_u64);
+        fork.get_list("log").push(1_u8);
+        db.merge_validated(fork.into_patch(), &constraints).unwrap();
+
+        // Constraints are checked against the state with the patch applied,
+        // including changes made in the previous merge.
+        let fork = db.fork();
+        fork.get_map("balances").put(&2_u8, 5_u64);
+        let err = db
+            .merge_validated(fork.into_patch(), &constraints)
+            .unwrap_err();
+        assert!(err.to_string().contains("sum 15 != total 10"), "{}", err);
+        let snapshot = db.snapshot();
+        assert!(!snapshot.get_map::<_, u8, u64>("balances").contains(&2));
+
+        // The first violated constraint is reported.
+        let fork = db.fork();
+        fork.get_list("log").extend(vec![2_u8, 3]);
+        fork.get_map("balances").put(&2_u8, 5_u64);
+        let err = db
+            .merge_validated(fork.into_patch(), &constraints)
+            .unwrap_err();
+        assert!(err.to_string().contains("sum 15"), "{}", err);
+
+        let fork = db.fork();
+        fork.get_map("balances").put(&2_u8, 5_u64);
+        fork.get_entry("total").set(15_u64);
+        fork.get_list("log").push(2_u8);
+        db.merge_validated(fork.into_patch(), &constraints).unwrap();
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get_entry::<_, u64>("total").get(), Some(15));
+        assert_eq!(snapshot.get_list::<_, u8>("log").len(), 2);
+    }
 }
@@ -1,6 +1,6 @@
 //! A definition of `BinaryValue` trait and implementations for common types.
 
-use std::{borrow::Cow, io::Read};
+use std::{borrow::Cow, convert::TryFrom, io::Read};
 
 use anyhow::{self, format_err};
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
@@ -114,6 +114,62 @@ impl BinaryValue for () {
     }
 }
 
+/// Appends a value to the buffer, prefixing it with its length as a little-endian `u32`.
+fn write_length_prefixed(buffer: &mut Vec<u8>, value: &[u8]) {
+    let len = u32::try_from(value.len()).expect("Tuple element is too large");
+    buffer.extend_from_slice(&len.to_le_bytes());
+    buffer.extend_from_slice(value);
+}
+
+/// Reads a length-prefixed value from the buffer, advancing the buffer past the value.
+fn read_length_prefixed<'a>(buffer: &mut &'a [u8]) -> anyhow::Result<&'a [u8]> {
+    let len = buffer.read_u32::<LittleEndian>()? as usize;
+    if buffer.len() < len {
+        return Err(format_err!(
+            "Tuple element length {} exceeds remaining {} bytes",
+            len,
+            buffer.len()
+        ));
+    }
+    let (value, rest) = buffer.split_at(len);
+    *buffer = rest;
+    Ok(value)
+}
+
+macro_rules! impl_binary_value_tuple {
+    ($($param:ident : $index:tt),+) => {
+        /// Each tuple element is prefixed with its length encoded as a little-endian `u32`,
+        /// so elements with variable length are decoded unambiguously.
+        impl<$($param: BinaryValue),+> BinaryValue for ($($param,)+) {
+            fn to_bytes(&self) -> Vec<u8> {
+                let mut buffer = vec![];
+                $(write_length_prefixed(&mut buffer, &self.$index.to_bytes());)+
+                buffer
+            }
+
+            fn from_bytes(bytes: Cow<'_, [u8]>) -> anyhow::Result<Self> {
+                let mut buffer = bytes.as_ref();
+                let value = ($(
+                    $param::from_bytes(Cow::Borrowed(read_length_prefixed(&mut buffer)?))?,
+                )+);
+                if !buffer.is_empty() {
+                    return Err(format_err!(
+                        "Unexpected {} trailing bytes after tuple",
+                        buffer.len()
+                    ));
+                }
+                Ok(value)
+            }
+        }
+    };
+}
+
+impl_binary_value_tuple! { A: 0, B: 1 }
+impl_binary_value_tuple! { A: 0, B: 1, C: 2 }
+impl_binary_value_tuple! { A: 0, B: 1, C: 2, D: 3 }
+impl_binary_value_tuple! { A: 0, B: 1, C: 2, D: 3, E: 4 }
+impl_binary_value_tuple! { A: 0, B: 1, C: 2, D: 3, E: 4, F: 5 }
+
 #[allow(clippy::use_self)] // false positives
 impl BinaryValue for bool {
     fn to_bytes(&self) -> Vec<u8> {
@@ -199,6 +255,69 @@ impl BinaryValue for Decimal {
     }
 }
 
+/// Header byte of values compressed with Zstandard.
+#[cfg(feature = "zstd")]
+const ZSTD_HEADER: u8 = 1;
+
+/// Wrapper compressing the serialized inner value with [Zstandard] before storing it.
+/// Compression is opt-in per index: use `CompressedValue<V>` as the value type of the index,
+/// e.g., `MapIndex<_, K, CompressedValue<V>>`. This is useful for large values compressing
+/// well, such as JSON blobs, and is independent of block compression in `RocksDB`.
+///
+/// The serialized value starts with a single byte recording the compression algorithm,
+/// followed by the compressed output of `V::to_bytes()`.
+///
+/// Available with the `zstd` feature.
+///
+/// [Zstandard]: https://facebook.github.io/zstd/
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::{access::CopyAccessExt, CompressedValue, Database, TemporaryDB};
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// let mut map = fork.get_map("blobs");
+/// let blob = "{\"key\": \"value\"}".repeat(100);
+/// map.put(&1_u64, CompressedValue(blob.clone()));
+/// assert_eq!(map.get(&1).map(CompressedValue::into_inner), Some(blob));
+/// ```
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CompressedValue<V>(pub V);
+
+#[cfg(feature = "zstd")]
+impl<V> CompressedValue<V> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> V {
+        self.0
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<V: BinaryValue> BinaryValue for CompressedValue<V> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let bytes = self.0.to_bytes();
+        let mut buffer = vec![ZSTD_HEADER];
+        zstd::stream::copy_encode(bytes.as_slice(), &mut buffer, 0)
+            .expect("Failed to compress value");
+        buffer
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> anyhow::Result<Self> {
+        let (&header, compressed) = bytes
+            .split_first()
+            .ok_or_else(|| format_err!("Compressed value is empty"))?;
+        match header {
+            ZSTD_HEADER => {
+                let bytes = zstd::stream::decode_all(compressed)?;
+                V::from_bytes(bytes.into()).map(Self)
+            }
+            _ => Err(format_err!("Unknown compression algorithm: {}", header)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Debug;
@@ -273,6 +392,44 @@ mod tests {
         <bool as BinaryValue>::from_bytes(bytes.into()).unwrap();
     }
 
+    #[test]
+    fn test_binary_form_tuples() {
+        let values = [
+            (1_u32, "foo".to_owned(), vec![1_u8, 2]),
+            (0, String::new(), vec![]),
+        ];
+        assert_round_trip_eq(&values);
+        let values = [(vec![], 2_u8), (vec![3_u8; 10], 255)];
+        assert_round_trip_eq(&values);
+        let values = [(
+            true,
+            "a".to_owned(),
+            -1_i64,
+            vec![0_u8; 3],
+            String::new(),
+            u128::max_value(),
+        )];
+        assert_round_trip_eq(&values);
+
+        // Variable-length elements are separated unambiguously.
+        let first = ("ab".to_owned(), "c".to_owned()).to_bytes();
+        let second = ("a".to_owned(), "bc".to_owned()).to_bytes();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_binary_form_tuple_incorrect() {
+        let mut bytes = (1_u8, 2_u8).to_bytes();
+        bytes.push(0);
+        let err = <(u8, u8)>::from_bytes(bytes.into()).unwrap_err();
+        assert!(err.to_string().contains("trailing bytes"), "{}", err);
+
+        let bytes = (1_u8, vec![1_u8, 2, 3]).to_bytes();
+        let truncated = bytes[..bytes.len() - 1].to_vec();
+        let err = <(u8, Vec<u8>)>::from_bytes(truncated.into()).unwrap_err();
+        assert!(err.to_string().contains("exceeds"), "{}", err);
+    }
+
     #[test]
     fn test_binary_form_string() {
         let values: Vec<_> = ["", "e", "2", "hello"]
@@ -318,4 +475,25 @@ mod tests {
         ];
         assert_round_trip_eq(&values);
     }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_binary_form_compressed_value() {
+        use super::CompressedValue;
+
+        let values = [
+            CompressedValue(String::new()),
+            CompressedValue("foo".to_owned()),
+            CompressedValue("bar".repeat(1_000)),
+        ];
+        assert_round_trip_eq(&values);
+        let bytes = values[2].to_bytes();
+        assert!(bytes.len() < 100);
+        assert_eq!(bytes[0], 1);
+
+        let err = CompressedValue::<String>::from_bytes(vec![].into()).unwrap_err();
+        assert!(err.to_string().contains("Compressed value is empty"));
+        let err = CompressedValue::<String>::from_bytes(vec![5, 0].into()).unwrap_err();
+        assert!(err.to_string().contains("Unknown compression algorithm: 5"));
+    }
 }
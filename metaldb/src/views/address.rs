@@ -1,6 +1,10 @@
-use std::{borrow::Cow, num::NonZeroU64};
+use std::{borrow::Cow, num::NonZeroU64, str::FromStr};
 
-use crate::BinaryKey;
+use crate::{
+    access::{AccessError, AccessErrorKind},
+    validation::check_index_valid_full_name,
+    BinaryKey,
+};
 
 pub fn key_bytes<K: BinaryKey + ?Sized>(key: &K) -> Vec<u8> {
     concat_keys!(key)
@@ -48,6 +52,8 @@ pub struct IndexAddress {
     pub(super) name: String,
     pub(super) id_in_group: Option<Vec<u8>>,
     pub(super) in_migration: bool,
+    pub(super) prefix_len: Option<usize>,
+    pub(super) key_hashing: bool,
 }
 
 impl IndexAddress {
@@ -57,6 +63,8 @@ impl IndexAddress {
             name: root.into(),
             id_in_group: None,
             in_migration: false,
+            prefix_len: None,
+            key_hashing: false,
         }
     }
 
@@ -104,11 +112,33 @@ impl IndexAddress {
     /// assert_eq!(suffixed.name(), "foo.suffix");
     /// ```
     pub fn append_name(self, suffix: &str) -> Self {
+        self.append_name_with_separator(".", suffix)
+    }
+
+    /// Appends a name part to `IndexAddress` using the specified separator. This is useful
+    /// for interoperability with databases using other separators, such as a slash `/`.
+    ///
+    /// Only dot `.` and slash `/` separators are guaranteed to produce valid index names.
+    /// Note that namespace-aware functionality, such as [migrations] and [`Prefixed`] accesses,
+    /// recognizes only dot separators.
+    ///
+    /// [migrations]: migration/index.html
+    /// [`Prefixed`]: access/struct.Prefixed.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::IndexAddress;
+    /// let addr = IndexAddress::from_root("foo");
+    /// let suffixed = addr.append_name_with_separator("/", "suffix");
+    /// assert_eq!(suffixed.name(), "foo/suffix");
+    /// ```
+    pub fn append_name_with_separator(self, separator: &str, suffix: &str) -> Self {
         let name = if self.name.is_empty() {
             suffix.to_owned()
         } else {
             // Because `concat` is faster than `format!("...")` in all cases.
-            [self.name(), ".", suffix].concat()
+            [self.name(), separator, suffix].concat()
         };
 
         Self { name, ..self }
@@ -128,6 +158,112 @@ impl IndexAddress {
         }
     }
 
+    /// Sets the length of key prefixes used to speed up prefix iteration in the index,
+    /// e.g., with [`MapIndex::iter_prefix`]. This is useful for grouped maps with keys
+    /// consisting of a fixed-size group identifier and an item identifier.
+    ///
+    /// The prefix length is a hint to the database backend. `RocksDB` configures a fixed-length
+    /// prefix extractor together with prefix Bloom filters for the column family
+    /// storing the index; iteration over keys with a prefix of at least `prefix_len` bytes
+    /// then skips data not containing the prefix. Other backends ignore the hint.
+    ///
+    /// The prefix length is fixed when the column family is created, i.e., when the first index
+    /// with the same name is written to the database. Since indexes in a group share
+    /// the column family, the prefix length applies to the entire group, and prefix lengths
+    /// specified for indexes created afterwards are ignored.
+    ///
+    /// [`MapIndex::iter_prefix`]: indexes/struct.MapIndex.html#method.iter_prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, DBOptions, IndexAddress, RocksDB};
+    /// # use tempfile::TempDir;
+    /// let temp_dir = TempDir::new().unwrap();
+    /// let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+    /// let fork = db.fork();
+    /// let addr = IndexAddress::from_root("orders")
+    ///     .append_key(&1_u8)
+    ///     .with_prefix_len(4);
+    /// let mut orders = fork.get_map(addr);
+    /// // Keys are composed of a 4-byte customer ID and a 4-byte order ID.
+    /// let key = |customer: u32, order: u32| u64::from(customer) << 32 | u64::from(order);
+    /// orders.put(&key(5, 1), "foo".to_owned());
+    /// orders.put(&key(5, 2), "bar".to_owned());
+    /// orders.put(&key(6, 1), "baz".to_owned());
+    ///
+    /// let customer_orders: Vec<String> = orders.iter_prefix(&5_u32).map(|(_, v)| v).collect();
+    /// assert_eq!(customer_orders, ["foo", "bar"]);
+    /// ```
+    pub fn with_prefix_len(self, prefix_len: usize) -> Self {
+        Self {
+            prefix_len: Some(prefix_len),
+            ..self
+        }
+    }
+
+    /// Returns the length of key prefixes set with [`with_prefix_len`].
+    ///
+    /// [`with_prefix_len`]: #method.with_prefix_len
+    pub fn prefix_len(&self) -> Option<usize> {
+        self.prefix_len
+    }
+
+    /// Makes the index store its keys prefixed by a short hash of the key. This spreads
+    /// monotonically increasing keys (e.g., timestamps) uniformly across the keyspace,
+    /// which avoids write hotspots in the database backend.
+    ///
+    /// Key hashing is supported for [`MapIndex`] and [`KeySetIndex`]; accessing other index
+    /// types with this option results in an error. Point operations (getting, checking
+    /// presence, putting and removing a key) hash the key transparently. Iteration over
+    /// the whole index (`iter`, `keys`, `values`) and `MapIndex::drain` are supported as well,
+    /// but yield entries in an unspecified order.
+    ///
+    /// Since the stored keys are no longer ordered, methods relying on the key order panic
+    /// for such indexes. These are `first`, `last`, `iter_from`, `keys_from`, `values_from`,
+    /// `iter_after`, `iter_prefix`, `keys_range` and `clear_range` for [`MapIndex`]
+    /// (including `iter_from` of the map returned by `MapIndex::map_values`), and `iter_from`
+    /// and `range` for [`KeySetIndex`].
+    ///
+    /// Similarly to index types, key hashing is fixed when the index is created;
+    /// the option is ignored when accessing an existing index.
+    ///
+    /// [`MapIndex`]: indexes/struct.MapIndex.html
+    /// [`KeySetIndex`]: indexes/struct.KeySetIndex.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use metaldb::{access::CopyAccessExt, Database, IndexAddress, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// let addr = IndexAddress::from_root("events").with_key_hashing();
+    /// let mut events = fork.get_map(addr);
+    /// for timestamp in 1_000_u64..1_010 {
+    ///     events.put(&timestamp, timestamp.to_string());
+    /// }
+    /// assert_eq!(events.get(&1_005), Some("1005".to_owned()));
+    /// assert_eq!(events.iter().count(), 10);
+    /// drop(events);
+    ///
+    /// // The option does not need to be repeated for existing indexes.
+    /// let events = fork.get_map::<_, u64, String>("events");
+    /// assert!(events.contains(&1_000));
+    /// ```
+    pub fn with_key_hashing(self) -> Self {
+        Self {
+            key_hashing: true,
+            ..self
+        }
+    }
+
+    /// Returns `true` if key hashing was requested with [`with_key_hashing`].
+    ///
+    /// [`with_key_hashing`]: #method.with_key_hashing
+    pub fn key_hashing(&self) -> bool {
+        self.key_hashing
+    }
+
     pub(crate) fn set_in_migration(&mut self) {
         self.in_migration = true;
     }
@@ -148,6 +284,28 @@ impl IndexAddress {
         }
     }
 
+    /// Restores the address from the fully qualified name obtained with `fully_qualified_name`.
+    pub(crate) fn from_fully_qualified_name(qualified_name: &[u8]) -> Self {
+        let (in_migration, qualified_name) = match qualified_name.split_first() {
+            Some((&MIGRATION_CHAR, rest)) => (true, rest),
+            _ => (false, qualified_name),
+        };
+        let (name, id_in_group) = match qualified_name.iter().position(|&b| b == SEPARATOR_CHAR) {
+            Some(pos) => (
+                &qualified_name[..pos],
+                Some(qualified_name[pos + 1..].to_vec()),
+            ),
+            None => (qualified_name, None),
+        };
+        Self {
+            name: String::from_utf8_lossy(name).into_owned(),
+            id_in_group,
+            in_migration,
+            prefix_len: None,
+            key_hashing: false,
+        }
+    }
+
     /// Returns the common prefix of fully qualified names for the child indexes.
     pub(crate) fn qualified_prefix(&self) -> Vec<u8> {
         let mut prefix = self.fully_qualified_name();
@@ -220,6 +378,71 @@ impl IndexAddress {
     }
 }
 
+/// Separator between the name and the hex-encoded key in the string form of an address.
+const KEY_SEPARATOR: char = ':';
+
+/// Parses an address from a string, which is useful for accepting index addresses
+/// in CLI tools.
+///
+/// The string consists of a dotted name, optionally followed by a colon `:` and
+/// the hex-encoded key of the index within a [group]. The name is validated according
+/// to the same rules as when an index is accessed; e.g., it must be non-empty
+/// and contain only `[0-9a-zA-Z]`, `_`, `-` and `.` chars.
+///
+/// [group]: indexes/group/struct.Group.html
+///
+/// # Examples
+///
+/// ```
+/// # use metaldb::IndexAddress;
+/// let addr: IndexAddress = "wallets.history".parse().unwrap();
+/// assert_eq!(addr, IndexAddress::from_root("wallets.history"));
+///
+/// let addr: IndexAddress = "wallets.history:0000002a".parse().unwrap();
+/// assert_eq!(addr, IndexAddress::from(("wallets.history", &42_u32)));
+///
+/// assert!("wallets history".parse::<IndexAddress>().is_err());
+/// assert!("wallets.history:xyz".parse::<IndexAddress>().is_err());
+/// ```
+impl FromStr for IndexAddress {
+    type Err = AccessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, key) = match s.find(KEY_SEPARATOR) {
+            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+            None => (s, None),
+        };
+
+        let mut addr = Self::from_root(name);
+        if let Err(kind) = check_index_valid_full_name(name) {
+            return Err(AccessError { addr, kind });
+        }
+        if let Some(key) = key {
+            if let Some(key) = decode_hex(key) {
+                addr.id_in_group = Some(key);
+            } else {
+                let kind = AccessErrorKind::Custom(anyhow::anyhow!(
+                    "Invalid key {:?}: expected an even number of hex digits",
+                    key
+                ));
+                return Err(AccessError { addr, kind });
+            }
+        }
+        Ok(addr)
+    }
+}
+
+/// Decodes a hex string. Returns `None` if the string is not a valid hex encoding.
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
 impl From<&str> for IndexAddress {
     fn from(name: &str) -> Self {
         Self::from_root(name)
@@ -238,6 +461,8 @@ impl<'a, K: BinaryKey + ?Sized> From<(&'a str, &'a K)> for IndexAddress {
             name: name.to_owned(),
             id_in_group: Some(key_bytes(key)),
             in_migration: false,
+            prefix_len: None,
+            key_hashing: false,
         }
     }
 }
@@ -273,12 +498,15 @@ impl ResolvedAddress {
         Self::new(name, None)
     }
 
-    pub(crate) fn id_to_bytes(&self) -> Option<[u8; 8]> {
+    /// Returns the byte representation of the `id`, which is used to prefix keys of the view
+    /// if several views share a column family.
+    pub fn id_to_bytes(&self) -> Option<[u8; 8]> {
         self.id.map(|id| id.get().to_le_bytes())
     }
 
-    /// Returns `key` prefixed by the `id`.
-    pub(crate) fn keyed<'k>(&self, key: &'k [u8]) -> Cow<'k, [u8]> {
+    /// Returns `key` prefixed by the `id`. Backends may use this method to store
+    /// several views in a single column family.
+    pub fn keyed<'k>(&self, key: &'k [u8]) -> Cow<'k, [u8]> {
         match self.id {
             None => Cow::Borrowed(key),
             Some(id) => {
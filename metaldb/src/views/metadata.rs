@@ -1,4 +1,4 @@
-use anyhow::{ensure, format_err};
+use anyhow::{bail, ensure, format_err};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
@@ -12,7 +12,10 @@ use crate::{
 };
 
 /// Name of the column family used to store `IndexesPool`.
-const INDEXES_POOL_NAME: &str = "__INDEXES_POOL__";
+pub(crate) const INDEXES_POOL_NAME: &str = "__INDEXES_POOL__";
+/// Name of the column family used to store key prefix lengths of column families
+/// set with `IndexAddress::with_prefix_len`.
+pub(crate) const PREFIX_LENGTHS_NAME: &str = "__PREFIX_LENGTHS__";
 
 /// Type of an index supported by `metaldb`.
 ///
@@ -59,6 +62,8 @@ impl TryFrom<u32> for IndexType {
 
 /// Index state attribute tag.
 const INDEX_STATE_TAG: u32 = 0;
+/// Tag of the attribute marking indexes with hashed keys. The attribute has no value.
+const KEY_HASHING_TAG: u32 = 2;
 
 /// A type that can be (de)serialized as a metadata value.
 pub trait BinaryAttribute: Sized {
@@ -132,6 +137,7 @@ pub struct IndexMetadata<V = Vec<u8>> {
     // calls `IndexState::unset()`. `None` option does not occupy space in the metadata
     // and can therefore be preferable to explicit "default" option.
     state: Option<V>,
+    key_hashing: bool,
 }
 
 impl<V> BinaryValue for IndexMetadata<V>
@@ -143,6 +149,9 @@ where
         if let Some(ref state) = self.state {
             capacity += mem::size_of_val(&INDEX_STATE_TAG) + mem::size_of::<u32>() + state.size();
         }
+        if self.key_hashing {
+            capacity += mem::size_of_val(&KEY_HASHING_TAG) + mem::size_of::<u32>();
+        }
         let mut buf = Vec::with_capacity(capacity);
 
         buf.write_u64::<LittleEndian>(self.identifier.get())
@@ -155,6 +164,10 @@ where
             buf.write_u32::<LittleEndian>(state.size() as u32).unwrap();
             state.write(&mut buf);
         }
+        if self.key_hashing {
+            buf.write_u32::<LittleEndian>(KEY_HASHING_TAG).unwrap();
+            buf.write_u32::<LittleEndian>(0).unwrap();
+        }
         buf
     }
 
@@ -167,31 +180,31 @@ where
         let index_type = IndexType::try_from(index_type)
             .map_err(|_| format_err!("Unknown index type: {}", index_type))?;
 
-        if bytes.is_empty() {
-            // There are no tags in the metadata, correspondingly, no index state.
-            return Ok(Self {
-                identifier,
-                index_type,
-                state: None,
-            });
+        let mut state = None;
+        let mut key_hashing = false;
+        // Reads attributes in TLV (tag, length, value) form.
+        while !bytes.is_empty() {
+            let tag = bytes.read_u32::<LittleEndian>()?;
+            let len = bytes.read_u32::<LittleEndian>()? as usize;
+            ensure!(
+                bytes.len() >= len,
+                "Attribute with tag {} is too short",
+                tag
+            );
+            let (value, rest) = bytes.split_at(len);
+            match tag {
+                INDEX_STATE_TAG => state = Some(V::read(value)?),
+                KEY_HASHING_TAG => key_hashing = true,
+                _ => bail!("Attribute with unknown tag: {}", tag),
+            }
+            bytes = rest;
         }
 
-        // Reads index state in TLV (tag, length, value) form.
-        let state_tag = bytes.read_u32::<LittleEndian>()?;
-        let state_len = bytes.read_u32::<LittleEndian>()? as usize;
-
-        ensure!(
-            state_tag == INDEX_STATE_TAG,
-            "Attribute with unknown tag: {}",
-            state_tag
-        );
-        ensure!(bytes.len() >= state_len, "Index state is too short");
-
-        let state_bytes = &bytes[0..state_len];
         Ok(Self {
             identifier,
             index_type,
-            state: Some(V::read(state_bytes)?),
+            state,
+            key_hashing,
         })
     }
 }
@@ -210,6 +223,11 @@ impl<V> IndexMetadata<V> {
     pub fn identifier(&self) -> NonZeroU64 {
         self.identifier
     }
+
+    /// Returns `true` if keys of the index are hashed. See `IndexAddress::with_key_hashing`.
+    pub fn key_hashing(&self) -> bool {
+        self.key_hashing
+    }
 }
 
 #[allow(clippy::use_self)] // false positive
@@ -228,6 +246,7 @@ impl IndexMetadata {
                     );
                 })
             }),
+            key_hashing: self.key_hashing,
         }
     }
 }
@@ -292,6 +311,24 @@ impl<T: RawAccess> IndexesPool<T> {
         self.0.get(index_name)
     }
 
+    /// Returns fully qualified names, resolved addresses and types of all indexes
+    /// with the fully qualified name starting with the specified `prefix`, in the order
+    /// of their full names.
+    pub(crate) fn indexes_by_prefix(
+        &self,
+        prefix: &[u8],
+    ) -> impl Iterator<Item = (Vec<u8>, ResolvedAddress, IndexType)> + '_ {
+        // The empty key is occupied by the pool length; index names are never empty.
+        let from: &[u8] = if prefix.is_empty() { &[0] } else { prefix };
+        self.0
+            .iter_from::<_, [u8], Vec<u8>, IndexMetadata>(&prefix.to_vec(), from)
+            .map(|(full_name, metadata)| {
+                let (name, _) = IndexAddress::parse_fully_qualified_name(&full_name, 0);
+                let resolved = ResolvedAddress::new(name, Some(metadata.identifier));
+                (full_name, resolved, metadata.index_type)
+            })
+    }
+
     fn set_len(&mut self, len: u64) {
         self.0.put_or_forget(&(), len);
     }
@@ -304,6 +341,7 @@ impl<T: RawAccess> IndexesPool<T> {
         &mut self,
         index_name: &[u8],
         index_type: IndexType,
+        key_hashing: bool,
     ) -> (IndexMetadata<V>, bool)
     where
         V: BinaryAttribute,
@@ -313,6 +351,7 @@ impl<T: RawAccess> IndexesPool<T> {
             identifier: NonZeroU64::new(len + 1).unwrap(),
             index_type,
             state: None,
+            key_hashing,
         };
         let is_phantom = !self.0.put_or_forget(index_name, metadata.to_bytes());
         self.set_len(len + 1);
@@ -372,6 +411,15 @@ impl<T: RawAccessMut> IndexesPool<T> {
         })
     }
 
+    /// Moves metadata of an index to the specified full name. Returns the moved metadata,
+    /// or `None` if there is no index with the `from` full name.
+    pub(crate) fn rename_index(&mut self, from: &[u8], to: &[u8]) -> Option<IndexMetadata> {
+        let metadata = self.index_metadata(from)?;
+        self.0.remove(from);
+        self.0.put(to, metadata);
+        Some(metadata)
+    }
+
     /// Removes indexes which address starts from the specified `prefix` (i.e., which can be
     /// obtained from the prefix by calling `append_key`).
     ///
@@ -384,6 +432,31 @@ impl<T: RawAccessMut> IndexesPool<T> {
         self.remove_by_prefix(&prefix, |_| name.to_owned())
     }
 
+    /// Removes indexes in the group with the specified address, that is, indexes which address
+    /// can be obtained from the group address by calling `append_key`.
+    ///
+    /// # Return value
+    ///
+    /// Returns resolved addresses of the removed indexes.
+    pub(crate) fn remove_group(&mut self, group: &IndexAddress) -> Vec<ResolvedAddress> {
+        let name = group.name();
+        let prefix = group.qualified_prefix();
+        self.remove_by_prefix(&prefix, |_| name.to_owned())
+    }
+
+    /// Removes indexes in the specified `namespace`, that is, indexes with the name part
+    /// of the address starting with `namespace` followed by a dot.
+    ///
+    /// # Return value
+    ///
+    /// Returns resolved addresses of the removed indexes.
+    pub(crate) fn remove_namespace(&mut self, namespace: &str) -> Vec<ResolvedAddress> {
+        let prefix = [namespace, "."].concat().into_bytes();
+        self.remove_by_prefix(&prefix, |key| {
+            IndexAddress::parse_fully_qualified_name(key, prefix.len()).0
+        })
+    }
+
     /// Removes views with the full name starting with the specified prefix. The `extract_name`
     /// argument provides a way to map from a full name to the name of the column family
     /// where the view is stored.
@@ -549,24 +622,42 @@ where
             });
         }
 
+        if index_address.key_hashing && !matches!(index_type, IndexType::Map | IndexType::KeySet) {
+            return Err(AccessError {
+                kind: AccessErrorKind::UnsupportedKeyHashing { index_type },
+                addr: index_address.clone(),
+            });
+        }
+
         // Actual name.
         let index_name = index_address.name().to_owned();
         // Full name for internal usage.
         let index_full_name = index_address.fully_qualified_name();
 
         let mut pool = IndexesPool::new(index_access.clone());
-        let (metadata, is_phantom) = pool.index_metadata(&index_full_name).map_or_else(
-            || pool.create_index_metadata(&index_full_name, index_type),
+        let existing_metadata = pool.index_metadata(&index_full_name);
+        let is_created = existing_metadata.is_none();
+        let (metadata, is_phantom) = existing_metadata.map_or_else(
+            || pool.create_index_metadata(&index_full_name, index_type, index_address.key_hashing),
             |metadata| (metadata, false),
         );
 
+        if let Some(prefix_len) = index_address.prefix_len.filter(|_| is_created) {
+            let address = ResolvedAddress::system(PREFIX_LENGTHS_NAME);
+            let mut prefix_lens = View::new(index_access.clone(), address);
+            // Only the first prefix length specified for the column family is recorded.
+            if prefix_lens.get::<_, u64>(index_name.as_str()).is_none() {
+                prefix_lens.put_or_forget(index_name.as_str(), prefix_len as u64);
+            }
+        }
+
         let real_index_type = metadata.index_type;
         let addr = ResolvedAddress::new(index_name, Some(metadata.identifier));
 
         let view = if is_phantom {
             View::new_phantom()
         } else {
-            View::new(index_access, addr)
+            View::new(index_access, addr).with_key_hashing(metadata.key_hashing)
         };
         let this = Self {
             view,
@@ -633,6 +724,7 @@ mod tests {
             identifier: NonZeroU64::new(12).unwrap(),
             index_type: IndexType::List,
             state: Some(16_u64),
+            key_hashing: false,
         };
 
         let bytes = metadata.to_bytes();
@@ -642,10 +734,22 @@ mod tests {
             identifier: NonZeroU64::new(12).unwrap(),
             index_type: IndexType::List,
             state: None::<u64>,
+            key_hashing: false,
         };
 
         let bytes = metadata.to_bytes();
         assert_eq!(IndexMetadata::from_bytes(bytes.into()).unwrap(), metadata);
+
+        for &state in &[None, Some(16_u64)] {
+            let metadata = IndexMetadata {
+                identifier: NonZeroU64::new(12).unwrap(),
+                index_type: IndexType::Map,
+                state,
+                key_hashing: true,
+            };
+            let bytes = metadata.to_bytes();
+            assert_eq!(IndexMetadata::from_bytes(bytes.into()).unwrap(), metadata);
+        }
     }
 
     #[test]
@@ -655,6 +759,7 @@ mod tests {
             identifier: NonZeroU64::new(12).unwrap(),
             index_type: IndexType::List,
             state: Some(16_u64),
+            key_hashing: false,
         };
 
         let mut bytes = metadata.to_bytes();
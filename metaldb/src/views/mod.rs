@@ -6,17 +6,25 @@ pub use self::{
     },
 };
 
+pub(crate) use self::metadata::{INDEXES_POOL_NAME, PREFIX_LENGTHS_NAME};
+
 use std::{
     borrow::{Borrow, Cow},
     fmt, iter,
     iter::Peekable,
     marker::PhantomData,
+    sync::Mutex,
 };
 
 use crate::{
-    db::{Change, ChangesMut, ChangesRef, ForkIter, ViewChanges},
+    db::{
+        last_before_with_changes, skip_removed_ranges, Change, ChangesMut, ChangesRef, ForkIter,
+        KeyRange, ViewChanges,
+    },
+    read_set::{record_read, TrackingIter},
     views::address::key_bytes,
-    BinaryKey, BinaryValue, Iter as BytesIter, Iterator as BytesIterator, Snapshot,
+    BinaryKey, BinaryValue, Iter as BytesIter, Iterator as BytesIterator, PinnedValue, ReadSet,
+    Snapshot,
 };
 
 mod address;
@@ -37,6 +45,8 @@ pub struct ViewInner<T: RawAccess> {
     address: ResolvedAddress,
     index_access: T,
     changes: T::Changes,
+    /// Whether keys are stored prefixed by their hash.
+    key_hashing: bool,
 }
 
 impl<T: RawAccess> fmt::Debug for ViewInner<T> {
@@ -54,6 +64,8 @@ pub trait ChangeSet {
     /// Provides mutable reference to changes. The implementation for a `RawAccessMut` type
     /// should always return `Some(_)`.
     fn as_mut(&mut self) -> Option<&mut ViewChanges>;
+    /// Notifies observers about a change to be recorded. The default implementation does nothing.
+    fn notify(&self, _key: &[u8], _change: &Change) {}
 }
 
 /// No-op implementation used in `Snapshot`.
@@ -82,6 +94,9 @@ impl ChangeSet for ChangesMut<'_> {
     fn as_mut(&mut self) -> Option<&mut ViewChanges> {
         Some(&mut *self)
     }
+    fn notify(&self, key: &[u8], change: &Change) {
+        ChangesMut::notify(self, key, change);
+    }
 }
 
 /// Allows to read data from the database. The data consists of a snapshot and
@@ -102,6 +117,15 @@ pub trait RawAccess: Clone {
     fn snapshot(&self) -> &dyn Snapshot;
     /// Returns changes related to specific `address` compared to the `snapshot()`.
     fn changes(&self, address: &ResolvedAddress) -> Self::Changes;
+
+    /// Returns the read set recording the entries read from the `snapshot()` via this access.
+    /// Reads are not recorded by default; see [`TrackingAccess`].
+    ///
+    /// [`TrackingAccess`]: struct.TrackingAccess.html
+    #[doc(hidden)]
+    fn tracked_reads(&self) -> Option<&Mutex<ReadSet>> {
+        None
+    }
 }
 
 /// Allows to mutate data in indexes.
@@ -168,18 +192,44 @@ impl_snapshot_access!(&'_ dyn Snapshot);
 impl_snapshot_access!(&'_ Box<dyn Snapshot>);
 impl_snapshot_access!(std::rc::Rc<dyn Snapshot>);
 impl_snapshot_access!(std::sync::Arc<dyn Snapshot>);
+impl_snapshot_access!(crate::OwnedSnapshot);
 
 impl<T: RawAccess> ViewInner<T> {
     fn snapshot(&self) -> &dyn Snapshot {
         self.index_access.snapshot()
     }
 
+    /// Reads a value from the snapshot, recording the read if reads via the access are tracked.
+    fn snapshot_get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.snapshot().get(&self.address, key);
+        if let Some(reads) = self.index_access.tracked_reads() {
+            record_read(reads, &self.address, key, value.clone());
+        }
+        value
+    }
+
     fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
         self.changes
             .as_ref()
             .map_or(Err(()), |changes| changes.get(key))
             // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
-            .unwrap_or_else(|()| self.snapshot().get(&self.address, key))
+            .unwrap_or_else(|()| self.snapshot_get(key))
+    }
+
+    fn get_pinned_bytes(&self, key: &[u8]) -> Option<PinnedValue<'_>> {
+        self.changes
+            .as_ref()
+            .map_or(Err(()), |changes| changes.get_ref(key))
+            .map(|value| value.map(PinnedValue::from))
+            // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
+            .unwrap_or_else(|()| {
+                if self.index_access.tracked_reads().is_some() {
+                    // The value needs to be copied into the read set anyway.
+                    self.snapshot_get(key).map(PinnedValue::from)
+                } else {
+                    self.snapshot().get_pinned(&self.address, key)
+                }
+            })
     }
 
     fn multi_get_bytes<I>(&self, keys: I) -> Vec<Option<Vec<u8>>>
@@ -213,7 +263,11 @@ impl<T: RawAccess> ViewInner<T> {
             &mut db_keys.iter().map(|(_, key)| key.as_ref()),
         );
 
-        for ((idx, _), item) in db_keys.into_iter().zip(db_res) {
+        let reads = self.index_access.tracked_reads();
+        for ((idx, key), item) in db_keys.into_iter().zip(db_res) {
+            if let Some(reads) = reads {
+                record_read(reads, &self.address, key.as_ref(), item.clone());
+            }
             res[idx] = item;
         }
 
@@ -225,10 +279,26 @@ impl<T: RawAccess> ViewInner<T> {
             .as_ref()
             .map_or(Err(()), |changes| changes.contains(key))
             // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
-            .unwrap_or_else(|()| self.snapshot().contains(&self.address, key))
+            .unwrap_or_else(|()| {
+                if self.index_access.tracked_reads().is_some() {
+                    self.snapshot_get(key).is_some()
+                } else {
+                    self.snapshot().contains(&self.address, key)
+                }
+            })
     }
 
-    fn iter_bytes(&self, from: &[u8]) -> BytesIter<'_> {
+    fn last_bytes(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        let entry =
+            last_before_with_changes(self.snapshot(), &self.address, self.changes.as_ref(), None);
+        if let (Some((key, _)), Some(_)) = (&entry, self.index_access.tracked_reads()) {
+            // The entry may originate from the changes, so the snapshot value is recorded.
+            self.snapshot_get(key);
+        }
+        entry
+    }
+
+    fn iter_bytes(&self, prefix: &[u8], from: &[u8]) -> BytesIter<'_> {
         use std::ops::Bound::{Included, Unbounded};
 
         let changes_iter = self
@@ -241,10 +311,17 @@ impl<T: RawAccess> ViewInner<T> {
             // Ignore all changes from the snapshot.
             Box::new(ChangesIter::new(changes_iter.unwrap()))
         } else {
-            Box::new(ForkIter::new(
-                self.snapshot().iter(&self.address, from),
-                changes_iter,
-            ))
+            let snapshot_iter = if prefix.is_empty() {
+                self.snapshot().iter(&self.address, from)
+            } else {
+                self.snapshot().iter_prefix(&self.address, prefix, from)
+            };
+            let snapshot_iter: BytesIter<'_> = match self.index_access.tracked_reads() {
+                Some(reads) => Box::new(TrackingIter::new(snapshot_iter, &self.address, reads)),
+                None => snapshot_iter,
+            };
+            let snapshot_iter = skip_removed_ranges(snapshot_iter, self.changes.as_ref());
+            Box::new(ForkIter::new(snapshot_iter, changes_iter))
         }
     }
 }
@@ -258,9 +335,61 @@ impl<T: RawAccess> View<T> {
             address,
             index_access,
             changes,
+            key_hashing: false,
         })
     }
 
+    /// Makes the view store keys prefixed by their hash if `key_hashing` is set.
+    pub(crate) fn with_key_hashing(mut self, key_hashing: bool) -> Self {
+        if let Self::Real(inner) = &mut self {
+            inner.key_hashing = key_hashing;
+        }
+        self
+    }
+
+    fn key_hashing(&self) -> bool {
+        matches!(
+            self,
+            Self::Real(ViewInner {
+                key_hashing: true,
+                ..
+            })
+        )
+    }
+
+    /// Checks whether the key has been written or removed (including by clearing the view)
+    /// in the changes of the view. Returns `false` if the view has no changes.
+    pub(crate) fn is_changed<K: BinaryKey + ?Sized>(&self, key: &K) -> bool {
+        match self {
+            Self::Real(inner) => inner.changes.as_ref().map_or(false, |changes| {
+                changes.get_ref(&self.stored_key(key)).is_ok()
+            }),
+            Self::Phantom => false,
+        }
+    }
+
+    /// Panics if the keys of the view are hashed, and thus are not stored in the key order.
+    pub(crate) fn assert_ordered(&self) {
+        assert!(
+            !self.key_hashing(),
+            "Methods relying on the key order are not supported for indexes with hashed keys"
+        );
+    }
+
+    /// Returns the key as stored in the view, i.e., prefixed by its hash if the view
+    /// has hashed keys.
+    pub(crate) fn stored_key<K: BinaryKey + ?Sized>(&self, key: &K) -> Vec<u8> {
+        let key = key_bytes(key);
+        if self.key_hashing() {
+            let mut stored_key = Vec::with_capacity(KEY_HASH_LEN + key.len());
+            stored_key.extend_from_slice(&key_hash(&key));
+            stored_key.extend_from_slice(&key);
+            stored_key
+        } else {
+            key
+        }
+    }
+
     /// Creates a new phantom view. The phantom views do not borrow changes and do not retain
     /// resolved address / access.
     pub(crate) fn new_phantom() -> Self {
@@ -275,14 +404,21 @@ impl<T: RawAccess> View<T> {
         }
     }
 
-    fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
+    pub(crate) fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
         match self {
             Self::Real(inner) => inner.get_bytes(key),
             Self::Phantom => None,
         }
     }
 
-    fn multi_get_bytes<I>(&self, keys: I) -> Vec<Option<Vec<u8>>>
+    fn get_pinned_bytes(&self, key: &[u8]) -> Option<PinnedValue<'_>> {
+        match self {
+            Self::Real(inner) => inner.get_pinned_bytes(key),
+            Self::Phantom => None,
+        }
+    }
+
+    pub(crate) fn multi_get_bytes<I>(&self, keys: I) -> Vec<Option<Vec<u8>>>
     where
         I: Iterator<Item = Vec<u8>>,
     {
@@ -299,9 +435,22 @@ impl<T: RawAccess> View<T> {
         }
     }
 
+    fn last_bytes(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        match self {
+            Self::Real(inner) => inner.last_bytes(),
+            Self::Phantom => None,
+        }
+    }
+
     fn iter_bytes(&self, from: &[u8]) -> BytesIter<'_> {
+        self.iter_prefix_bytes(&[], from)
+    }
+
+    /// Same as `iter_bytes`, but the returned iterator is only guaranteed to yield entries
+    /// with keys starting with `prefix`.
+    fn iter_prefix_bytes(&self, prefix: &[u8], from: &[u8]) -> BytesIter<'_> {
         match self {
-            Self::Real(inner) => inner.iter_bytes(from),
+            Self::Real(inner) => inner.iter_bytes(prefix, from),
             Self::Phantom => Box::new(EmptyIterator),
         }
     }
@@ -312,11 +461,19 @@ impl<T: RawAccess> View<T> {
         K: BinaryKey + ?Sized,
         V: BinaryValue,
     {
-        self.get_bytes(&key_bytes(key)).map(|v| {
+        self.get_bytes(&self.stored_key(key)).map(|v| {
             BinaryValue::from_bytes(Cow::Owned(v)).expect("Error while deserializing value")
         })
     }
 
+    /// Returns a raw value for the specified key, borrowing it from the storage if possible.
+    pub(crate) fn get_pinned<K>(&self, key: &K) -> Option<PinnedValue<'_>>
+    where
+        K: BinaryKey + ?Sized,
+    {
+        self.get_pinned_bytes(&self.stored_key(key))
+    }
+
     pub fn multi_get<K, V, I>(&self, keys: I) -> Vec<Option<V>>
     where
         K: BinaryKey + ?Sized,
@@ -324,7 +481,7 @@ impl<T: RawAccess> View<T> {
         I: IntoIterator,
         I::Item: Borrow<K>,
     {
-        self.multi_get_bytes(&mut keys.into_iter().map(|key| key_bytes(key.borrow())))
+        self.multi_get_bytes(&mut keys.into_iter().map(|key| self.stored_key(key.borrow())))
             .into_iter()
             .map(|v| {
                 v.map(|v| {
@@ -340,7 +497,22 @@ impl<T: RawAccess> View<T> {
     where
         K: BinaryKey + ?Sized,
     {
-        self.contains_raw_key(&key_bytes(key))
+        self.contains_raw_key(&self.stored_key(key))
+    }
+
+    /// Returns the entry with the greatest key in the index, or `None` if the index is empty.
+    /// Unlike iteration, this method performs a reverse seek bounded by the index.
+    pub fn last<K, V>(&self) -> Option<(K::Owned, V)>
+    where
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        self.assert_ordered();
+        self.last_bytes().map(|(key, value)| {
+            let value = BinaryValue::from_bytes(Cow::Owned(value))
+                .expect("Error while deserializing value");
+            (K::read(&key), value)
+        })
     }
 
     /// Returns an iterator over the entries of the index in ascending order. The iterator element
@@ -353,10 +525,17 @@ impl<T: RawAccess> View<T> {
         V: BinaryValue,
     {
         let iter_prefix = key_bytes(subprefix);
+        if !iter_prefix.is_empty() {
+            self.assert_ordered();
+        }
+        // Iteration over an entire index with hashed keys yields the keys in the hash order.
+        let key_offset = if self.key_hashing() { KEY_HASH_LEN } else { 0 };
         Iter {
-            base_iter: self.iter_bytes(&iter_prefix),
+            base_iter: self.iter_prefix_bytes(&iter_prefix, &iter_prefix),
             prefix: iter_prefix,
             detach_prefix: false,
+            key_offset,
+            end: None,
             ended: false,
             _k: PhantomData,
             _v: PhantomData,
@@ -373,12 +552,41 @@ impl<T: RawAccess> View<T> {
         K: BinaryKey + ?Sized,
         V: BinaryValue,
     {
+        self.assert_ordered();
         let iter_prefix = key_bytes(subprefix);
         let iter_from = key_bytes(from);
         Iter {
-            base_iter: self.iter_bytes(&iter_from),
+            base_iter: if iter_from.starts_with(&iter_prefix) {
+                self.iter_prefix_bytes(&iter_prefix, &iter_from)
+            } else {
+                self.iter_bytes(&iter_from)
+            },
             prefix: iter_prefix,
             detach_prefix: false,
+            key_offset: 0,
+            end: None,
+            ended: false,
+            _k: PhantomData,
+            _v: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the entries of the index starting from the specified key
+    /// as stored in the view (i.e., prefixed by its hash if the view has hashed keys).
+    /// Unlike `iter_from`, this method is supported for views with hashed keys; the entries
+    /// are yielded in the order of stored keys.
+    pub(crate) fn iter_from_stored<K, V>(&self, from: &[u8]) -> Iter<'_, K, V>
+    where
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        let key_offset = if self.key_hashing() { KEY_HASH_LEN } else { 0 };
+        Iter {
+            base_iter: self.iter_bytes(from),
+            prefix: vec![],
+            detach_prefix: false,
+            key_offset,
+            end: None,
             ended: false,
             _k: PhantomData,
             _v: PhantomData,
@@ -396,11 +604,10 @@ impl<T: RawAccess> View<T> {
         K: BinaryKey + ?Sized,
         V: BinaryValue,
     {
+        let key = self.stored_key(key);
         if let Self::Real(inner) = self {
-            if let Some(changes) = inner.changes.as_mut() {
-                changes
-                    .data
-                    .insert(concat_keys!(key), Change::Put(value.into_bytes()));
+            if inner.changes.as_ref().is_some() {
+                record_change(&mut inner.changes, key, Change::Put(value.into_bytes()));
                 return true;
             }
         }
@@ -408,27 +615,38 @@ impl<T: RawAccess> View<T> {
     }
 }
 
+/// Records a change in the provided change set, notifying observers of the change.
+fn record_change<C: ChangeSet>(changes: &mut C, key: Vec<u8>, change: Change) {
+    changes.notify(&key, &change);
+    if let Some(changes) = changes.as_mut() {
+        changes.data.insert(key, change);
+    }
+}
+
 impl<T: RawAccessMut> View<T> {
-    fn changes_mut(&mut self) -> &mut ViewChanges {
-        const ACCESS_ERROR: &str =
-            "Attempt to modify a readonly view of the database using a generic access. \
-             The caller should check the access type before calling any mutable methods";
+    const ACCESS_ERROR: &'static str =
+        "Attempt to modify a readonly view of the database using a generic access. \
+         The caller should check the access type before calling any mutable methods";
 
+    fn change_set_mut(&mut self) -> &mut T::Changes {
         match self {
-            Self::Real(ViewInner { changes, .. }) => changes.as_mut().expect(ACCESS_ERROR),
-            Self::Phantom => panic!("{}", ACCESS_ERROR),
+            Self::Real(ViewInner { changes, .. }) => changes,
+            Self::Phantom => panic!("{}", Self::ACCESS_ERROR),
         }
     }
 
+    fn changes_mut(&mut self) -> &mut ViewChanges {
+        self.change_set_mut().as_mut().expect(Self::ACCESS_ERROR)
+    }
+
     /// Inserts a key-value pair into the fork.
     pub fn put<K, V>(&mut self, key: &K, value: V)
     where
         K: BinaryKey + ?Sized,
         V: BinaryValue,
     {
-        self.changes_mut()
-            .data
-            .insert(concat_keys!(key), Change::Put(value.into_bytes()));
+        let key = self.stored_key(key);
+        record_change(self.change_set_mut(), key, Change::Put(value.into_bytes()));
     }
 
     /// Removes a key from the view.
@@ -436,15 +654,21 @@ impl<T: RawAccessMut> View<T> {
     where
         K: BinaryKey + ?Sized,
     {
-        self.changes_mut()
-            .data
-            .insert(concat_keys!(key), Change::Delete);
+        let key = self.stored_key(key);
+        record_change(self.change_set_mut(), key, Change::Delete);
     }
 
     /// Clears the view removing all its elements.
     pub fn clear(&mut self) {
         self.changes_mut().clear();
     }
+
+    /// Removes all keys within the `[start, end)` range of raw keys from the view.
+    /// If `end` is `None`, all keys starting from `start` are removed.
+    pub(crate) fn remove_range(&mut self, start: Vec<u8>, end: Option<Vec<u8>>) {
+        self.assert_ordered();
+        self.changes_mut().remove_range(KeyRange { start, end });
+    }
 }
 
 /// A bytes iterator implementation that has no items.
@@ -527,6 +751,10 @@ pub struct Iter<'a, K: ?Sized, V> {
     base_iter: BytesIter<'a>,
     prefix: Vec<u8>,
     detach_prefix: bool,
+    /// Number of leading bytes of raw keys (e.g., key hashes) skipped when parsing keys.
+    key_offset: usize,
+    /// Exclusive upper bound for the raw keys yielded by the iterator.
+    end: Option<Vec<u8>>,
     ended: bool,
     _k: PhantomData<K>,
     _v: PhantomData<V>,
@@ -543,28 +771,38 @@ where
     K: BinaryKey + ?Sized,
     V: BinaryValue,
 {
-    /// Drops the keys returned by the underlying iterator without parsing them.
-    pub(crate) fn drop_key_type(self) -> Iter<'a, (), V> {
+    /// Stops the iteration once a raw key reaches the specified exclusive bound.
+    pub(crate) fn with_end(self, end: Option<Vec<u8>>) -> Self {
+        Self { end, ..self }
+    }
+
+    /// Changes the types keys and values returned by the underlying iterator are parsed into.
+    fn cast<K2: ?Sized, V2>(self) -> Iter<'a, K2, V2> {
         Iter {
             base_iter: self.base_iter,
             prefix: self.prefix,
             detach_prefix: self.detach_prefix,
+            key_offset: self.key_offset,
+            end: self.end,
             ended: self.ended,
             _k: PhantomData,
             _v: PhantomData,
         }
     }
 
+    /// Drops the keys returned by the underlying iterator without parsing them.
+    pub(crate) fn drop_key_type(self) -> Iter<'a, (), V> {
+        self.cast()
+    }
+
     /// Drops the values returned by the underlying iterator without parsing them.
     pub(crate) fn drop_value_type(self) -> Iter<'a, K, ()> {
-        Iter {
-            base_iter: self.base_iter,
-            prefix: self.prefix,
-            detach_prefix: self.detach_prefix,
-            ended: self.ended,
-            _k: PhantomData,
-            _v: PhantomData,
-        }
+        self.cast()
+    }
+
+    /// Returns the keys returned by the underlying iterator as raw bytes.
+    pub(crate) fn raw_key_type(self) -> Iter<'a, [u8], V> {
+        self.cast()
     }
 }
 
@@ -581,12 +819,16 @@ where
         }
 
         if let Some((key_slice, value_slice)) = self.base_iter.next() {
-            if key_slice.starts_with(&self.prefix) {
+            let is_before_end = self
+                .end
+                .as_ref()
+                .map_or(true, |end| key_slice < end.as_slice());
+            if key_slice.starts_with(&self.prefix) && is_before_end {
                 let key = if self.detach_prefix {
                     // Since we've checked `start_with`, slicing here cannot panic.
                     K::read(&key_slice[self.prefix.len()..])
                 } else {
-                    K::read(key_slice)
+                    K::read(&key_slice[self.key_offset..])
                 };
                 let value = V::from_bytes(Cow::Borrowed(value_slice))
                     .expect("Unable to decode value from bytes");
@@ -598,3 +840,18 @@ where
         None
     }
 }
+
+/// Length of the hash prefixing keys of views with hashed keys.
+const KEY_HASH_LEN: usize = 4;
+
+/// Computes the hash prefixing a key of a view with hashed keys. The hash is 32-bit FNV-1a,
+/// which is stable across platforms and releases and is cheap to compute for short keys.
+fn key_hash(key: &[u8]) -> [u8; KEY_HASH_LEN] {
+    const OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+
+    let hash = key.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(PRIME)
+    });
+    hash.to_be_bytes()
+}
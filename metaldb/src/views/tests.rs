@@ -6,11 +6,12 @@ use url::form_urlencoded::byte_serialize;
 use std::{num::NonZeroU64, panic, rc::Rc};
 
 use crate::{
-    access::CopyAccessExt,
+    access::{AccessErrorKind, CopyAccessExt},
     db,
     validation::is_valid_identifier,
     views::{IndexAddress, IndexType, RawAccess, View, ViewWithMetadata},
-    DBOptions, Database, Fork, ListIndex, MapIndex, ResolvedAddress, RocksDB, TemporaryDB,
+    DBOptions, Database, DatabaseExt, Fork, ListIndex, MapIndex, ResolvedAddress, RocksDB,
+    TemporaryDB,
 };
 
 const IDX_NAME: &str = "idx_name";
@@ -254,6 +255,110 @@ where
     assert_iter(&view, 4, &[(4, 0)]);
 }
 
+fn test_remove_range<T, I>(db: &T, address: I)
+where
+    T: Database,
+    I: Into<ResolvedAddress> + Copy,
+{
+    let fork = db.fork();
+    {
+        let mut view = View::new(&fork, address);
+        for i in 1..=6 {
+            view.put(&vec![i], vec![i]);
+        }
+    }
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut fork = db.fork();
+    {
+        let mut view = View::new(&fork, address);
+        view.put(&vec![7], vec![7]);
+        view.put(&vec![3], vec![30]);
+        view.remove_range(vec![2], Some(vec![5]));
+
+        assert_eq!(view.get_bytes(&[2]), None);
+        assert_eq!(view.get_bytes(&[3]), None);
+        assert_eq!(view.get_bytes(&[5]), Some(vec![5]));
+        assert!(!view.contains_raw_key(&[4]));
+        assert_iter(&view, 0, &[(1, 1), (5, 5), (6, 6), (7, 7)]);
+        assert_iter(&view, 3, &[(5, 5), (6, 6), (7, 7)]);
+
+        // Entries put after removing the range are visible.
+        view.put(&vec![3], vec![33]);
+        assert_iter(&view, 0, &[(1, 1), (3, 33), (5, 5), (6, 6), (7, 7)]);
+
+        view.remove_range(vec![6], None);
+        assert_iter(&view, 0, &[(1, 1), (3, 33), (5, 5)]);
+        assert_eq!(view.last_bytes(), Some((vec![5], vec![5])));
+    }
+    fork.flush();
+    {
+        let mut view = View::new(&fork, address);
+        assert_iter(&view, 0, &[(1, 1), (3, 33), (5, 5)]);
+        view.remove_range(vec![], Some(vec![2]));
+        view.remove_range(vec![4], Some(vec![6]));
+        assert_iter(&view, 0, &[(3, 33)]);
+        assert_eq!(view.last_bytes(), Some((vec![3], vec![33])));
+    }
+    let patch = fork.into_patch();
+    {
+        let view = View::new(&patch, address);
+        assert_iter(&view, 0, &[(3, 33)]);
+        assert_eq!(view.get_bytes(&[6]), None);
+    }
+    db.merge(patch).unwrap();
+
+    let snapshot = db.snapshot();
+    let view = View::new(&snapshot, address);
+    assert_iter(&view, 0, &[(3, 33)]);
+    for i in (1..=7).filter(|&i| i != 3) {
+        assert_eq!(view.get_bytes(&[i]), None);
+    }
+    assert_eq!(view.last_bytes(), Some((vec![3], vec![33])));
+}
+
+fn test_remove_range_in_sibling_views<T: Database>(db: &T) {
+    const IDX_1: (&str, u64) = ("foo", 22);
+    const IDX_2: (&str, u64) = ("foo", 23);
+    const IDX_3: (&str, u64) = ("foo", 24);
+
+    let fork = db.fork();
+    for &addr in &[IDX_1, IDX_2, IDX_3] {
+        let mut view = View::new(&fork, addr);
+        for i in 0..4 {
+            view.put(&vec![i], vec![i]);
+        }
+    }
+    db.merge(fork.into_patch()).unwrap();
+
+    let fork = db.fork();
+    {
+        View::new(&fork, IDX_2).remove_range(vec![], None);
+        View::new(&fork, IDX_3).remove_range(vec![1], Some(vec![3]));
+    }
+    let backup = db.merge_with_backup(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    assert_iter(
+        &View::new(&snapshot, IDX_1),
+        0,
+        &[(0, 0), (1, 1), (2, 2), (3, 3)],
+    );
+    assert_iter(&View::new(&snapshot, IDX_2), 0, &[]);
+    assert_iter(&View::new(&snapshot, IDX_3), 0, &[(0, 0), (3, 3)]);
+
+    // Reverting the patch restores removed entries.
+    db.merge(backup).unwrap();
+    let snapshot = db.snapshot();
+    for &addr in &[IDX_1, IDX_2, IDX_3] {
+        assert_iter(
+            &View::new(&snapshot, addr),
+            0,
+            &[(0, 0), (1, 1), (2, 2), (3, 3)],
+        );
+    }
+}
+
 fn test_fork_iter<T, I>(db: &T, address: I)
 where
     T: Database,
@@ -598,6 +703,26 @@ fn clear_prefixed_view() {
     test_clear_view(&TemporaryDB::new(), PREFIXED_IDX);
 }
 
+#[test]
+fn remove_range() {
+    test_remove_range(&TemporaryDB::new(), IDX_NAME);
+    test_remove_range(&TemporaryDB::new(), PREFIXED_IDX);
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(&dir, &DBOptions::default()).unwrap();
+    test_remove_range(&db, IDX_NAME);
+    test_remove_range(&db, PREFIXED_IDX);
+}
+
+#[test]
+fn remove_range_in_sibling_views() {
+    test_remove_range_in_sibling_views(&TemporaryDB::new());
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let db = RocksDB::open(&dir, &DBOptions::default()).unwrap();
+    test_remove_range_in_sibling_views(&db);
+}
+
 #[test]
 fn clear_sibling_views() {
     const IDX_1: (&str, u64) = ("foo", 23);
@@ -1029,6 +1154,19 @@ fn valid_name_for_url() {
     assert_valid_name_url("core.service_name");
 }
 
+#[test]
+fn slash_separated_index_names() {
+    assert!(check_valid_name("core/index_name"));
+    assert!(check_valid_name("core/nested.index_name"));
+    assert!(check_valid_name("a/b/c"));
+
+    assert!(!check_valid_name("/index_name"));
+    assert!(!check_valid_name("index_name/"));
+    assert!(!check_valid_name("core//index_name"));
+    assert!(!check_valid_name("core/index name"));
+    assert!(!is_valid_identifier("/"));
+}
+
 #[test]
 #[should_panic(expected = "Invalid characters used in name")]
 fn invalid_name_panic() {
@@ -1081,3 +1219,25 @@ fn fork_from_patch() {
     db.merge(fork.into_patch())
         .expect("Fork created from patch should be merged successfully");
 }
+
+#[test]
+fn parsing_index_addresses() {
+    let addr: IndexAddress = "foo.bar".parse().unwrap();
+    assert_eq!(addr, IndexAddress::from_root("foo.bar"));
+    let addr: IndexAddress = "foo:".parse().unwrap();
+    assert_eq!(addr, IndexAddress::from(("foo", &())));
+    let addr: IndexAddress = "foo:00ffAB".parse().unwrap();
+    assert_eq!(addr.id_in_group(), Some(&[0, 0xff, 0xab][..]));
+
+    let err = "".parse::<IndexAddress>().unwrap_err();
+    assert_matches!(err.kind, AccessErrorKind::EmptyName);
+    let err = "__reserved".parse::<IndexAddress>().unwrap_err();
+    assert_matches!(err.kind, AccessErrorKind::ReservedName);
+    let err = "foo bar".parse::<IndexAddress>().unwrap_err();
+    assert_matches!(err.kind, AccessErrorKind::InvalidCharsInName { .. });
+    for &invalid_key in &["foo:0", "foo:+f", "foo:xy", "foo:00:11"] {
+        let err = invalid_key.parse::<IndexAddress>().unwrap_err();
+        assert_matches!(err.kind, AccessErrorKind::Custom(_));
+        assert_eq!(err.addr, IndexAddress::from_root("foo"));
+    }
+}
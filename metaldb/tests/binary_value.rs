@@ -0,0 +1,243 @@
+//! Tests related to `BinaryValue` derivation.
+
+use metaldb_derive::BinaryValue;
+
+use std::borrow::Cow;
+
+use metaldb::{access::CopyAccessExt, BinaryValue, Database, TemporaryDB};
+
+#[derive(Debug, PartialEq, BinaryValue)]
+#[binary_value(codec = "tagged")]
+enum Event {
+    Created { username: String, balance: u64 },
+    Renamed(String),
+    Transferred(String, String, u64),
+    Deleted,
+}
+
+macro_rules! wide_enum {
+    ($($variant:ident)*) => {
+        #[derive(Debug, PartialEq, BinaryValue)]
+        #[binary_value(codec = "tagged")]
+        enum Wide {
+            $($variant,)*
+            Last(u64),
+        }
+    };
+}
+
+wide_enum! {
+    V0 V1 V2 V3 V4 V5 V6 V7 V8 V9 V10 V11 V12 V13 V14 V15 V16 V17 V18 V19 V20 V21 V22 V23
+    V24 V25 V26 V27 V28 V29 V30 V31 V32 V33 V34 V35 V36 V37 V38 V39 V40 V41 V42 V43 V44 V45
+    V46 V47 V48 V49 V50 V51 V52 V53 V54 V55 V56 V57 V58 V59 V60 V61 V62 V63 V64 V65 V66 V67
+    V68 V69 V70 V71 V72 V73 V74 V75 V76 V77 V78 V79 V80 V81 V82 V83 V84 V85 V86 V87 V88 V89
+    V90 V91 V92 V93 V94 V95 V96 V97 V98 V99 V100 V101 V102 V103 V104 V105 V106 V107 V108
+    V109 V110 V111 V112 V113 V114 V115 V116 V117 V118 V119 V120 V121 V122 V123 V124 V125
+    V126 V127 V128 V129 V130 V131 V132 V133 V134 V135 V136 V137 V138 V139 V140 V141 V142
+    V143 V144 V145 V146 V147 V148 V149 V150 V151 V152 V153 V154 V155 V156 V157 V158 V159
+    V160 V161 V162 V163 V164 V165 V166 V167 V168 V169 V170 V171 V172 V173 V174 V175 V176
+    V177 V178 V179 V180 V181 V182 V183 V184 V185 V186 V187 V188 V189 V190 V191 V192 V193
+    V194 V195 V196 V197 V198 V199 V200 V201 V202 V203 V204 V205 V206 V207 V208 V209 V210
+    V211 V212 V213 V214 V215 V216 V217 V218 V219 V220 V221 V222 V223 V224 V225 V226 V227
+    V228 V229 V230 V231 V232 V233 V234 V235 V236 V237 V238 V239 V240 V241 V242 V243 V244
+    V245 V246 V247 V248 V249 V250 V251 V252 V253 V254 V255 V256 V257 V258 V259 V260 V261
+    V262 V263 V264 V265 V266 V267 V268 V269 V270 V271 V272 V273 V274 V275 V276 V277 V278
+    V279 V280 V281 V282 V283 V284 V285 V286 V287 V288 V289 V290 V291 V292 V293 V294 V295
+    V296 V297 V298 V299
+}
+
+/// Successive versions of the same type, as they would evolve in an application.
+mod v1 {
+    use metaldb_derive::BinaryValue;
+
+    #[derive(Debug, PartialEq, BinaryValue)]
+    #[binary_value(codec = "bincode", version = 1)]
+    pub struct Wallet {
+        pub username: String,
+        pub balance: u64,
+    }
+}
+
+mod v2 {
+    use metaldb_derive::BinaryValue;
+
+    #[derive(Debug, PartialEq, BinaryValue)]
+    #[binary_value(codec = "bincode", version = 2)]
+    pub struct Wallet {
+        pub username: String,
+        #[binary_value(since = 2)]
+        pub frozen: bool,
+        pub balance: u64,
+    }
+}
+
+mod v3 {
+    use metaldb_derive::BinaryValue;
+
+    #[derive(Debug, PartialEq, BinaryValue)]
+    #[binary_value(codec = "bincode", version = 3)]
+    pub struct Wallet {
+        pub username: String,
+        #[binary_value(since = 2)]
+        pub frozen: bool,
+        pub balance: u64,
+        #[binary_value(since = 3)]
+        pub history: Vec<u64>,
+    }
+}
+
+#[derive(Debug, PartialEq, BinaryValue)]
+#[binary_value(codec = "bincode", version = 1)]
+struct Pair(u32, #[binary_value(since = 1)] String);
+
+fn round_trip<T: BinaryValue + PartialEq + std::fmt::Debug>(value: &T) -> Vec<u8> {
+    let bytes = value.to_bytes();
+    assert_eq!(T::from_bytes(Cow::Borrowed(&bytes)).unwrap(), *value);
+    bytes
+}
+
+#[test]
+fn tagged_enum_encoding() {
+    assert_eq!(round_trip(&Event::Deleted), [3]);
+    assert_eq!(round_trip(&Event::Renamed("Bob".to_owned())), b"\x01Bob");
+
+    let created = Event::Created {
+        username: "Alice".to_owned(),
+        balance: 100,
+    };
+    let bytes = round_trip(&created);
+    assert_eq!(bytes[..7], *b"\x00\x05Alice");
+    assert_eq!(bytes[7..], 100_u64.to_bytes()[..]);
+
+    let transfer = Event::Transferred(String::new(), "Bob".to_owned(), 5);
+    let bytes = round_trip(&transfer);
+    assert_eq!(bytes[..6], *b"\x02\x00\x03Bob");
+}
+
+#[test]
+fn tagged_enum_with_many_variants() {
+    assert_eq!(round_trip(&Wide::V0), [0]);
+    assert_eq!(round_trip(&Wide::V127), [127]);
+    assert_eq!(round_trip(&Wide::V128), [0x80, 1]);
+    assert_eq!(round_trip(&Wide::V299), [0xab, 2]);
+    let bytes = round_trip(&Wide::Last(1));
+    assert_eq!(bytes[..2], [0xac, 2]);
+}
+
+#[test]
+fn invalid_tagged_enum_values() {
+    let err = Event::from_bytes(Cow::Borrowed(&[])).unwrap_err();
+    assert!(err.to_string().contains("Missing variant tag"));
+    let err = Event::from_bytes(Cow::Borrowed(&[4])).unwrap_err();
+    assert!(err.to_string().contains("Unknown variant tag 4"));
+    let err = Event::from_bytes(Cow::Borrowed(&[3, 0])).unwrap_err();
+    assert!(err.to_string().contains("Unexpected payload"));
+    let err = Event::from_bytes(Cow::Borrowed(&[2, 10, 0])).unwrap_err();
+    assert!(err.to_string().contains("exceeds the value length"));
+    let err = Wide::from_bytes(Cow::Borrowed(&[0x80])).unwrap_err();
+    assert!(err.to_string().contains("Invalid varint"));
+}
+
+#[test]
+fn tagged_enum_in_index() {
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    let mut events = fork.get_list("events");
+    events.extend(vec![
+        Event::Renamed("Carol".to_owned()),
+        Event::Deleted,
+        Event::Transferred("Alice".to_owned(), "Bob".to_owned(), 10),
+    ]);
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let events = snapshot.get_list::<_, Event>("events");
+    assert_eq!(events.get(1), Some(Event::Deleted));
+    assert_eq!(
+        events.last(),
+        Some(Event::Transferred("Alice".to_owned(), "Bob".to_owned(), 10))
+    );
+}
+
+#[test]
+fn versioned_struct_encoding() {
+    let wallet = v2::Wallet {
+        username: "Alice".to_owned(),
+        frozen: true,
+        balance: 100,
+    };
+    let bytes = round_trip(&wallet);
+    assert_eq!(bytes[0], 2);
+    assert_eq!(
+        bytes[1..],
+        bincode::serialize(&("Alice", true, 100_u64)).unwrap()[..]
+    );
+
+    let bytes = round_trip(&Pair(1, "foo".to_owned()));
+    assert_eq!(bytes[0], 1);
+    assert_eq!(bytes[1..], bincode::serialize(&(1_u32, "foo")).unwrap()[..]);
+}
+
+#[test]
+fn reading_older_versions() {
+    let wallet = v1::Wallet {
+        username: "Alice".to_owned(),
+        balance: 100,
+    };
+    let bytes = wallet.to_bytes();
+    let wallet = v2::Wallet::from_bytes(Cow::Borrowed(&bytes)).unwrap();
+    assert_eq!(
+        wallet,
+        v2::Wallet {
+            username: "Alice".to_owned(),
+            frozen: false,
+            balance: 100,
+        }
+    );
+    let wallet = v3::Wallet::from_bytes(Cow::Borrowed(&bytes)).unwrap();
+    assert!(!wallet.frozen);
+    assert_eq!(wallet.balance, 100);
+    assert!(wallet.history.is_empty());
+
+    let wallet = v2::Wallet {
+        username: "Bob".to_owned(),
+        frozen: true,
+        balance: 5,
+    };
+    let wallet = v3::Wallet::from_bytes(Cow::Owned(wallet.to_bytes())).unwrap();
+    assert_eq!(
+        wallet,
+        v3::Wallet {
+            username: "Bob".to_owned(),
+            frozen: true,
+            balance: 5,
+            history: vec![],
+        }
+    );
+
+    // Version 0 predates the second field of `Pair`.
+    let mut bytes = vec![0];
+    bytes.extend_from_slice(&bincode::serialize(&7_u32).unwrap());
+    let pair = Pair::from_bytes(Cow::Owned(bytes)).unwrap();
+    assert_eq!(pair, Pair(7, String::new()));
+}
+
+#[test]
+fn invalid_versioned_struct_values() {
+    let err = v1::Wallet::from_bytes(Cow::Borrowed(&[])).unwrap_err();
+    assert!(err.to_string().contains("Missing version"));
+
+    let wallet = v3::Wallet {
+        username: "Alice".to_owned(),
+        frozen: false,
+        balance: 100,
+        history: vec![50, 50],
+    };
+    let err = v1::Wallet::from_bytes(Cow::Owned(wallet.to_bytes())).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unsupported version 3 of `Wallet` (the latest supported version is 1)"));
+
+    let truncated = vec![2, 1, 0];
+    assert!(v2::Wallet::from_bytes(Cow::Owned(truncated)).is_err());
+}
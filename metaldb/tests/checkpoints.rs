@@ -1,4 +1,4 @@
-use metaldb::{access::CopyAccessExt, DBOptions, Database, RocksDB};
+use metaldb::{access::CopyAccessExt, DBOptions, Database, RocksDB, WriteDurability};
 use tempfile::TempDir;
 
 #[test]
@@ -74,3 +74,96 @@ fn checkpoints() {
         checkpoint.merge_sync(fork.into_patch()).unwrap();
     }
 }
+
+#[test]
+fn bulk_merge_is_persisted_after_flush() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("db");
+
+    {
+        let db = RocksDB::open(&*path, &DBOptions::default()).unwrap();
+        for i in 0..5_u8 {
+            let fork = db.fork();
+            fork.get_entry(("bulk", &i)).set(vec![i; 1024]);
+            db.bulk_merge(fork.into_patch()).unwrap();
+        }
+        db.flush().unwrap();
+    }
+
+    let db = RocksDB::open(&*path, &DBOptions::default()).unwrap();
+    let fork = db.fork();
+    for i in 0..5_u8 {
+        assert_eq!(fork.get_entry(("bulk", &i)).get(), Some(vec![i; 1024]));
+    }
+}
+
+#[test]
+fn merges_with_all_durability_options_are_persisted() {
+    const OPTIONS: [WriteDurability; 3] = [
+        WriteDurability::NoWal,
+        WriteDurability::WalNoSync,
+        WriteDurability::WalSync,
+    ];
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("db");
+    {
+        let db = RocksDB::open(&*path, &DBOptions::default()).unwrap();
+        for (i, &durability) in OPTIONS.iter().enumerate() {
+            let fork = db.fork();
+            fork.get_entry(("entry", &(i as u8))).set(i as u64);
+            db.merge_with_options(fork.into_patch(), durability)
+                .unwrap();
+            assert_eq!(
+                db.snapshot().get_entry(("entry", &(i as u8))).get(),
+                Some(i as u64)
+            );
+        }
+        // Changes merged without WAL need to be flushed explicitly.
+        db.flush().unwrap();
+    }
+
+    let db = RocksDB::open(&*path, &DBOptions::default()).unwrap();
+    let snapshot = db.snapshot();
+    for i in 0..OPTIONS.len() {
+        let entry = snapshot.get_entry::<_, u64>(("entry", &(i as u8)));
+        assert_eq!(entry.get(), Some(i as u64));
+    }
+}
+
+#[test]
+fn backup_archive_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path().join("src"), &DBOptions::default()).unwrap();
+    {
+        let fork = db.fork();
+        fork.get_entry("first").set(vec![1_u8; 1024]);
+        fork.get_list(("list", &1_u8)).extend(0..100_u32);
+        db.merge_sync(fork.into_patch()).unwrap();
+    }
+
+    let mut archive = vec![];
+    db.create_backup_archive(&mut archive).unwrap();
+
+    // Data added after the archive is created should not be restored.
+    {
+        let fork = db.fork();
+        fork.get_entry("second").set(vec![2_u8; 1024]);
+        db.merge_sync(fork.into_patch()).unwrap();
+    }
+
+    let restored_path = temp_dir.path().join("restored");
+    RocksDB::restore_from_archive(&archive[..], &restored_path).unwrap();
+    // Restoring into a non-empty directory is an error.
+    assert!(RocksDB::restore_from_archive(&archive[..], &restored_path).is_err());
+
+    let restored = RocksDB::open(&restored_path, &DBOptions::default()).unwrap();
+    let fork = restored.fork();
+    assert_eq!(fork.get_entry("first").get(), Some(vec![1_u8; 1024]));
+    assert_eq!(fork.get_entry("second").get(), None::<Vec<u8>>);
+    let list = fork.get_list::<_, u32>(("list", &1_u8));
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        (0..100).collect::<Vec<_>>()
+    );
+}
@@ -23,6 +23,22 @@ pub trait AsForkAction {
     fn as_fork_action(&self) -> Option<ForkAction>;
 }
 
+/// Part of a collection which may be changed by an action.
+#[allow(dead_code)] // Not used by all test crates.
+pub enum Delta<K> {
+    /// Only entries with the specified keys may be changed.
+    Keys(Vec<K>),
+    /// Any entry may be changed.
+    All,
+}
+
+/// Reports the part of the collection changed by an action when it is applied
+/// to the reference collection.
+#[allow(dead_code)] // Not used by all test crates.
+pub trait AsDelta<K> {
+    fn as_delta(&self) -> Delta<K>;
+}
+
 pub fn compare_collections<A, R, T>(
     db: &TemporaryDB,
     actions: &[A],
@@ -58,3 +74,51 @@ where
     let collection = T::from_fork(fork);
     compare(&collection, &reference)
 }
+
+/// Same as `compare_collections`, but after each action only compares the entries
+/// reported by the action as changed using `compare_key`. The entire collections are compared
+/// with `compare` after actions changing arbitrary entries and at the end of the sequence.
+#[allow(dead_code)] // Not used by all test crates.
+pub fn compare_collections_incremental<A, K, R, T>(
+    db: &TemporaryDB,
+    actions: &[A],
+    compare: impl Fn(&T, &R) -> TestCaseResult,
+    compare_key: impl Fn(&T, &R, &K) -> TestCaseResult,
+) -> TestCaseResult
+where
+    A: Clone + AsForkAction + AsDelta<K> + Modifier<R> + Modifier<T> + std::fmt::Debug,
+    R: Default,
+    T: FromFork,
+{
+    let mut fork = Rc::new(db.fork());
+    {
+        let mut collection = T::from_fork(fork.clone());
+        collection.clear();
+    }
+    let mut reference = R::default();
+
+    for action in actions {
+        match action.as_fork_action() {
+            Some(ForkAction::Merge) => {
+                let patch = Rc::try_unwrap(fork).expect("fork ref leaked").into_patch();
+                db.merge(patch).unwrap();
+                fork = Rc::new(db.fork());
+            }
+            None => {
+                let mut collection = T::from_fork(fork.clone());
+                action.clone().modify(&mut collection);
+                action.clone().modify(&mut reference);
+                match action.as_delta() {
+                    Delta::Keys(keys) => {
+                        for key in &keys {
+                            compare_key(&collection, &reference, key)?;
+                        }
+                    }
+                    Delta::All => compare(&collection, &reference)?,
+                }
+            }
+        }
+    }
+    let collection = T::from_fork(fork);
+    compare(&collection, &reference)
+}
@@ -3,7 +3,7 @@
 use metaldb_derive::FromAccess;
 
 use metaldb::{
-    access::{Access, CopyAccessExt, FromAccess, RawAccessMut},
+    access::{Access, CopyAccessExt, FromAccess, RawAccessMut, TrackingAccess},
     BinaryKey, Database, Entry, Group, Lazy, ListIndex, MapIndex, TemporaryDB,
 };
 
@@ -275,3 +275,162 @@ fn multiple_flattened_fields() {
     assert_eq!(fork.get_list::<_, Vec<u8>>("list").len(), 1);
     assert_eq!(fork.get_map(("maps", &23_u32)).get("Alice"), Some(1_u64));
 }
+
+#[test]
+fn enum_components() {
+    #[derive(FromAccess)]
+    struct Inner<T: Access> {
+        entry: Entry<T::Base, u64>,
+    }
+
+    #[derive(FromAccess)]
+    enum State<T: Access> {
+        Idle,
+        Running {
+            log: ListIndex<T::Base, String>,
+            #[from_access(flatten)]
+            inner: Inner<T>,
+        },
+        #[from_access(rename = "done")]
+        Finished(#[from_access(rename = "result")] Entry<T::Base, u64>),
+    }
+
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    assert!(matches!(State::from_root(&fork).unwrap(), State::Idle));
+
+    fork.get_entry::<_, u8>("state._variant").set(1);
+    {
+        let state = State::from_access(&fork, "state".into()).unwrap();
+        if let State::Running { mut log, mut inner } = state {
+            log.push("started".to_owned());
+            inner.entry.set(5);
+        } else {
+            panic!("Unexpected variant");
+        }
+    }
+    assert_eq!(fork.get_list::<_, String>("state.Running.log").len(), 1);
+    assert_eq!(
+        fork.get_entry::<_, u64>("state.Running.entry").get(),
+        Some(5)
+    );
+
+    fork.get_entry::<_, u8>("state._variant").set(2);
+    {
+        let state = State::from_access(&fork, "state".into()).unwrap();
+        if let State::Finished(mut result) = state {
+            result.set(42);
+        } else {
+            panic!("Unexpected variant");
+        }
+    }
+    assert_eq!(
+        fork.get_entry::<_, u64>("state.done.result").get(),
+        Some(42)
+    );
+
+    fork.get_entry::<_, u8>("state._variant").set(3);
+    let err = State::from_access(&fork, "state".into()).err().unwrap();
+    assert!(err.to_string().contains("Unknown variant tag 3"), "{}", err);
+}
+
+#[test]
+fn custom_separator() {
+    #[derive(FromAccess)]
+    #[from_access(separator = "/")]
+    struct Slashed<T: Access> {
+        list: ListIndex<T::Base, u64>,
+        #[from_access(rename = "inner")]
+        simple: Simple<T>,
+    }
+
+    #[derive(FromAccess)]
+    #[from_access(separator = "/")]
+    enum SlashedEnum<T: Access> {
+        Empty,
+        Full { entry: Entry<T::Base, u64> },
+    }
+
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    {
+        let mut component = Slashed::from_access(&fork, "app".into()).unwrap();
+        component.list.push(1);
+        component.simple.list.push("foo".to_owned());
+    }
+    assert_eq!(fork.get_list::<_, u64>("app/list").len(), 1);
+    // Nested components use their own separator.
+    assert_eq!(fork.get_list::<_, String>("app/inner.list").len(), 1);
+
+    fork.get_entry::<_, u8>("state/_variant").set(1);
+    if let SlashedEnum::Full { mut entry } =
+        SlashedEnum::from_access(&fork, "state".into()).unwrap()
+    {
+        entry.set(42);
+    } else {
+        panic!("Unexpected variant");
+    }
+    assert_eq!(fork.get_entry::<_, u64>("state/Full/entry").get(), Some(42));
+}
+
+#[test]
+fn skipped_fields() {
+    #[derive(FromAccess)]
+    struct WithCache<T: Access> {
+        map: MapIndex<T::Base, u64, String>,
+        #[from_access(skip)]
+        cache: Vec<String>,
+    }
+
+    #[derive(FromAccess)]
+    struct Tuple<T: Access>(
+        #[from_access(rename = "list")] ListIndex<T::Base, u64>,
+        #[from_access(skip)] Option<u64>,
+    );
+
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    {
+        let mut component = WithCache::from_access(&fork, "component".into()).unwrap();
+        assert!(component.cache.is_empty());
+        component.map.put(&1, "foo".to_owned());
+        component.cache.push("foo".to_owned());
+    }
+    assert_eq!(
+        fork.get_map::<_, u64, String>("component.map").get(&1),
+        Some("foo".to_owned())
+    );
+    assert!(fork.index_type("component.cache").is_none());
+
+    let component = WithCache::from_root(&fork).unwrap();
+    assert!(component.cache.is_empty());
+    let tuple = Tuple::from_access(&fork, "tuple".into()).unwrap();
+    assert!(tuple.0.is_empty());
+    assert_eq!(tuple.1, None);
+}
+
+#[test]
+fn components_with_tracking_access() {
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    ComplexSchema::from_root(&fork)
+        .unwrap()
+        .modify(1, "foo".to_owned());
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let access = TrackingAccess::new(&snapshot);
+    let complex = ComplexSchema::from_root(access.clone()).unwrap();
+    assert_eq!(complex.count.get(), Some(1));
+    assert_eq!(complex.group.get("foo").map.get(&1), Some("foo".to_owned()));
+    drop(complex);
+
+    let read_set = access.into_read_set();
+    let names: Vec<_> = read_set
+        .keys()
+        .map(|(address, _)| address.name.as_str())
+        .collect();
+    assert!(names.contains(&"count"));
+    assert!(names.contains(&"group.map"));
+    assert!(!names.contains(&"generic.inner"));
+}
@@ -0,0 +1,130 @@
+//! Tests that indexes work with a backend implemented outside of the crate.
+
+use metaldb::{
+    access::CopyAccessExt, check_database, Change, Database, Iter, Iterator, Patch,
+    ResolvedAddress, Snapshot,
+};
+
+use std::{
+    collections::{btree_map, BTreeMap, HashMap},
+    iter::Peekable,
+    ops::Bound,
+    sync::{Arc, RwLock},
+};
+
+type Storage = HashMap<ResolvedAddress, BTreeMap<Vec<u8>, Vec<u8>>>;
+
+/// Minimal in-memory backend which stores each view in a separate map.
+#[derive(Debug, Default)]
+struct MapDB {
+    storage: Arc<RwLock<Storage>>,
+}
+
+impl MapDB {
+    fn new() -> Self {
+        let mut db = Self::default();
+        check_database(&mut db).unwrap();
+        db
+    }
+}
+
+#[derive(Debug)]
+struct MapSnapshot(Storage);
+
+struct MapIter<'a>(Peekable<btree_map::Range<'a, Vec<u8>, Vec<u8>>>);
+
+impl Iterator for MapIter<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        self.0
+            .next()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        self.0
+            .peek()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+}
+
+impl Snapshot for MapSnapshot {
+    fn get(&self, name: &ResolvedAddress, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(name)?.get(key).cloned()
+    }
+
+    fn iter(&self, name: &ResolvedAddress, from: &[u8]) -> Iter<'_> {
+        static EMPTY: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+        let view = self.0.get(name).unwrap_or(&EMPTY);
+        let range = view.range::<[u8], _>((Bound::Included(from), Bound::Unbounded));
+        Box::new(MapIter(range.peekable()))
+    }
+}
+
+impl Database for MapDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(MapSnapshot(self.storage.read().unwrap().clone()))
+    }
+
+    fn merge(&self, patch: Patch) -> metaldb::Result<()> {
+        let mut storage = self.storage.write().unwrap();
+        for (address, changes) in patch.into_changes() {
+            let view = storage.entry(address).or_default();
+            if changes.is_cleared() {
+                view.clear();
+            }
+            for range in changes.removed_ranges() {
+                view.retain(|key, _| !range.contains(key));
+            }
+            for (key, change) in changes.into_data() {
+                match change {
+                    Change::Put(value) => view.insert(key, value),
+                    Change::Delete => view.remove(&key),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_sync(&self, patch: Patch) -> metaldb::Result<()> {
+        self.merge(patch)
+    }
+}
+
+#[test]
+fn indexes_with_custom_backend() {
+    let db = MapDB::new();
+    let fork = db.fork();
+    fork.get_list("list").extend(vec![1_u32, 2, 3]);
+    fork.get_map("map").put("foo", 1_u64);
+    fork.get_map("map").put("bar", 2_u64);
+    fork.get_entry(("group", &1_u8)).set("!".to_owned());
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let list = snapshot.get_list::<_, u32>("list");
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(list.last(), Some(3));
+    let map = snapshot.get_map::<_, str, u64>("map");
+    assert_eq!(map.keys().collect::<Vec<_>>(), vec!["bar", "foo"]);
+    assert_eq!(
+        snapshot.get_entry::<_, String>(("group", &1_u8)).get(),
+        Some("!".to_owned())
+    );
+
+    // Clearing and removing ranges is applied by the backend.
+    let fork = db.fork();
+    fork.get_list::<_, u32>("list").clear();
+    fork.get_map::<_, str, u64>("map").remove("bar");
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    assert!(snapshot.get_list::<_, u32>("list").is_empty());
+    let map = snapshot.get_map::<_, str, u64>("map");
+    assert_eq!(map.iter().collect::<Vec<_>>(), vec![("foo".to_owned(), 1)]);
+
+    // The database version is checked on reopening.
+    let mut db = MapDB {
+        storage: Arc::clone(&db.storage),
+    };
+    check_database(&mut db).unwrap();
+}
@@ -27,6 +27,8 @@ enum ListAction<V> {
     Truncate(u64),
     // Applied to index modulo `collection.len()`.
     Set(u64, V),
+    // Applied to index modulo `collection.len() + 1` to cover out-of-range indexes.
+    SwapRemove(u64),
     Clear,
     MergeFork,
 }
@@ -64,6 +66,12 @@ impl<V> Modifier<Vec<V>> for ListAction<V> {
                     list[idx as usize % len] = val;
                 }
             }
+            ListAction::SwapRemove(idx) => {
+                let idx = idx as usize % (list.len() + 1);
+                if idx < list.len() {
+                    list.swap_remove(idx);
+                }
+            }
             ListAction::Clear => {
                 list.clear();
             }
@@ -96,6 +104,9 @@ impl<V: BinaryValue> Modifier<ListIndex<Rc<Fork>, V>> for ListAction<V> {
                     list.set(idx % len, val);
                 }
             }
+            ListAction::SwapRemove(idx) => {
+                list.swap_remove(idx % (list.len() + 1));
+            }
             ListAction::Clear => {
                 list.clear();
             }
@@ -121,6 +132,7 @@ fn generate_action() -> impl Strategy<Value = ListAction<i32>> {
         vec(num::i32::ANY, 1..5).prop_map(ListAction::Extend),
         num::u64::ANY.prop_map(ListAction::Truncate),
         (num::u64::ANY, num::i32::ANY).prop_map(|(i, v)| ListAction::Set(i, v)),
+        num::u64::ANY.prop_map(ListAction::SwapRemove),
         strategy::Just(ListAction::Clear),
         strategy::Just(ListAction::MergeFork),
     ]
@@ -10,9 +10,15 @@ use proptest::{
 
 use std::{collections::HashMap, hash::Hash, rc::Rc};
 
-use metaldb::{access::AccessExt, BinaryValue, Fork, MapIndex, TemporaryDB};
+use metaldb::{
+    access::{AccessExt, CopyAccessExt},
+    BinaryValue, Database, Fork, MapIndex, TemporaryDB,
+};
 
-use crate::common::{compare_collections, AsForkAction, ForkAction, FromFork, ACTIONS_MAX_LEN};
+use crate::common::{
+    compare_collections, compare_collections_incremental, AsDelta, AsForkAction, Delta, ForkAction,
+    FromFork, ACTIONS_MAX_LEN,
+};
 
 mod common;
 mod key;
@@ -36,6 +42,15 @@ impl<K, V> AsForkAction for MapAction<K, V> {
     }
 }
 
+impl<K: Clone, V> AsDelta<K> for MapAction<K, V> {
+    fn as_delta(&self) -> Delta<K> {
+        match self {
+            MapAction::Put(k, _) | MapAction::Remove(k) => Delta::Keys(vec![k.clone()]),
+            MapAction::Clear | MapAction::MergeFork => Delta::All,
+        }
+    }
+}
+
 impl<K, V> Modifier<HashMap<K, V>> for MapAction<K, V>
 where
     K: Eq + Hash,
@@ -96,6 +111,15 @@ fn compare_map(map: &MapIndex<Rc<Fork>, u8, i32>, ref_map: &HashMap<u8, i32>) ->
     Ok(())
 }
 
+fn compare_map_entry(
+    map: &MapIndex<Rc<Fork>, u8, i32>,
+    ref_map: &HashMap<u8, i32>,
+    key: &u8,
+) -> TestCaseResult {
+    prop_assert_eq!(map.get(key), ref_map.get(key).copied());
+    Ok(())
+}
+
 fn generate_action() -> impl Strategy<Value = MapAction<u8, i32>> {
     prop_oneof![
         (num::u8::ANY, num::i32::ANY).prop_map(|(i, v)| MapAction::Put(i, v)),
@@ -112,3 +136,44 @@ fn compare_map_to_hash_map() {
         compare_collections(&db, actions, compare_map)?;
     });
 }
+
+#[test]
+fn compare_map_to_hash_map_incrementally() {
+    let db = TemporaryDB::new();
+    proptest!(|(ref actions in vec(generate_action(), 1..ACTIONS_MAX_LEN * 10))| {
+        compare_collections_incremental(&db, actions, compare_map, compare_map_entry)?;
+    });
+}
+
+#[test]
+fn drain_map_index() {
+    let db = TemporaryDB::new();
+    let entries = vec((num::u8::ANY, num::i32::ANY), 0..50);
+    proptest!(|(entries in entries, merge in proptest::bool::ANY, take in 0..60_usize)| {
+        let fork = db.fork();
+        let mut map = fork.get_map::<_, u8, i32>("drain");
+        let ref_map: HashMap<_, _> = entries.into_iter().collect();
+        for (key, value) in &ref_map {
+            map.put(key, *value);
+        }
+        drop(map);
+        let fork = if merge {
+            db.merge(fork.into_patch()).unwrap();
+            db.fork()
+        } else {
+            fork
+        };
+
+        let mut map = fork.get_map::<_, u8, i32>("drain");
+        let drained: Vec<_> = map.drain().take(take).collect();
+        let mut expected: Vec<_> = ref_map.clone().drain().collect();
+        expected.sort_unstable();
+        let (expected_drained, expected_rest) = expected.split_at(take.min(expected.len()));
+        prop_assert_eq!(&drained[..], expected_drained);
+        prop_assert_eq!(&map.iter().collect::<Vec<_>>()[..], expected_rest);
+
+        let rest: Vec<_> = map.drain().collect();
+        prop_assert_eq!(&rest[..], expected_rest);
+        prop_assert!(map.iter().next().is_none());
+    });
+}
@@ -4,13 +4,21 @@
 
 use modifier::Modifier;
 use proptest::{
-    collection::vec, prop_assert, prop_oneof, proptest, strategy, strategy::Strategy,
-    test_runner::TestCaseResult,
+    collection::vec, prop_assert, prop_assert_eq, prop_oneof, proptest, strategy,
+    strategy::Strategy, test_runner::TestCaseResult,
 };
 
-use std::{collections::HashSet, hash::Hash, rc::Rc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    hash::Hash,
+    ops::Bound,
+    rc::Rc,
+};
 
-use metaldb::{access::AccessExt, Fork, KeySetIndex, TemporaryDB};
+use metaldb::{
+    access::{AccessExt, CopyAccessExt},
+    Database, Fork, KeySetIndex, TemporaryDB,
+};
 
 mod common;
 
@@ -96,6 +104,8 @@ fn compare_key_set(set: &KeySetIndex<Rc<Fork>, u8>, ref_set: &HashSet<u8>) -> Te
     for k in set.iter() {
         prop_assert!(ref_set.contains(&k));
     }
+    // Redundant insertions and removals must not produce phantom entries.
+    prop_assert_eq!(set.iter().count(), ref_set.len());
     Ok(())
 }
 
@@ -106,3 +116,49 @@ fn compare_key_set_to_hash_set() {
         compare_collections(&db, actions, compare_key_set)?;
     });
 }
+
+fn generate_range() -> impl Strategy<Value = (Bound<u8>, Bound<u8>)> {
+    (0..72_u8, 0..72_u8, 0..5_u8).prop_map(|(x, y, kind)| {
+        let (lo, hi) = if x <= y { (x, y) } else { (y, x) };
+        match kind {
+            0 => (Bound::Included(lo), Bound::Excluded(hi)),
+            1 => (Bound::Included(lo), Bound::Included(hi)),
+            2 => (Bound::Excluded(lo), Bound::Included(hi)),
+            3 => (Bound::Unbounded, Bound::Excluded(hi)),
+            _ => (Bound::Included(lo), Bound::Unbounded),
+        }
+    })
+}
+
+#[test]
+fn key_set_range_matches_btree_set() {
+    proptest!(|(
+        ref items in vec(0..64_u8, 0..32),
+        ref neighbor_items in vec(0..72_u8, 0..16),
+        range in generate_range(),
+    )| {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        // Some of the items are merged to the database, and some are kept in the fork.
+        let (merged_items, pending_items) = items.split_at(items.len() / 2);
+        for &item in merged_items {
+            fork.get_key_set(("set", &1_u8)).insert(&item);
+        }
+        // Sets sharing the column family with the tested one must not influence the result.
+        for &item in neighbor_items {
+            fork.get_key_set(("set", &0_u8)).insert(&item);
+            fork.get_key_set(("set", &2_u8)).insert(&item);
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut set = fork.get_key_set::<_, u8>(("set", &1_u8));
+        for item in pending_items {
+            set.insert(item);
+        }
+
+        let ref_set: BTreeSet<_> = items.iter().copied().collect();
+        let expected: Vec<_> = ref_set.range(range).copied().collect();
+        prop_assert_eq!(set.range(range).collect::<Vec<_>>(), expected);
+    });
+}
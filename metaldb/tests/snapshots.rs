@@ -0,0 +1,28 @@
+use metaldb::{access::CopyAccessExt, DBOptions, Database, RocksDB};
+use tempfile::TempDir;
+
+#[test]
+fn snapshot_with_sequence() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open(temp_dir.path(), &DBOptions::default()).unwrap();
+
+    let fork = db.fork();
+    fork.get_entry("first").set(1_u32);
+    db.merge_sync(fork.into_patch()).unwrap();
+
+    let (snapshot, sequence) = db.snapshot_with_sequence();
+    assert_eq!(sequence, db.current_sequence());
+    assert_eq!(snapshot.get_entry::<_, u32>("first").get(), Some(1));
+
+    let fork = db.fork();
+    fork.get_entry("first").set(2_u32);
+    db.merge_sync(fork.into_patch()).unwrap();
+    assert!(db.current_sequence() > sequence);
+
+    // The snapshot is not affected by the write and still reflects the state at `sequence`.
+    assert_eq!(snapshot.get_entry::<_, u32>("first").get(), Some(1));
+
+    let (snapshot, new_sequence) = db.snapshot_with_sequence();
+    assert_eq!(new_sequence, db.current_sequence());
+    assert_eq!(snapshot.get_entry::<_, u32>("first").get(), Some(2));
+}
@@ -0,0 +1,172 @@
+// cspell:ignore oneof
+
+//! Property testing for sparse list index as a rust collection.
+
+use modifier::Modifier;
+use proptest::{
+    collection::vec, num, prop_assert, prop_assert_eq, prop_oneof, proptest, strategy,
+    strategy::Strategy, test_runner::TestCaseResult,
+};
+
+use std::{collections::BTreeMap, rc::Rc};
+
+use metaldb::{access::AccessExt, BinaryValue, Fork, SparseListIndex, TemporaryDB};
+
+mod common;
+
+use crate::common::{compare_collections, AsForkAction, ForkAction, FromFork, ACTIONS_MAX_LEN};
+
+/// Positions of elements modified by actions are taken modulo this value, so that
+/// the actions hit both existing elements and positions beyond the list capacity.
+const MAX_INDEX: u64 = 64;
+
+#[derive(Debug, Clone)]
+enum SparseListAction<V> {
+    Push(V),
+    Pop,
+    // Applied to index modulo `MAX_INDEX`.
+    Set(u64, V),
+    // Applied to index modulo `MAX_INDEX`.
+    SetIfAbsent(u64, V),
+    // Applied to index modulo `MAX_INDEX`.
+    GetOrInsertWith(u64, V),
+    // Applied to index modulo `MAX_INDEX`.
+    Remove(u64),
+    Clear,
+    MergeFork,
+}
+
+impl<V> AsForkAction for SparseListAction<V> {
+    fn as_fork_action(&self) -> Option<ForkAction> {
+        match self {
+            SparseListAction::MergeFork => Some(ForkAction::Merge),
+            _ => None,
+        }
+    }
+}
+
+/// Reference implementation of a sparse list.
+#[derive(Debug, Default)]
+struct RefSparseList<V> {
+    items: BTreeMap<u64, V>,
+    capacity: u64,
+}
+
+impl<V> RefSparseList<V> {
+    fn insert(&mut self, index: u64, value: V) {
+        self.capacity = self.capacity.max(index + 1);
+        self.items.insert(index, value);
+    }
+}
+
+impl<V> Modifier<RefSparseList<V>> for SparseListAction<V> {
+    fn modify(self, list: &mut RefSparseList<V>) {
+        match self {
+            SparseListAction::Push(val) => {
+                let index = list.capacity;
+                list.insert(index, val);
+            }
+            SparseListAction::Pop => {
+                let first_index = list.items.keys().next().copied();
+                if let Some(index) = first_index {
+                    list.items.remove(&index);
+                }
+            }
+            SparseListAction::Set(idx, val) => {
+                list.insert(idx % MAX_INDEX, val);
+            }
+            SparseListAction::SetIfAbsent(idx, val)
+            | SparseListAction::GetOrInsertWith(idx, val) => {
+                let idx = idx % MAX_INDEX;
+                if !list.items.contains_key(&idx) {
+                    list.insert(idx, val);
+                }
+            }
+            SparseListAction::Remove(idx) => {
+                list.items.remove(&(idx % MAX_INDEX));
+            }
+            SparseListAction::Clear => {
+                list.items.clear();
+                list.capacity = 0;
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<V: BinaryValue> Modifier<SparseListIndex<Rc<Fork>, V>> for SparseListAction<V> {
+    fn modify(self, list: &mut SparseListIndex<Rc<Fork>, V>) {
+        match self {
+            SparseListAction::Push(val) => {
+                list.push(val);
+            }
+            SparseListAction::Pop => {
+                list.pop();
+            }
+            SparseListAction::Set(idx, val) => {
+                list.set(idx % MAX_INDEX, val);
+            }
+            SparseListAction::SetIfAbsent(idx, val) => {
+                let idx = idx % MAX_INDEX;
+                let was_absent = list.get(idx).is_none();
+                assert_eq!(list.set_if_absent(idx, val), was_absent);
+            }
+            SparseListAction::GetOrInsertWith(idx, val) => {
+                list.get_or_insert_with(idx % MAX_INDEX, || val);
+            }
+            SparseListAction::Remove(idx) => {
+                list.remove(idx % MAX_INDEX);
+            }
+            SparseListAction::Clear => {
+                list.clear();
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<V: BinaryValue> FromFork for SparseListIndex<Rc<Fork>, V> {
+    fn from_fork(fork: Rc<Fork>) -> Self {
+        fork.get_sparse_list("test")
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+}
+
+fn generate_action() -> impl Strategy<Value = SparseListAction<i32>> {
+    prop_oneof![
+        num::i32::ANY.prop_map(SparseListAction::Push),
+        strategy::Just(SparseListAction::Pop),
+        (num::u64::ANY, num::i32::ANY).prop_map(|(i, v)| SparseListAction::Set(i, v)),
+        (num::u64::ANY, num::i32::ANY).prop_map(|(i, v)| SparseListAction::SetIfAbsent(i, v)),
+        (num::u64::ANY, num::i32::ANY).prop_map(|(i, v)| SparseListAction::GetOrInsertWith(i, v)),
+        num::u64::ANY.prop_map(SparseListAction::Remove),
+        strategy::Just(SparseListAction::Clear),
+        strategy::Just(SparseListAction::MergeFork),
+    ]
+}
+
+fn compare_sparse_list(
+    list: &SparseListIndex<Rc<Fork>, i32>,
+    ref_list: &RefSparseList<i32>,
+) -> TestCaseResult {
+    prop_assert!(ref_list
+        .items
+        .iter()
+        .map(|(&index, &value)| (index, value))
+        .eq(list));
+    prop_assert!(ref_list.items.keys().copied().eq(list.indexes()));
+    prop_assert_eq!(list.len(), ref_list.items.len() as u64);
+    prop_assert_eq!(list.capacity(), ref_list.capacity);
+    Ok(())
+}
+
+#[test]
+fn compare_sparse_list_to_btree_map() {
+    let db = TemporaryDB::new();
+    proptest!(|(ref actions in vec(generate_action(), 1..ACTIONS_MAX_LEN))| {
+        compare_collections(&db, actions, compare_sparse_list)?;
+    });
+}